@@ -17,7 +17,7 @@ use sha2::{Digest, Sha256};
 
 /// Bump this whenever the cached value layout or key composition changes, so old
 /// entries can never be mistaken for new ones. (FR5)
-const CACHE_FORMAT_VERSION: u32 = 1;
+const CACHE_FORMAT_VERSION: u32 = 2;
 
 /// Stable identity of a pinned plugin/processor: the pieces that pin its behavior.
 #[derive(Serialize)]
@@ -64,7 +64,7 @@ struct CacheKeyMaterial<'a> {
     template_id: &'a str,
     template_version: i64,
     images: Option<TemplateImages<'a>>,
-    answers: BTreeMap<&'a str, &'a Answer>,
+    answers: BTreeMap<&'a str, serde_json::Value>,
     deterministic_states: BTreeMap<&'a str, &'a str>,
     plugins: Vec<PinnedArtifact<'a>>,
     processors: Vec<PinnedArtifact<'a>>,
@@ -102,6 +102,27 @@ fn pinned_artifacts<'a, T>(
     out
 }
 
+/// An answer in canonical serialized form: its `{type, value}` serde shape with every
+/// nested `Object` key sorted (regardless of whether serde_json preserves insertion
+/// order) and a `-0` number folded into `0`.
+fn canonical_answer(answer: &Answer) -> serde_json::Value {
+    fn canonical(value: serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::Object(map) => {
+                let sorted: BTreeMap<String, serde_json::Value> =
+                    map.into_iter().map(|(k, v)| (k, canonical(v))).collect();
+                serde_json::Value::Object(sorted.into_iter().collect())
+            }
+            serde_json::Value::Array(arr) => {
+                serde_json::Value::Array(arr.into_iter().map(canonical).collect())
+            }
+            serde_json::Value::Number(n) if n.as_f64() == Some(0.0) => serde_json::json!(0),
+            other => other,
+        }
+    }
+    canonical(serde_json::to_value(answer).expect("answers are always serializable"))
+}
+
 /// Compute the content-addressed cache key for a node execution.
 ///
 /// `answers` is the node's full effective input (preset-merged-with-inherited
@@ -114,8 +135,10 @@ pub fn compute_key(
     answers: &std::collections::HashMap<String, Answer>,
     deterministic_states: &std::collections::HashMap<String, String>,
 ) -> String {
-    let answers_sorted: BTreeMap<&str, &Answer> =
-        answers.iter().map(|(k, v)| (k.as_str(), v)).collect();
+    let answers_sorted: BTreeMap<&str, serde_json::Value> = answers
+        .iter()
+        .map(|(k, v)| (k.as_str(), canonical_answer(v)))
+        .collect();
     let states_sorted: BTreeMap<&str, &str> = deterministic_states
         .iter()
         .map(|(k, v)| (k.as_str(), v.as_str()))
@@ -374,4 +397,27 @@ mod tests {
             "a group (no images) must differ from a template with images"
         );
     }
+
+    // Number/Object answers key canonically: object key order and the sign of zero
+    // never matter, while a number never collides with its string rendering.
+    #[test]
+    fn number_and_object_answers_are_canonical() {
+        let t = template("tid", 1);
+        let s = HashMap::new();
+        let key = |answer: Answer| compute_key(&t, &[("x".to_string(), answer)].into(), &s);
+
+        let obj = |json: &str| match serde_json::from_str(json).unwrap() {
+            serde_json::Value::Object(map) => Answer::Object(map),
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            key(obj(r#"{"a":1,"b":{"c":true,"d":"x"}}"#)),
+            key(obj(r#"{"b":{"d":"x","c":true},"a":1}"#))
+        );
+        assert_eq!(key(Answer::Number(0.0)), key(Answer::Number(-0.0)));
+        assert_ne!(
+            key(Answer::Number(8080.0)),
+            key(Answer::String("8080".to_string()))
+        );
+    }
 }
@@ -283,6 +283,7 @@ mod tests {
         let entry = CacheEntry {
             archive: b"x".to_vec(),
             state: HashMap::new(),
+            secrets: Default::default(),
         };
         cache.store(&t, "k", &entry);
         assert!(cache.lookup(&t, "k").is_none());
@@ -308,6 +309,7 @@ mod tests {
         let entry = CacheEntry {
             archive: b"hello".to_vec(),
             state: HashMap::new(),
+            secrets: Default::default(),
         };
         // A valid 64-char lowercase-hex key (the only shape the store accepts).
         let k = "deadbeef".repeat(8);
@@ -8,12 +8,13 @@
 //! (created `0600`), `sync_all`, `rename`, then a parent fsync; any write error
 //! is swallowed so a cache fault never aborts a run (FR8, FR9).
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 
 use cyanprompt::domain::models::answer::Answer;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
 /// The value stored for one node: its output archive and the answers it merges
@@ -22,6 +23,15 @@ use sha2::{Digest, Sha256};
 pub struct CacheEntry {
     pub archive: Vec<u8>,
     pub state: HashMap<String, Answer>,
+    /// Ids in `state` that answered `Password` questions.
+    pub secrets: HashSet<String>,
+}
+
+/// The JSON tail of an entry's payload.
+#[derive(Serialize, Deserialize)]
+struct StoredState {
+    answers: HashMap<String, Answer>,
+    secrets: HashSet<String>,
 }
 
 /// On-disk layout (all integers little-endian):
@@ -31,7 +41,7 @@ pub struct CacheEntry {
 /// where payload =
 ///   [0..8)             archive length (u64)
 ///   [8..8+alen)        archive bytes
-///   [8+alen..]         state JSON (serde_json of HashMap<String, Answer>)
+///   [8+alen..]         state JSON (`{"answers": {id: Answer}, "secrets": [id]}`)
 const CHECKSUM_LEN: usize = 32;
 const LEN_FIELD: usize = 8;
 const HEADER_LEN: usize = CHECKSUM_LEN + LEN_FIELD;
@@ -114,7 +124,11 @@ impl CacheStore {
     }
 
     fn encode(entry: &CacheEntry) -> Vec<u8> {
-        let state_json = serde_json::to_vec(&entry.state).unwrap_or_default();
+        let state_json = serde_json::to_vec(&StoredState {
+            answers: entry.state.clone(),
+            secrets: entry.secrets.clone(),
+        })
+        .unwrap_or_default();
         let mut payload = Vec::with_capacity(LEN_FIELD + entry.archive.len() + state_json.len());
         payload.extend_from_slice(&(entry.archive.len() as u64).to_le_bytes());
         payload.extend_from_slice(&entry.archive);
@@ -158,9 +172,13 @@ impl CacheStore {
         let archive_end = LEN_FIELD.checked_add(archive_len)?;
         let archive = payload.get(LEN_FIELD..archive_end)?.to_vec();
         let state_bytes = payload.get(archive_end..)?;
-        let state: HashMap<String, Answer> = serde_json::from_slice(state_bytes).ok()?;
+        let stored: StoredState = serde_json::from_slice(state_bytes).ok()?;
 
-        Some(CacheEntry { archive, state })
+        Some(CacheEntry {
+            archive,
+            state: stored.answers,
+            secrets: stored.secrets,
+        })
     }
 
     /// Look up an entry by key. Any IO error / checksum mismatch / decode error
@@ -325,9 +343,11 @@ mod tests {
         let mut state = HashMap::new();
         state.insert("answer".to_string(), Answer::String("v".to_string()));
         state.insert("flag".to_string(), Answer::Bool(true));
+        state.insert("token".to_string(), Answer::String("s".to_string()));
         CacheEntry {
             archive: b"\x00\x01\x02 some archive bytes \xff".to_vec(),
             state,
+            secrets: HashSet::from(["token".to_string()]),
         }
     }
 
@@ -349,6 +369,29 @@ mod tests {
         assert_eq!(got, e);
     }
 
+    // The archive is stored verbatim, so file modes, symlinks and empty directories
+    // survive a cache hit exactly as they do a fresh execution.
+    #[test]
+    fn round_trips_archive_metadata() {
+        use crate::fs::{FileUnpacker, TarGzUnpacker};
+        use std::path::Path;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = CacheStore::new(dir.path().join("cyanprint"));
+        let e = CacheEntry {
+            archive: crate::fs::metadata_test_archive(),
+            state: HashMap::new(),
+            secrets: Default::default(),
+        };
+        store.put(&key(), &e);
+
+        let got = store.get(&key()).expect("entry should be present");
+        let vfs = TarGzUnpacker.unpack(got.archive).unwrap();
+        assert_eq!(vfs.get_meta(Path::new("gradlew")).mode, Some(0o755));
+        assert!(vfs.get_meta(Path::new("latest")).symlink);
+        assert_eq!(vfs.empty_dirs(), vec![PathBuf::from("logs")]);
+    }
+
     // AC2: flipping a byte in the stored file -> get returns None (miss), no error.
     #[test]
     fn corruption_is_a_miss() {
@@ -10,8 +10,8 @@ mod registry;
 
 pub use consensus::{ConsensusResult, determine_consensus};
 pub use models::{
-    ConflictResolution, FileConflictEntry, FileOrigin, ResolverChoice, ResolverFile, ResolverInput,
-    ResolverInstance, ResolverInstanceInfo, ResolverOutput, TemplateInfo, TemplateResolverInfo,
-    TemplateVariationInfo,
+    ConflictResolution, ContentEncoding, FileConflictEntry, FileOrigin, ResolverChoice,
+    ResolverFile, ResolverInput, ResolverInstance, ResolverInstanceInfo, ResolverOutput,
+    TemplateInfo, TemplateResolverInfo, TemplateVariationInfo,
 };
 pub use registry::ConflictFileResolverRegistry;
@@ -2,8 +2,12 @@
 //!
 //! These models match the Helium SDK structure for resolver input/output.
 
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
 use serde::{Deserialize, Serialize};
 
+use crate::fs::is_binary;
+
 /// Unique resolver instance identified by docker reference, tag, and config
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ResolverInstance {
@@ -48,6 +52,23 @@ pub struct FileOrigin {
     pub layer: i32,
 }
 
+/// How `content` is encoded on the wire. Text travels as-is; binary content is base64
+/// so it survives JSON. The field is omitted for text, keeping text payloads identical
+/// to those of resolvers that predate binary support.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentEncoding {
+    #[default]
+    Utf8,
+    Base64,
+}
+
+impl ContentEncoding {
+    fn is_utf8(&self) -> bool {
+        *self == ContentEncoding::Utf8
+    }
+}
+
 /// Single file variation from a template
 #[derive(Debug, Clone, Serialize)]
 pub struct ResolverFile {
@@ -55,10 +76,33 @@ pub struct ResolverFile {
     pub path: String,
     /// File content
     pub content: String,
+    /// Encoding of `content`; `base64` for binary variations
+    #[serde(default, skip_serializing_if = "ContentEncoding::is_utf8")]
+    pub encoding: ContentEncoding,
     /// Origin metadata
     pub origin: FileOrigin,
 }
 
+impl ResolverFile {
+    /// Build a variation from raw bytes, base64-encoding binary content.
+    pub fn from_bytes(path: String, content: &[u8], origin: FileOrigin) -> Self {
+        let (content, encoding) = if is_binary(content) {
+            (BASE64.encode(content), ContentEncoding::Base64)
+        } else {
+            (
+                String::from_utf8_lossy(content).into_owned(),
+                ContentEncoding::Utf8,
+            )
+        };
+        Self {
+            path,
+            content,
+            encoding,
+            origin,
+        }
+    }
+}
+
 /// Resolver input - matches Helium SDK ResolverInput
 #[derive(Debug, Clone, Serialize)]
 pub struct ResolverInput {
@@ -75,6 +119,19 @@ pub struct ResolverOutput {
     pub path: String,
     /// Resolved file content
     pub content: String,
+    /// Encoding of `content`; resolvers returning binary set `base64`
+    #[serde(default)]
+    pub encoding: ContentEncoding,
+}
+
+impl ResolverOutput {
+    /// The resolved content as raw bytes, decoding base64 when the resolver used it.
+    pub fn into_bytes(self) -> Result<Vec<u8>, base64::DecodeError> {
+        match self.encoding {
+            ContentEncoding::Utf8 => Ok(self.content.into_bytes()),
+            ContentEncoding::Base64 => BASE64.decode(self.content),
+        }
+    }
 }
 
 /// Resolution type for conflict tracking
@@ -144,3 +201,45 @@ impl From<&ResolverInstance> for ResolverInstanceInfo {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn origin() -> FileOrigin {
+        FileOrigin {
+            template: "alice/web".to_string(),
+            layer: 0,
+        }
+    }
+
+    #[test]
+    fn text_variation_is_sent_as_is_without_encoding_field() {
+        let file = ResolverFile::from_bytes("a.json".to_string(), b"{}", origin());
+        assert_eq!(file.content, "{}");
+        let json = serde_json::to_value(&file).unwrap();
+        assert!(json.get("encoding").is_none(), "got: {json}");
+    }
+
+    #[test]
+    fn binary_variation_is_base64_encoded() {
+        let bytes = [0x89, b'P', b'N', b'G', 0x00, 0xff];
+        let file = ResolverFile::from_bytes("logo.png".to_string(), &bytes, origin());
+        assert_eq!(file.encoding, ContentEncoding::Base64);
+        let json = serde_json::to_value(&file).unwrap();
+        assert_eq!(json["encoding"], "base64");
+        assert_eq!(BASE64.decode(file.content).unwrap(), bytes);
+    }
+
+    #[test]
+    fn output_decodes_base64_and_defaults_to_text() {
+        let text: ResolverOutput =
+            serde_json::from_str(r#"{"path":"a.txt","content":"hi"}"#).unwrap();
+        assert_eq!(text.into_bytes().unwrap(), b"hi");
+
+        let binary: ResolverOutput =
+            serde_json::from_str(r#"{"path":"a.bin","content":"AP8=","encoding":"base64"}"#)
+                .unwrap();
+        assert_eq!(binary.into_bytes().unwrap(), vec![0x00, 0xff]);
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use similar::TextDiff;
+
+use super::VirtualFileSystem;
+
+/// How a single path would change if a merged VFS were written over the local one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileChangeKind {
+    /// Present in the merged VFS only — the write would create it.
+    Added,
+    /// Present in both with different content — the write would overwrite it.
+    Modified,
+    /// Present locally only — `cleanup_deleted_files` would remove it.
+    Deleted,
+}
+
+/// One changed path between the local and merged VFS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    pub path: PathBuf,
+    pub kind: FileChangeKind,
+}
+
+/// The set of changes a MERGE+WRITE would apply to the working tree, computed purely
+/// from the local (on-disk) VFS and the merged VFS without touching disk.
+#[derive(Debug, Clone, Default)]
+pub struct VfsDiff {
+    /// Changed paths, sorted by path. Unchanged files are omitted.
+    pub changes: Vec<FileChange>,
+}
+
+impl VfsDiff {
+    /// Compare `local` (the files currently on disk) against `merged` (what would be
+    /// written). Deletions use the same rule as `cleanup_deleted_files`: a path present
+    /// locally but absent from the merged result.
+    pub fn compute(local: &VirtualFileSystem, merged: &VirtualFileSystem) -> Self {
+        let mut changes: Vec<FileChange> = Vec::new();
+
+        for path in merged.get_paths() {
+            let kind = match (local.get_file(&path), merged.get_file(&path)) {
+                (None, Some(_)) => Some(FileChangeKind::Added),
+                (Some(old), Some(new)) if old != new => Some(FileChangeKind::Modified),
+                _ => None,
+            };
+            if let Some(kind) = kind {
+                changes.push(FileChange { path, kind });
+            }
+        }
+
+        for path in deleted_paths(local, merged) {
+            changes.push(FileChange {
+                path,
+                kind: FileChangeKind::Deleted,
+            });
+        }
+
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        Self { changes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Paths of the given kind, in path order.
+    pub fn paths_of(&self, kind: FileChangeKind) -> Vec<PathBuf> {
+        self.changes
+            .iter()
+            .filter(|c| c.kind == kind)
+            .map(|c| c.path.clone())
+            .collect()
+    }
+}
+
+/// Paths present in `local` but absent from `merged` — the files a write of `merged`
+/// would leave behind as stale and `cleanup_deleted_files` removes. Sorted.
+pub fn deleted_paths(local: &VirtualFileSystem, merged: &VirtualFileSystem) -> Vec<PathBuf> {
+    let merged_paths: HashSet<PathBuf> = merged.get_paths().into_iter().collect();
+    let mut deleted: Vec<PathBuf> = local
+        .get_paths()
+        .into_iter()
+        .filter(|p| !merged_paths.contains(p))
+        .collect();
+    deleted.sort();
+    deleted
+}
+
+/// Render a git-style unified diff for one file. `None` on either side means the file
+/// does not exist there (rendered against `/dev/null`). Content that is not valid UTF-8
+/// on either side is summarised as `Binary files ... differ` instead of a line diff.
+pub fn unified_diff(path: &str, old: Option<&[u8]>, new: Option<&[u8]>) -> String {
+    let old_label = if old.is_some() {
+        format!("a/{path}")
+    } else {
+        "/dev/null".to_string()
+    };
+    let new_label = if new.is_some() {
+        format!("b/{path}")
+    } else {
+        "/dev/null".to_string()
+    };
+
+    let old_text = std::str::from_utf8(old.unwrap_or_default());
+    let new_text = std::str::from_utf8(new.unwrap_or_default());
+    match (old_text, new_text) {
+        (Ok(old_text), Ok(new_text)) => TextDiff::from_lines(old_text, new_text)
+            .unified_diff()
+            .context_radius(3)
+            .header(&old_label, &new_label)
+            .to_string(),
+        _ => format!("Binary files {old_label} and {new_label} differ\n"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vfs(files: &[(&str, &str)]) -> VirtualFileSystem {
+        let mut vfs = VirtualFileSystem::new();
+        for (path, content) in files {
+            vfs.add_file(PathBuf::from(path), content.as_bytes().to_vec());
+        }
+        vfs
+    }
+
+    #[test]
+    fn compute_classifies_added_modified_deleted() {
+        let local = vfs(&[("same.txt", "x"), ("edit.txt", "old"), ("gone.txt", "bye")]);
+        let merged = vfs(&[("same.txt", "x"), ("edit.txt", "new"), ("new.txt", "hi")]);
+
+        let diff = VfsDiff::compute(&local, &merged);
+
+        assert_eq!(
+            diff.changes,
+            vec![
+                FileChange {
+                    path: PathBuf::from("edit.txt"),
+                    kind: FileChangeKind::Modified
+                },
+                FileChange {
+                    path: PathBuf::from("gone.txt"),
+                    kind: FileChangeKind::Deleted
+                },
+                FileChange {
+                    path: PathBuf::from("new.txt"),
+                    kind: FileChangeKind::Added
+                },
+            ]
+        );
+        assert_eq!(
+            diff.paths_of(FileChangeKind::Deleted),
+            vec![PathBuf::from("gone.txt")]
+        );
+    }
+
+    #[test]
+    fn compute_identical_is_empty() {
+        let local = vfs(&[("a.txt", "a")]);
+        assert!(VfsDiff::compute(&local, &local.clone()).is_empty());
+    }
+
+    #[test]
+    fn unified_diff_renders_headers_and_hunks() {
+        let out = unified_diff("a.txt", Some(b"one\ntwo\n"), Some(b"one\nthree\n"));
+        assert!(out.contains("--- a/a.txt"), "got:\n{out}");
+        assert!(out.contains("+++ b/a.txt"), "got:\n{out}");
+        assert!(out.contains("-two"), "got:\n{out}");
+        assert!(out.contains("+three"), "got:\n{out}");
+    }
+
+    #[test]
+    fn unified_diff_uses_dev_null_for_missing_side() {
+        let out = unified_diff("new.txt", None, Some(b"hello\n"));
+        assert!(out.contains("--- /dev/null"), "got:\n{out}");
+        assert!(out.contains("+++ b/new.txt"), "got:\n{out}");
+    }
+
+    #[test]
+    fn unified_diff_summarises_binary_content() {
+        let out = unified_diff("img.png", Some(&[0xff, 0xfe]), Some(&[0x00, 0xff]));
+        assert_eq!(out, "Binary files a/img.png and b/img.png differ\n");
+    }
+}
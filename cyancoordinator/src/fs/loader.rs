@@ -2,8 +2,9 @@ use ignore::{DirEntry, WalkBuilder};
 use std::error::Error;
 use std::path::{Path, PathBuf};
 
-use super::VirtualFileSystem;
 use super::traits::FileLoader;
+use super::{FileMeta, VirtualFileSystem, normalize_mode};
+use crate::state::StateLocation;
 
 // DiskFileLoader implementation for loading files from disk
 pub struct DiskFileLoader;
@@ -22,11 +23,11 @@ impl FileLoader for DiskFileLoader {
                 let full_path = dir.join(path);
                 if full_path.exists()
                     && !is_git_path(&full_path)
-                    && path.file_name() != Some(".cyan_state.yaml".as_ref())
+                    && !is_pending_merge_path(path)
+                    && !StateLocation::current().is_any_state_file(path)
                 {
-                    let content = std::fs::read(&full_path)
+                    load_entry(&mut vfs, &full_path, path.clone())
                         .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
-                    vfs.add_file(path.clone(), content);
                 }
             }
         } else {
@@ -45,10 +46,23 @@ impl FileLoader for DiskFileLoader {
                             let path = entry.path();
                             let rel_path = path.strip_prefix(dir).unwrap_or(path);
 
-                            if path.is_file() {
-                                let content = std::fs::read(path)
+                            let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
+                            if is_dir {
+                                // Only empty directories need recording; the rest
+                                // are implied by the files beneath them
+                                if rel_path.as_os_str().is_empty() {
+                                    continue;
+                                }
+                                let empty = std::fs::read_dir(path)
+                                    .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?
+                                    .next()
+                                    .is_none();
+                                if empty {
+                                    vfs.add_dir(rel_path.to_path_buf());
+                                }
+                            } else {
+                                load_entry(&mut vfs, path, rel_path.to_path_buf())
                                     .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
-                                vfs.add_file(rel_path.to_path_buf(), content);
                             }
                         }
                     }
@@ -63,25 +77,67 @@ impl FileLoader for DiskFileLoader {
     }
 }
 
+// Load one file or symlink (without following it) into the VFS with its metadata
+fn load_entry(vfs: &mut VirtualFileSystem, full_path: &Path, rel: PathBuf) -> std::io::Result<()> {
+    let metadata = std::fs::symlink_metadata(full_path)?;
+    if metadata.file_type().is_symlink() {
+        let target = std::fs::read_link(full_path)?;
+        vfs.add_symlink(rel, &target);
+    } else if metadata.is_file() {
+        let content = std::fs::read(full_path)?;
+        vfs.add_file_with_meta(
+            rel,
+            content,
+            FileMeta {
+                mode: file_mode(&metadata),
+                symlink: false,
+            },
+        );
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn file_mode(metadata: &std::fs::Metadata) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(normalize_mode(metadata.permissions().mode()))
+}
+
+#[cfg(not(unix))]
+fn file_mode(_metadata: &std::fs::Metadata) -> Option<u32> {
+    None
+}
+
 // Helper function to determine if a path is in the .git directory
 fn is_git_path(path: &Path) -> bool {
     path.components().any(|c| c.as_os_str() == ".git")
 }
 
+// Helper function to determine if a project-relative path is in the project's
+// .cyan_merge directory, where a conflicted update keeps its pre-merge backups, or its
+// .cyan_txn directory of a write transaction. A nested project's are ordinary files
+fn is_pending_merge_path(rel_path: &Path) -> bool {
+    rel_path
+        .components()
+        .next()
+        .is_some_and(|c| c.as_os_str() == ".cyan_merge" || c.as_os_str() == super::TXN_DIR)
+}
+
 // Helper function to determine if an entry should be processed
-fn should_process_entry(entry: &DirEntry, _base_dir: &Path) -> bool {
+fn should_process_entry(entry: &DirEntry, base_dir: &Path) -> bool {
     // Skip the .git directory and its contents
     if is_git_path(entry.path()) {
         return false;
     }
 
-    // Skip .cyan_state.yaml files
-    if entry.file_name() == ".cyan_state.yaml" {
+    // Skip state files, this project's and nested projects'
+    let rel_path = entry.path().strip_prefix(base_dir).unwrap_or(entry.path());
+    if StateLocation::current().is_any_state_file(rel_path) {
         return false;
     }
 
-    // Skip directories, we only want files
-    if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+    // Skip pre-merge backups of a conflicted update
+    if is_pending_merge_path(rel_path) {
         return false;
     }
 
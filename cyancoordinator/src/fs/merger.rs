@@ -1,12 +1,12 @@
 use git2::{self, Oid, Repository};
 use std::error::Error;
 use std::fmt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tempfile::tempdir;
 use walkdir::WalkDir;
 
-use super::VirtualFileSystem;
-use super::traits::FileMerger;
+use super::traits::{BinaryMergePolicy, FileMerger, MergeLabels};
+use super::{FileMeta, VirtualFileSystem};
 
 /// Error types for the GitLikeMerger
 #[derive(Debug)]
@@ -50,6 +50,7 @@ impl From<String> for MergeError {
 pub struct GitLikeMerger {
     debug: bool,
     similarity_threshold: u32, // Threshold percentage for rename detection (0-100)
+    binary_policy: BinaryMergePolicy,
 }
 
 impl GitLikeMerger {
@@ -57,9 +58,81 @@ impl GitLikeMerger {
         Self {
             debug,
             similarity_threshold: similarity_threshold.min(100) as u32,
+            binary_policy: BinaryMergePolicy::default(),
         }
     }
 
+    /// Set what happens to binary files changed on both sides (default: keep local).
+    pub fn with_binary_policy(mut self, policy: BinaryMergePolicy) -> Self {
+        self.binary_policy = policy;
+        self
+    }
+
+    // Split binary paths out of the three VFS. Binary files bypass the git merge (which
+    // would line-merge or conflict-mark them) and are merged whole here instead.
+    // Returns the text-only (base, current, incoming) and the merged binary files.
+    fn merge_binary_files(
+        &self,
+        base: &VirtualFileSystem,
+        current: &VirtualFileSystem,
+        incoming: &VirtualFileSystem,
+    ) -> Result<
+        (
+            VirtualFileSystem,
+            VirtualFileSystem,
+            VirtualFileSystem,
+            VirtualFileSystem,
+        ),
+        MergeError,
+    > {
+        let mut paths: Vec<PathBuf> = base
+            .get_paths()
+            .into_iter()
+            .chain(current.get_paths())
+            .chain(incoming.get_paths())
+            .filter(|p| base.is_binary(p) || current.is_binary(p) || incoming.is_binary(p))
+            .collect();
+        paths.sort();
+        paths.dedup();
+
+        let (mut base_text, mut current_text, mut incoming_text) =
+            (base.clone(), current.clone(), incoming.clone());
+        let mut merged = VirtualFileSystem::new();
+
+        for path in paths {
+            let b = base_text.files.remove(&path);
+            let l = current_text.files.remove(&path);
+            let i = incoming_text.files.remove(&path);
+
+            let winner = if l == i || i == b {
+                l
+            } else if l == b {
+                i
+            } else {
+                // Changed on both sides, differently
+                match self.binary_policy {
+                    BinaryMergePolicy::KeepLocal => l,
+                    BinaryMergePolicy::TakeIncoming => i,
+                    BinaryMergePolicy::Fail => {
+                        return Err(MergeError::Other(format!(
+                            "binary file {} was changed both locally and by the template",
+                            path.display()
+                        )));
+                    }
+                }
+            };
+
+            if self.debug {
+                println!("🧱 Merged binary file whole: {}", path.display());
+            }
+            if let Some(content) = winner {
+                merged.add_file(path, content);
+            }
+        }
+
+        Ok((base_text, current_text, incoming_text, merged))
+    }
+
     // Create a temporary git repository and return the repository and its directory
     fn create_temp_repo(
         &self,
@@ -130,12 +203,80 @@ impl GitLikeMerger {
         Ok(commit_id)
     }
 
+    // Merge binary files whole, then the text files through git, and combine the results
+    fn perform_merge(
+        &self,
+        base: &VirtualFileSystem,
+        current: &VirtualFileSystem,
+        incoming: &VirtualFileSystem,
+        labels: &MergeLabels,
+    ) -> Result<VirtualFileSystem, MergeError> {
+        let (base, current, incoming, binary) = self.merge_binary_files(base, current, incoming)?;
+        let mut result = self.perform_git_merge(&base, &current, &incoming, labels)?;
+        for path in binary.get_paths() {
+            if let Some(content) = binary.get_file(&path) {
+                result.add_file(path, content.clone());
+            }
+        }
+        Ok(result)
+    }
+
+    // Git only sees content; merge each result entry's metadata (mode, symlink) and
+    // the set of explicit directories 3-way here, taking whichever side changed.
+    fn merge_metadata(
+        result: &mut VirtualFileSystem,
+        base: &VirtualFileSystem,
+        current: &VirtualFileSystem,
+        incoming: &VirtualFileSystem,
+    ) {
+        let meta_of = |vfs: &VirtualFileSystem, path: &Path| -> Option<FileMeta> {
+            vfs.get_file(path).map(|_| vfs.get_meta(path))
+        };
+        for path in result.get_paths() {
+            let b = meta_of(base, &path);
+            let meta = match (meta_of(current, &path), meta_of(incoming, &path)) {
+                (Some(l), Some(i)) => {
+                    if Some(l) == b {
+                        i
+                    } else {
+                        l
+                    }
+                }
+                (Some(l), None) => l,
+                (None, Some(i)) => i,
+                (None, None) => FileMeta::default(),
+            };
+            result.set_meta(path, meta);
+        }
+
+        let all_dirs: std::collections::BTreeSet<PathBuf> = base
+            .get_dirs()
+            .into_iter()
+            .chain(current.get_dirs())
+            .chain(incoming.get_dirs())
+            .collect();
+        for dir in all_dirs {
+            let (b, l, i) = (
+                base.dirs.contains(&dir),
+                current.dirs.contains(&dir),
+                incoming.dirs.contains(&dir),
+            );
+            // A directory in base survives only if neither side removed it; a new one
+            // survives if either side added it
+            let keep = if b { l && i } else { l || i };
+            if keep {
+                result.add_dir(dir);
+            }
+        }
+    }
+
     // Create branches for each VFS and perform a 3-way merge
     fn perform_git_merge(
         &self,
         base: &VirtualFileSystem,
         current: &VirtualFileSystem,
         incoming: &VirtualFileSystem,
+        labels: &MergeLabels,
     ) -> Result<VirtualFileSystem, MergeError> {
         if self.debug {
             println!("🔄 Starting Git folder-level 3-way merge");
@@ -234,7 +375,11 @@ impl GitLikeMerger {
 
         // Set up checkout options
         let mut checkout_opts = git2::build::CheckoutBuilder::new();
-        checkout_opts.force();
+        checkout_opts
+            .force()
+            .ancestor_label(&labels.base)
+            .our_label(&labels.local)
+            .their_label(&labels.incoming);
 
         // Perform the merge analysis
         let analysis = repo.merge_analysis(&[&incoming_annotated])?;
@@ -259,12 +404,25 @@ impl GitLikeMerger {
             )?;
 
             // Check if we have conflicts
-            if repo.index()?.has_conflicts() {
+            let index = repo.index()?;
+            let mut conflicted = Vec::new();
+            if index.has_conflicts() {
                 if self.debug {
                     println!("⚠️ Merge resulted in conflicts");
                 }
 
-                // In case of conflicts, we keep the conflicts in the working directory
+                // In case of conflicts, we keep the conflicts (with markers) in the working
+                // directory and remember which paths they are in
+                for conflict in index.conflicts()? {
+                    let conflict = conflict?;
+                    let entry = conflict
+                        .our
+                        .or(conflict.their)
+                        .or(conflict.ancestor)
+                        .ok_or_else(|| "conflict without any index entry".to_string())?;
+                    let path = String::from_utf8_lossy(&entry.path).into_owned();
+                    conflicted.push(PathBuf::from(path));
+                }
             } else {
                 if self.debug {
                     println!("✅ Merge successful, committing");
@@ -290,7 +448,12 @@ impl GitLikeMerger {
             }
 
             // Create a VFS from the result in the working directory
-            let result_vfs = self.read_vfs_from_dir(temp_dir.path())?;
+            let mut result_vfs = self.read_vfs_from_dir(temp_dir.path())?;
+            for path in conflicted {
+                if result_vfs.get_file(&path).is_some() {
+                    result_vfs.mark_conflicted(path);
+                }
+            }
             Ok(result_vfs)
         } else {
             Err(MergeError::Other(
@@ -339,8 +502,183 @@ impl FileMerger for GitLikeMerger {
         base: &VirtualFileSystem,
         current: &VirtualFileSystem,
         incoming: &VirtualFileSystem,
+        labels: &MergeLabels,
     ) -> Result<VirtualFileSystem, Box<dyn Error + Send>> {
-        self.perform_git_merge(base, current, incoming)
-            .map_err(|e| Box::new(e) as Box<dyn Error + Send>)
+        let mut result = self
+            .perform_merge(base, current, incoming, labels)
+            .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
+        Self::merge_metadata(&mut result, base, current, incoming);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn vfs(files: &[(&str, &str)]) -> VirtualFileSystem {
+        let mut vfs = VirtualFileSystem::new();
+        for (path, content) in files {
+            vfs.add_file(PathBuf::from(path), content.as_bytes().to_vec());
+        }
+        vfs
+    }
+
+    #[test]
+    fn clean_merge_has_no_conflicts() {
+        let base = vfs(&[("a.txt", "one\ntwo\nthree\n")]);
+        let local = vfs(&[("a.txt", "ONE\ntwo\nthree\n")]);
+        let incoming = vfs(&[("a.txt", "one\ntwo\nTHREE\n")]);
+
+        let merged = GitLikeMerger::new(false, 50)
+            .merge(&base, &local, &incoming, &MergeLabels::default())
+            .unwrap();
+
+        assert!(!merged.has_conflicts());
+        assert_eq!(
+            merged.get_file(&PathBuf::from("a.txt")).unwrap(),
+            b"ONE\ntwo\nTHREE\n"
+        );
+    }
+
+    // A file the user edited but the template no longer produces is kept as edited: it
+    // is flagged conflicted (modify/delete) but carries no markers.
+    #[test]
+    fn edited_file_deleted_upstream_is_kept() {
+        let base = vfs(&[("a.txt", "one\n"), ("b.txt", "two\n")]);
+        let local = vfs(&[("a.txt", "mine\n"), ("b.txt", "two\n")]);
+        let incoming = vfs(&[]);
+
+        let merged = GitLikeMerger::new(false, 50)
+            .merge(&base, &local, &incoming, &MergeLabels::default())
+            .unwrap();
+
+        assert_eq!(merged.get_file(&PathBuf::from("a.txt")).unwrap(), b"mine\n");
+        assert!(merged.get_file(&PathBuf::from("b.txt")).is_none());
+        assert_eq!(merged.conflicted_paths(), vec![PathBuf::from("a.txt")]);
+    }
+
+    fn bin(files: &[(&str, &[u8])]) -> VirtualFileSystem {
+        let mut vfs = VirtualFileSystem::new();
+        for (path, content) in files {
+            vfs.add_file(PathBuf::from(path), content.to_vec());
+        }
+        vfs
+    }
+
+    #[test]
+    fn binary_changed_on_one_side_takes_that_side() {
+        let base = bin(&[("logo.png", &[0x89, 0x00, 0x01])]);
+        let local = base.clone();
+        let incoming = bin(&[("logo.png", &[0x89, 0x00, 0x02])]);
+
+        let merged = GitLikeMerger::new(false, 50)
+            .merge(&base, &local, &incoming, &MergeLabels::default())
+            .unwrap();
+
+        assert_eq!(
+            merged.get_file(&PathBuf::from("logo.png")).unwrap(),
+            &vec![0x89, 0x00, 0x02]
+        );
+        assert!(!merged.has_conflicts());
+    }
+
+    #[test]
+    fn binary_changed_on_both_sides_follows_policy() {
+        let base = bin(&[("font.ttf", &[0x00, 0x01]), ("a.txt", b"text\n")]);
+        let local = bin(&[("font.ttf", &[0x00, 0x02]), ("a.txt", b"text\n")]);
+        let incoming = bin(&[("font.ttf", &[0x00, 0x03]), ("a.txt", b"text\n")]);
+        let path = PathBuf::from("font.ttf");
+        let labels = MergeLabels::default();
+
+        let keep = GitLikeMerger::new(false, 50)
+            .merge(&base, &local, &incoming, &labels)
+            .unwrap();
+        assert_eq!(keep.get_file(&path).unwrap(), &vec![0x00, 0x02]);
+        assert_eq!(keep.get_file(&PathBuf::from("a.txt")).unwrap(), b"text\n");
+
+        let take = GitLikeMerger::new(false, 50)
+            .with_binary_policy(BinaryMergePolicy::TakeIncoming)
+            .merge(&base, &local, &incoming, &labels)
+            .unwrap();
+        assert_eq!(take.get_file(&path).unwrap(), &vec![0x00, 0x03]);
+
+        let err = GitLikeMerger::new(false, 50)
+            .with_binary_policy(BinaryMergePolicy::Fail)
+            .merge(&base, &local, &incoming, &labels)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("font.ttf"), "got: {err}");
+    }
+
+    #[test]
+    fn binary_deleted_locally_stays_deleted_when_template_unchanged() {
+        let base = bin(&[("img.bin", &[0x00])]);
+        let local = VirtualFileSystem::new();
+        let incoming = base.clone();
+
+        let merged = GitLikeMerger::new(false, 50)
+            .merge(&base, &local, &incoming, &MergeLabels::default())
+            .unwrap();
+
+        assert!(merged.get_file(&PathBuf::from("img.bin")).is_none());
+    }
+
+    #[test]
+    fn metadata_takes_the_side_that_changed() {
+        let exec = FileMeta {
+            mode: Some(0o755),
+            symlink: false,
+        };
+        let plain = FileMeta {
+            mode: Some(0o644),
+            symlink: false,
+        };
+        let mut base = VirtualFileSystem::new();
+        base.add_file_with_meta(PathBuf::from("gradlew"), b"run\n".to_vec(), plain);
+        base.add_file_with_meta(PathBuf::from("tool.sh"), b"x\n".to_vec(), plain);
+        base.add_dir(PathBuf::from("logs"));
+        let mut local = base.clone();
+        local.set_meta(PathBuf::from("tool.sh"), exec);
+        let mut incoming = base.clone();
+        incoming.set_meta(PathBuf::from("gradlew"), exec);
+        incoming.add_symlink(PathBuf::from("latest"), Path::new("gradlew"));
+        incoming.add_dir(PathBuf::from("cache"));
+        local.dirs.remove(&PathBuf::from("logs"));
+
+        let merged = GitLikeMerger::new(false, 50)
+            .merge(&base, &local, &incoming, &MergeLabels::default())
+            .unwrap();
+
+        assert_eq!(merged.get_meta(Path::new("gradlew")), exec);
+        assert_eq!(merged.get_meta(Path::new("tool.sh")), exec);
+        assert!(merged.get_meta(Path::new("latest")).symlink);
+        assert_eq!(merged.get_dirs(), vec![PathBuf::from("cache")]);
+    }
+
+    #[test]
+    fn conflicting_hunk_gets_labelled_markers() {
+        let base = vfs(&[("a.txt", "value\n"), ("b.txt", "same\n")]);
+        let local = vfs(&[("a.txt", "mine\n"), ("b.txt", "same\n")]);
+        let incoming = vfs(&[("a.txt", "theirs\n"), ("b.txt", "same\n")]);
+        let labels = MergeLabels {
+            base: "alice/web:1".to_string(),
+            local: "local".to_string(),
+            incoming: "alice/web:2".to_string(),
+        };
+
+        let merged = GitLikeMerger::new(false, 50)
+            .merge(&base, &local, &incoming, &labels)
+            .unwrap();
+
+        assert_eq!(merged.conflicted_paths(), vec![PathBuf::from("a.txt")]);
+        let content =
+            String::from_utf8(merged.get_file(&PathBuf::from("a.txt")).unwrap().clone()).unwrap();
+        assert_eq!(
+            content,
+            "<<<<<<< local\nmine\n=======\ntheirs\n>>>>>>> alice/web:2\n"
+        );
     }
 }
@@ -1,18 +1,26 @@
+mod diff;
 mod loader;
 mod merger;
 mod traits;
+mod transaction;
 mod unpacker;
 mod vfs;
 mod writer;
 
+pub use diff::{FileChange, FileChangeKind, VfsDiff, deleted_paths, unified_diff};
 pub use loader::DiskFileLoader;
 pub use merger::GitLikeMerger;
 pub use traits::*;
+pub use transaction::{TXN_DIR, WriteTransaction};
 pub use unpacker::TarGzUnpacker;
-pub use vfs::VirtualFileSystem;
-pub use writer::DiskFileWriter;
+pub use vfs::{FileMeta, VirtualFileSystem, is_binary, normalize_mode};
+pub use writer::{DiskFileWriter, copy_entry, remove_any};
+
+/// tar.gz fixture with an executable, a symlink and an empty directory, shared by the
+/// metadata round-trip tests.
+#[cfg(test)]
+pub(crate) use unpacker::tests::archive as metadata_test_archive;
 
-use std::collections::HashSet;
 use std::error::Error;
 use std::path::{Path, PathBuf};
 
@@ -37,6 +45,7 @@ pub trait Vfs {
         base: &VirtualFileSystem,
         local: &VirtualFileSystem,
         incoming: &VirtualFileSystem,
+        labels: &MergeLabels,
     ) -> Result<VirtualFileSystem, Box<dyn Error + Send>>;
 
     /// Write a virtual file system to disk
@@ -100,8 +109,9 @@ impl Vfs for DefaultVfs {
         base: &VirtualFileSystem,
         local: &VirtualFileSystem,
         incoming: &VirtualFileSystem,
+        labels: &MergeLabels,
     ) -> Result<VirtualFileSystem, Box<dyn Error + Send>> {
-        self.merger.merge(base, local, incoming)
+        self.merger.merge(base, local, incoming, labels)
     }
 
     fn write_to_disk(
@@ -118,11 +128,7 @@ impl Vfs for DefaultVfs {
         local_vfs: &VirtualFileSystem,
         merged_vfs: &VirtualFileSystem,
     ) -> Result<Vec<PathBuf>, Box<dyn Error + Send>> {
-        let local_paths: HashSet<PathBuf> = local_vfs.get_paths().into_iter().collect();
-        let merged_paths: HashSet<PathBuf> = merged_vfs.get_paths().into_iter().collect();
-
-        let files_to_delete: Vec<PathBuf> =
-            local_paths.difference(&merged_paths).cloned().collect();
+        let files_to_delete = deleted_paths(local_vfs, merged_vfs);
 
         if !files_to_delete.is_empty() {
             self.writer.cleanup(target_dir, &files_to_delete)?;
@@ -138,6 +144,49 @@ mod tests {
     use std::fs;
     use tempfile::tempdir;
 
+    #[test]
+    fn is_binary_detects_nul_and_invalid_utf8() {
+        assert!(!is_binary(b"plain text\n"));
+        assert!(!is_binary("héllo".as_bytes()));
+        assert!(is_binary(&[b'a', 0x00, b'b']));
+        assert!(is_binary(&[0xff, 0xfe, 0xfd]));
+
+        let mut vfs = VirtualFileSystem::new();
+        vfs.add_file(PathBuf::from("logo.png"), vec![0x89, 0x00]);
+        vfs.add_file(PathBuf::from("a.txt"), b"a".to_vec());
+        assert!(vfs.is_binary(Path::new("logo.png")));
+        assert!(!vfs.is_binary(Path::new("a.txt")));
+        assert!(!vfs.is_binary(Path::new("missing")));
+    }
+
+    #[test]
+    fn loader_skips_only_the_project_own_scratch_dirs() {
+        let dir = tempdir().unwrap();
+        let target = dir.path();
+        for path in [
+            ".cyan_merge/files/a.txt",
+            "sub/.cyan_merge/b.txt",
+            "sub/.cyan_txn/c",
+        ] {
+            let full = target.join(path);
+            fs::create_dir_all(full.parent().unwrap()).unwrap();
+            fs::write(full, b"x").unwrap();
+        }
+        fs::create_dir_all(target.join(TXN_DIR)).unwrap();
+        fs::write(target.join(TXN_DIR).join("d"), b"x").unwrap();
+
+        let vfs = DiskFileLoader.load(target, &[]).unwrap();
+        let mut paths = vfs.get_paths();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("sub/.cyan_merge/b.txt"),
+                PathBuf::from("sub/.cyan_txn/c")
+            ]
+        );
+    }
+
     #[test]
     fn test_cleanup_deleted_files_computes_diff() {
         let dir = tempdir().unwrap();
@@ -17,13 +17,73 @@ pub trait FileLoader {
     ) -> Result<VirtualFileSystem, Box<dyn Error + Send>>;
 }
 
+/// Labels written after the `<<<<<<<` / `>>>>>>>` markers of a conflicted hunk, naming
+/// where each side came from (e.g. `local` vs `alice/web:12`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeLabels {
+    pub base: String,
+    pub local: String,
+    pub incoming: String,
+}
+
+impl Default for MergeLabels {
+    fn default() -> Self {
+        Self {
+            base: "base".to_string(),
+            local: "local".to_string(),
+            incoming: "incoming".to_string(),
+        }
+    }
+}
+
+/// What a 3-way merge does with a binary file that changed both locally and in the
+/// template. Binary files are never line-merged; one side wins whole, or the merge fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BinaryMergePolicy {
+    /// Keep the local file and ignore the template's change.
+    #[default]
+    KeepLocal,
+    /// Replace the local file with the template's version.
+    TakeIncoming,
+    /// Abort the merge with an error naming the file.
+    Fail,
+}
+
+impl std::str::FromStr for BinaryMergePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keep-local" => Ok(Self::KeepLocal),
+            "take-incoming" => Ok(Self::TakeIncoming),
+            "fail" => Ok(Self::Fail),
+            other => Err(format!(
+                "unknown binary merge policy '{other}' (expected keep-local, take-incoming or fail)"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for BinaryMergePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::KeepLocal => "keep-local",
+            Self::TakeIncoming => "take-incoming",
+            Self::Fail => "fail",
+        })
+    }
+}
+
 // FileMerger trait for merging files
 pub trait FileMerger {
+    /// 3-way merge. Hunks that cannot be merged are written with standard conflict
+    /// markers labelled by `labels`, and their paths are marked conflicted on the result.
     fn merge(
         &self,
         base: &VirtualFileSystem,
         current: &VirtualFileSystem,
         incoming: &VirtualFileSystem,
+        labels: &MergeLabels,
     ) -> Result<VirtualFileSystem, Box<dyn Error + Send>>;
 }
 
@@ -0,0 +1,366 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::VirtualFileSystem;
+use super::writer::{copy_entry, remove_any};
+
+/// Directory under the project root that holds a running write transaction.
+pub const TXN_DIR: &str = ".cyan_txn";
+
+const JOURNAL: &str = "journal.json";
+const BACKUP: &str = "backup";
+const COPYING: &str = "copying";
+
+// One path the transaction touched, recorded before it is touched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JournalEntry {
+    path: PathBuf,
+    /// Whether anything lived at `path` before the transaction touched it
+    existed: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Journal {
+    entries: Vec<JournalEntry>,
+}
+
+/// An all-or-nothing update of a project directory.
+///
+/// The transaction does no writing of its own: the caller's
+/// [`FileWriter`](super::FileWriter) does, after [`prepare_write`](Self::prepare_write)
+/// or [`prepare_remove`](Self::prepare_remove) has copied every original it will
+/// replace or delete into a backup area under [`TXN_DIR`]. Each touched path is
+/// appended to an on-disk journal *before* it is touched, so a run that dies halfway
+/// can be rolled back by [`WriteTransaction::recover`] on the next invocation.
+///
+/// Dropping a transaction that was not committed rolls it back.
+#[derive(Debug)]
+pub struct WriteTransaction {
+    target_dir: PathBuf,
+    root: PathBuf,
+    journal: Journal,
+    touched: HashSet<PathBuf>,
+    finished: bool,
+}
+
+impl WriteTransaction {
+    /// Start a transaction on `target_dir`. Fails if an interrupted transaction is still
+    /// waiting to be recovered.
+    pub fn begin(target_dir: &Path) -> io::Result<Self> {
+        let root = target_dir.join(TXN_DIR);
+        if root.join(JOURNAL).exists() {
+            return Err(io::Error::other(format!(
+                "an interrupted write is pending recovery in {}",
+                root.display()
+            )));
+        }
+        // Without a journal nothing was touched: whatever is left is safe to discard
+        if root.exists() {
+            std::fs::remove_dir_all(&root)?;
+        }
+        std::fs::create_dir_all(root.join(BACKUP))?;
+
+        let txn = Self {
+            target_dir: target_dir.to_path_buf(),
+            root,
+            journal: Journal::default(),
+            touched: HashSet::new(),
+            finished: false,
+        };
+        txn.save_journal()?;
+        Ok(txn)
+    }
+
+    /// Roll back a transaction an earlier run left behind. Returns whether there was one.
+    pub fn recover(target_dir: &Path) -> io::Result<bool> {
+        let root = target_dir.join(TXN_DIR);
+        let journal_path = root.join(JOURNAL);
+        if !journal_path.exists() {
+            if root.exists() {
+                std::fs::remove_dir_all(&root)?;
+            }
+            return Ok(false);
+        }
+
+        let journal: Journal = serde_json::from_slice(&std::fs::read(&journal_path)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let txn = Self {
+            target_dir: target_dir.to_path_buf(),
+            root,
+            touched: journal.entries.iter().map(|e| e.path.clone()).collect(),
+            journal,
+            finished: false,
+        };
+        txn.rollback()?;
+        Ok(true)
+    }
+
+    /// Back up a file that will be rewritten in place outside the transaction (such as
+    /// the state file), so a rollback restores it.
+    pub fn protect(&mut self, path: &Path) -> io::Result<()> {
+        self.back_up(path)
+    }
+
+    /// Register a path the caller is about to create, so a rollback removes it.
+    pub fn track(&mut self, path: &Path) -> io::Result<()> {
+        self.record(path).map(|_| ())
+    }
+
+    /// Back up files a [`FileWriter`](super::FileWriter) is about to delete, so a
+    /// rollback restores them (and the directories their deletion leaves empty).
+    pub fn prepare_remove(&mut self, paths: &[PathBuf]) -> io::Result<()> {
+        for path in paths {
+            // `symlink_metadata` so symlinks (even dangling ones) are kept themselves
+            if std::fs::symlink_metadata(self.target_dir.join(path)).is_ok_and(|m| !m.is_dir()) {
+                self.back_up(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Back up every file a [`FileWriter`](super::FileWriter) is about to overwrite with
+    /// `vfs`, and journal the files and directories it is about to create.
+    pub fn prepare_write(&mut self, vfs: &VirtualFileSystem) -> io::Result<()> {
+        for path in vfs.files.keys() {
+            // The writer never replaces an existing (real) directory
+            if std::fs::symlink_metadata(self.target_dir.join(path)).is_ok_and(|m| m.is_dir()) {
+                continue;
+            }
+            if let Some(parent) = path.parent() {
+                self.record_missing_dirs(parent)?;
+            }
+            self.back_up(path)?;
+        }
+        for dir in vfs.empty_dirs() {
+            self.record_missing_dirs(&dir)?;
+        }
+        Ok(())
+    }
+
+    /// Keep every change and discard the backups.
+    pub fn commit(mut self) -> io::Result<()> {
+        self.finished = true;
+        // Removing the journal is the commit point: past it there is nothing to recover
+        std::fs::remove_file(self.root.join(JOURNAL))?;
+        std::fs::remove_dir_all(&self.root)
+    }
+
+    /// Undo every change, newest first, and discard the transaction.
+    pub fn rollback(mut self) -> io::Result<()> {
+        self.finished = true;
+        self.undo()
+    }
+
+    fn undo(&self) -> io::Result<()> {
+        for entry in self.journal.entries.iter().rev() {
+            let full = self.target_dir.join(&entry.path);
+            let backup = self.backup_path(&entry.path);
+            if std::fs::symlink_metadata(&backup).is_ok() {
+                remove_any(&full)?;
+                create_parent(&full)?;
+                std::fs::rename(&backup, &full)?;
+            } else if !entry.existed {
+                remove_any(&full)?;
+            }
+            // Existed but was never backed up: the crash came before it was touched
+        }
+        std::fs::remove_dir_all(&self.root)
+    }
+
+    // Journal `path` the first time it is touched; returns whether this was the first time
+    fn record(&mut self, path: &Path) -> io::Result<bool> {
+        if !self.touched.insert(path.to_path_buf()) {
+            return Ok(false);
+        }
+        let existed = std::fs::symlink_metadata(self.target_dir.join(path)).is_ok();
+        self.journal.entries.push(JournalEntry {
+            path: path.to_path_buf(),
+            existed,
+        });
+        self.save_journal()?;
+        Ok(true)
+    }
+
+    // Journal `dir`'s missing ancestors (and itself), outermost first, so a rollback
+    // removes the directories the writer creates
+    fn record_missing_dirs(&mut self, dir: &Path) -> io::Result<()> {
+        let mut missing: Vec<&Path> = dir
+            .ancestors()
+            .filter(|a| !a.as_os_str().is_empty())
+            .filter(|a| !self.target_dir.join(a).exists())
+            .collect();
+        missing.reverse();
+        for dir in missing {
+            self.record(dir)?;
+        }
+        Ok(())
+    }
+
+    // Journal `path` and, the first time it is touched, copy what lives there
+    fn back_up(&mut self, path: &Path) -> io::Result<()> {
+        if self.record(path)? {
+            let full = self.target_dir.join(path);
+            if std::fs::symlink_metadata(&full).is_ok() {
+                // Copy aside and rename into place, so a crash mid-copy never leaves a
+                // truncated backup for the rollback to restore
+                let copying = self.root.join(COPYING);
+                copy_entry(&full, &copying)?;
+                let backup = self.backup_path(path);
+                create_parent(&backup)?;
+                std::fs::rename(&copying, &backup)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn backup_path(&self, path: &Path) -> PathBuf {
+        self.root.join(BACKUP).join(path)
+    }
+
+    // Write the journal next to itself and rename it over, so it is never half-written
+    fn save_journal(&self) -> io::Result<()> {
+        let json = serde_json::to_vec_pretty(&self.journal).map_err(io::Error::other)?;
+        let tmp = self.root.join(format!("{JOURNAL}.tmp"));
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, self.root.join(JOURNAL))
+    }
+}
+
+impl Drop for WriteTransaction {
+    fn drop(&mut self) {
+        if !self.finished {
+            if let Err(e) = self.undo() {
+                tracing::error!("Failed to roll back write transaction: {e}");
+            }
+        }
+    }
+}
+
+fn create_parent(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(parent) => std::fs::create_dir_all(parent),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::{DiskFileWriter, FileWriter};
+    use tempfile::tempdir;
+
+    fn project() -> tempfile::TempDir {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("keep.txt"), b"old").unwrap();
+        std::fs::create_dir_all(dir.path().join("old")).unwrap();
+        std::fs::write(dir.path().join("old/gone.txt"), b"gone").unwrap();
+        std::fs::write(dir.path().join(".cyan_state.yaml"), b"state: 1").unwrap();
+        dir
+    }
+
+    // Write through `DiskFileWriter` the way `batch_process` does through its operator
+    fn write(txn: &mut WriteTransaction, target: &Path, vfs: &VirtualFileSystem) -> bool {
+        txn.prepare_write(vfs).unwrap();
+        DiskFileWriter.write(target, vfs).is_ok()
+    }
+
+    fn update(txn: &mut WriteTransaction, target: &Path) {
+        txn.protect(Path::new(".cyan_state.yaml")).unwrap();
+        let gone = [PathBuf::from("old/gone.txt")];
+        txn.prepare_remove(&gone).unwrap();
+        DiskFileWriter.cleanup(target, &gone).unwrap();
+        let mut vfs = VirtualFileSystem::new();
+        vfs.add_file(PathBuf::from("keep.txt"), b"new".to_vec());
+        vfs.add_file(PathBuf::from("src/deep/added.txt"), b"added".to_vec());
+        assert!(write(txn, target, &vfs));
+        std::fs::write(target.join(".cyan_state.yaml"), b"state: 2").unwrap();
+    }
+
+    fn assert_untouched(target: &Path) {
+        assert_eq!(std::fs::read(target.join("keep.txt")).unwrap(), b"old");
+        assert_eq!(std::fs::read(target.join("old/gone.txt")).unwrap(), b"gone");
+        assert_eq!(
+            std::fs::read(target.join(".cyan_state.yaml")).unwrap(),
+            b"state: 1"
+        );
+        assert!(!target.join("src").exists());
+        assert!(!target.join(TXN_DIR).exists());
+    }
+
+    #[test]
+    fn commit_keeps_every_change() {
+        let dir = project();
+        let mut txn = WriteTransaction::begin(dir.path()).unwrap();
+        update(&mut txn, dir.path());
+        txn.commit().unwrap();
+
+        assert_eq!(std::fs::read(dir.path().join("keep.txt")).unwrap(), b"new");
+        assert_eq!(
+            std::fs::read(dir.path().join("src/deep/added.txt")).unwrap(),
+            b"added"
+        );
+        assert!(!dir.path().join("old").exists());
+        assert!(!dir.path().join(TXN_DIR).exists());
+    }
+
+    #[test]
+    fn drop_without_commit_rolls_back() {
+        let dir = project();
+        {
+            let mut txn = WriteTransaction::begin(dir.path()).unwrap();
+            update(&mut txn, dir.path());
+        }
+        assert_untouched(dir.path());
+    }
+
+    #[test]
+    fn interrupted_run_is_recovered_from_the_journal() {
+        let dir = project();
+        let mut txn = WriteTransaction::begin(dir.path()).unwrap();
+        update(&mut txn, dir.path());
+        // Simulate the process dying: nothing runs the destructor
+        std::mem::forget(txn);
+
+        assert!(WriteTransaction::begin(dir.path()).is_err());
+        assert!(WriteTransaction::recover(dir.path()).unwrap());
+        assert_untouched(dir.path());
+        assert!(!WriteTransaction::recover(dir.path()).unwrap());
+    }
+
+    #[test]
+    fn failed_write_rolls_back_touched_paths() {
+        let dir = project();
+        let mut txn = WriteTransaction::begin(dir.path()).unwrap();
+        update(&mut txn, dir.path());
+
+        // `keep.txt` is a file, so nothing can be written beneath it
+        let mut clash = VirtualFileSystem::new();
+        clash.add_file(PathBuf::from("fresh.txt"), b"fresh".to_vec());
+        clash.add_file(PathBuf::from("keep.txt/child"), b"x".to_vec());
+        assert!(!write(&mut txn, dir.path(), &clash));
+
+        txn.rollback().unwrap();
+        assert_untouched(dir.path());
+        assert!(!dir.path().join("fresh.txt").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn deleted_symlink_is_restored_as_a_symlink() {
+        let dir = project();
+        std::os::unix::fs::symlink("keep.txt", dir.path().join("link")).unwrap();
+        let mut txn = WriteTransaction::begin(dir.path()).unwrap();
+        let link = [PathBuf::from("link")];
+        txn.prepare_remove(&link).unwrap();
+        DiskFileWriter.cleanup(dir.path(), &link).unwrap();
+        txn.rollback().unwrap();
+
+        assert_eq!(
+            std::fs::read_link(dir.path().join("link")).unwrap(),
+            PathBuf::from("keep.txt")
+        );
+        assert_untouched(dir.path());
+    }
+}
@@ -4,8 +4,8 @@ use std::io::Read;
 use std::path::Path;
 use tar::Archive;
 
-use super::VirtualFileSystem;
 use super::traits::FileUnpacker;
+use super::{FileMeta, VirtualFileSystem, normalize_mode};
 
 // TarGzUnpacker implementation for tar.gz archives
 pub struct TarGzUnpacker;
@@ -26,21 +26,48 @@ impl FileUnpacker for TarGzUnpacker {
                 .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?
                 .to_path_buf();
 
-            // Skip directories - we only want to process files
-            if entry.header().entry_type().is_dir() {
+            // Skip any .git directory files
+            if is_git_path(&path) {
                 continue;
             }
 
-            // Skip any .git directory files
-            if is_git_path(&path) {
+            let entry_type = entry.header().entry_type();
+
+            // Record directories so empty ones survive to the write
+            if entry_type.is_dir() {
+                let dir = path.components().as_path().to_path_buf();
+                if !dir.as_os_str().is_empty() && dir != Path::new(".") {
+                    vfs.add_dir(dir);
+                }
+                continue;
+            }
+
+            if entry_type.is_symlink() {
+                let target = entry
+                    .link_name()
+                    .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?
+                    .map(|t| t.to_path_buf())
+                    .unwrap_or_default();
+                vfs.add_symlink(path, &target);
                 continue;
             }
 
+            let mode = entry
+                .header()
+                .mode()
+                .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
             let mut buffer = Vec::new();
             entry
                 .read_to_end(&mut buffer)
                 .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
-            vfs.add_file(path, buffer);
+            vfs.add_file_with_meta(
+                path,
+                buffer,
+                FileMeta {
+                    mode: Some(normalize_mode(mode)),
+                    symlink: false,
+                },
+            );
         }
 
         Ok(vfs)
@@ -51,3 +78,60 @@ impl FileUnpacker for TarGzUnpacker {
 fn is_git_path(path: &Path) -> bool {
     path.components().any(|c| c.as_os_str() == ".git")
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::path::PathBuf;
+
+    /// A tar.gz with an executable, a plain file, a symlink and an empty directory.
+    pub(crate) fn archive() -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+
+        let mut header = tar::Header::new_gnu();
+        header.set_size(10);
+        header.set_mode(0o775);
+        header.set_entry_type(tar::EntryType::Regular);
+        builder
+            .append_data(&mut header, "gradlew", &b"#!/bin/sh\n"[..])
+            .unwrap();
+
+        let mut header = tar::Header::new_gnu();
+        header.set_size(4);
+        header.set_mode(0o664);
+        builder
+            .append_data(&mut header, "README", &b"read"[..])
+            .unwrap();
+
+        let mut header = tar::Header::new_gnu();
+        header.set_size(0);
+        header.set_entry_type(tar::EntryType::Symlink);
+        builder
+            .append_link(&mut header, "latest", "README")
+            .unwrap();
+
+        let mut header = tar::Header::new_gnu();
+        header.set_size(0);
+        header.set_mode(0o755);
+        header.set_entry_type(tar::EntryType::Directory);
+        builder.append_data(&mut header, "logs/", &[][..]).unwrap();
+
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn unpack_keeps_modes_symlinks_and_empty_dirs() {
+        let vfs = TarGzUnpacker.unpack(archive()).unwrap();
+
+        assert_eq!(vfs.get_meta(Path::new("gradlew")).mode, Some(0o755));
+        assert_eq!(vfs.get_meta(Path::new("README")).mode, Some(0o644));
+
+        let link = vfs.get_meta(Path::new("latest"));
+        assert!(link.symlink);
+        assert_eq!(vfs.get_file(Path::new("latest")).unwrap(), b"README");
+
+        assert_eq!(vfs.empty_dirs(), vec![PathBuf::from("logs")]);
+    }
+}
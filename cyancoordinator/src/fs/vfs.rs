@@ -1,10 +1,42 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
 
+/// How many leading bytes are inspected for a NUL byte, matching git's heuristic.
+const BINARY_SNIFF_LEN: usize = 8000;
+
+/// True when `content` should be treated as binary: it has a NUL byte within the first
+/// 8000 bytes (git's heuristic) or is not valid UTF-8. Binary content is never
+/// line-merged or lossily converted to text.
+pub fn is_binary(content: &[u8]) -> bool {
+    content.iter().take(BINARY_SNIFF_LEN).any(|b| *b == 0) || std::str::from_utf8(content).is_err()
+}
+
+/// Normalize unix permission bits the way git does: only the executable bit is
+/// tracked, so umask differences between machines never register as changes.
+pub fn normalize_mode(mode: u32) -> u32 {
+    if mode & 0o111 != 0 { 0o755 } else { 0o644 }
+}
+
+/// Metadata a VFS entry carries besides its content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FileMeta {
+    /// Normalized permission bits (`0o644`/`0o755`, see [`normalize_mode`]). `None`
+    /// leaves the writer's default.
+    pub mode: Option<u32>,
+    /// The entry is a symlink; like git, its content is the link target.
+    pub symlink: bool,
+}
+
 // VirtualFileSystem represents an in-memory file system
 #[derive(Debug, Default)]
 pub struct VirtualFileSystem {
     pub(crate) files: HashMap<PathBuf, Vec<u8>>,
+    /// Non-default metadata per file; absent means a plain file with default mode.
+    pub(crate) meta: HashMap<PathBuf, FileMeta>,
+    /// Directories that exist in their own right (written even when empty).
+    pub(crate) dirs: BTreeSet<PathBuf>,
+    /// Paths a 3-way merge left with unresolved conflict markers.
+    pub(crate) conflicts: BTreeSet<PathBuf>,
 }
 
 impl Clone for VirtualFileSystem {
@@ -13,7 +45,12 @@ impl Clone for VirtualFileSystem {
         for (path, content) in &self.files {
             new_files.insert(path.clone(), content.clone());
         }
-        Self { files: new_files }
+        Self {
+            files: new_files,
+            meta: self.meta.clone(),
+            dirs: self.dirs.clone(),
+            conflicts: self.conflicts.clone(),
+        }
     }
 }
 
@@ -21,13 +58,90 @@ impl VirtualFileSystem {
     pub fn new() -> Self {
         Self {
             files: HashMap::new(),
+            meta: HashMap::new(),
+            dirs: BTreeSet::new(),
+            conflicts: BTreeSet::new(),
         }
     }
 
+    /// Add a plain file with default mode, replacing any entry (and metadata) at `path`.
     pub fn add_file(&mut self, path: PathBuf, content: Vec<u8>) {
+        self.meta.remove(&path);
         self.files.insert(path, content);
     }
 
+    /// Add a file together with its metadata.
+    pub fn add_file_with_meta(&mut self, path: PathBuf, content: Vec<u8>, meta: FileMeta) {
+        self.files.insert(path.clone(), content);
+        self.set_meta(path, meta);
+    }
+
+    /// Add a symlink at `path` pointing to `target`.
+    pub fn add_symlink(&mut self, path: PathBuf, target: &Path) {
+        let content = target.to_string_lossy().into_owned().into_bytes();
+        self.add_file_with_meta(
+            path,
+            content,
+            FileMeta {
+                mode: None,
+                symlink: true,
+            },
+        );
+    }
+
+    /// Metadata of the entry at `path` (the default for plain files and missing paths).
+    pub fn get_meta(&self, path: &Path) -> FileMeta {
+        self.meta.get(path).copied().unwrap_or_default()
+    }
+
+    pub fn set_meta(&mut self, path: PathBuf, meta: FileMeta) {
+        if meta == FileMeta::default() {
+            self.meta.remove(&path);
+        } else {
+            self.meta.insert(path, meta);
+        }
+    }
+
+    /// Copy the entry at `path` (content and metadata) from `other`. Returns `false`
+    /// when `other` has no such file.
+    pub fn copy_entry(&mut self, other: &VirtualFileSystem, path: &Path) -> bool {
+        match other.get_file(path) {
+            Some(content) => {
+                self.add_file_with_meta(path.to_path_buf(), content.clone(), other.get_meta(path));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove the entry at `path` (content, metadata and conflict mark), returning its
+    /// content.
+    pub fn remove_file(&mut self, path: &Path) -> Option<Vec<u8>> {
+        self.meta.remove(path);
+        self.conflicts.remove(path);
+        self.files.remove(path)
+    }
+
+    /// Record a directory so it is written even if no file ends up inside it.
+    pub fn add_dir(&mut self, path: PathBuf) {
+        self.dirs.insert(path);
+    }
+
+    /// Every recorded directory, sorted.
+    pub fn get_dirs(&self) -> Vec<PathBuf> {
+        self.dirs.iter().cloned().collect()
+    }
+
+    /// Recorded directories with no file beneath them — the ones a writer must create
+    /// explicitly. Sorted.
+    pub fn empty_dirs(&self) -> Vec<PathBuf> {
+        self.dirs
+            .iter()
+            .filter(|dir| !self.files.keys().any(|file| file.starts_with(dir)))
+            .cloned()
+            .collect()
+    }
+
     pub fn get_file(&self, path: &Path) -> Option<&Vec<u8>> {
         self.files.get(path)
     }
@@ -35,4 +149,26 @@ impl VirtualFileSystem {
     pub fn get_paths(&self) -> Vec<PathBuf> {
         self.files.keys().cloned().collect()
     }
+
+    /// Whether the file at `path` holds binary content (see [`is_binary`]). Missing
+    /// files are not binary.
+    pub fn is_binary(&self, path: &Path) -> bool {
+        self.files
+            .get(path)
+            .is_some_and(|content| is_binary(content))
+    }
+
+    /// Record that `path` holds a conflicted merge result (written with markers).
+    pub fn mark_conflicted(&mut self, path: PathBuf) {
+        self.conflicts.insert(path);
+    }
+
+    /// Paths left conflicted by the merge that produced this VFS, sorted.
+    pub fn conflicted_paths(&self) -> Vec<PathBuf> {
+        self.conflicts.iter().cloned().collect()
+    }
+
+    pub fn has_conflicts(&self) -> bool {
+        !self.conflicts.is_empty()
+    }
 }
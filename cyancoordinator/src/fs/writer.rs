@@ -2,8 +2,8 @@ use std::collections::BTreeSet;
 use std::error::Error;
 use std::path::{Path, PathBuf};
 
-use super::VirtualFileSystem;
 use super::traits::FileWriter;
+use super::{FileMeta, VirtualFileSystem};
 
 // DiskFileWriter implementation for writing files to disk
 pub struct DiskFileWriter;
@@ -16,9 +16,10 @@ impl FileWriter for DiskFileWriter {
     ) -> Result<(), Box<dyn Error + Send>> {
         for (path, content) in &vfs.files {
             let full_path = target_dir.join(path);
+            let existing = std::fs::symlink_metadata(&full_path).ok();
 
-            // Skip if target is an existing directory
-            if full_path.is_dir() {
+            // Skip if target is an existing (real) directory
+            if existing.as_ref().is_some_and(|m| m.is_dir()) {
                 continue;
             }
 
@@ -28,8 +29,24 @@ impl FileWriter for DiskFileWriter {
                     .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
             }
 
-            // Write file
-            std::fs::write(&full_path, content)
+            // Never write through an existing symlink, and never reuse a regular file
+            // where a symlink is wanted: replace the entry itself
+            let meta = vfs.get_meta(path);
+            if existing
+                .as_ref()
+                .is_some_and(|m| m.file_type().is_symlink() || meta.symlink)
+            {
+                std::fs::remove_file(&full_path)
+                    .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
+            }
+
+            write_entry(&full_path, content, meta)
+                .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
+        }
+
+        // Directories that must exist even though no file lives in them
+        for dir in vfs.empty_dirs() {
+            std::fs::create_dir_all(target_dir.join(dir))
                 .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
         }
 
@@ -45,7 +62,9 @@ impl FileWriter for DiskFileWriter {
 
         for path in files_to_delete {
             let full_path = target_dir.join(path);
-            if full_path.is_file() {
+            // `symlink_metadata` so symlinks (even dangling ones) are removed themselves
+            let is_file_or_link = std::fs::symlink_metadata(&full_path).is_ok_and(|m| !m.is_dir());
+            if is_file_or_link {
                 std::fs::remove_file(&full_path)
                     .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
 
@@ -89,6 +108,71 @@ impl FileWriter for DiskFileWriter {
     }
 }
 
+// Write one entry: a symlink to the target held in `content`, or a file with its mode
+fn write_entry(full_path: &Path, content: &[u8], meta: FileMeta) -> std::io::Result<()> {
+    if meta.symlink {
+        let target = PathBuf::from(String::from_utf8_lossy(content).into_owned());
+        return create_symlink(&target, full_path);
+    }
+    std::fs::write(full_path, content)?;
+    if let Some(mode) = meta.mode {
+        set_mode(full_path, mode)?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+// Symlinks need privileges on other platforms; write the target path as a file, like
+// git does with `core.symlinks = false`
+#[cfg(not(unix))]
+fn create_symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::fs::write(link, target.to_string_lossy().as_bytes())
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Copy a file or symlink as it is, mode included: a symlink is recreated rather than
+/// followed.
+pub fn copy_entry(from: &Path, to: &Path) -> std::io::Result<()> {
+    remove_any(to)?;
+    if std::fs::symlink_metadata(from)?.file_type().is_symlink() {
+        create_symlink(&std::fs::read_link(from)?, to)
+    } else {
+        std::fs::copy(from, to).map(|_| ())
+    }
+}
+
+/// Remove whatever lives at `path` (file, symlink or directory tree), if anything.
+pub fn remove_any(path: &Path) -> std::io::Result<()> {
+    match std::fs::symlink_metadata(path) {
+        Ok(m) if m.is_dir() => std::fs::remove_dir_all(path),
+        Ok(_) => std::fs::remove_file(path),
+        // Nothing can live beneath a file either
+        Err(e)
+            if matches!(
+                e.kind(),
+                std::io::ErrorKind::NotFound | std::io::ErrorKind::NotADirectory
+            ) =>
+        {
+            Ok(())
+        }
+        Err(e) => Err(e),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -151,6 +235,47 @@ mod tests {
         assert!(target.join("subdir/keep.txt").exists());
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_write_applies_modes_symlinks_and_empty_dirs() {
+        use crate::fs::{DiskFileLoader, FileLoader, FileUnpacker, TarGzUnpacker};
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let target = dir.path();
+        let vfs = TarGzUnpacker
+            .unpack(crate::fs::metadata_test_archive())
+            .unwrap();
+
+        DiskFileWriter.write(target, &vfs).unwrap();
+
+        let mode = |p: &str| {
+            std::fs::metadata(target.join(p))
+                .unwrap()
+                .permissions()
+                .mode()
+        };
+        assert_eq!(mode("gradlew") & 0o777, 0o755);
+        assert_eq!(mode("README") & 0o777, 0o644);
+        assert_eq!(
+            std::fs::read_link(target.join("latest")).unwrap(),
+            PathBuf::from("README")
+        );
+        assert!(target.join("logs").is_dir());
+
+        // Loading the written tree back yields the same entries and metadata
+        let loaded = DiskFileLoader.load(target, &[]).unwrap();
+        for path in vfs.get_paths() {
+            assert_eq!(loaded.get_file(&path), vfs.get_file(&path), "{path:?}");
+            assert_eq!(loaded.get_meta(&path), vfs.get_meta(&path), "{path:?}");
+        }
+        assert_eq!(loaded.empty_dirs(), vec![PathBuf::from("logs")]);
+
+        // Rewriting over the existing symlink replaces it rather than writing through it
+        DiskFileWriter.write(target, &vfs).unwrap();
+        assert_eq!(std::fs::read(target.join("README")).unwrap(), b"read");
+    }
+
     #[test]
     fn test_cleanup_ignores_nonexistent_files() {
         let dir = tempdir().unwrap();
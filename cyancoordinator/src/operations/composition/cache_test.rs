@@ -97,6 +97,8 @@ fn render_answers(answers: &HashMap<String, Answer>) -> String {
                 Answer::String(s) => s.clone(),
                 Answer::Bool(b) => b.to_string(),
                 Answer::StringArray(a) => a.join(","),
+                Answer::Number(n) => n.to_string(),
+                Answer::Object(o) => serde_json::Value::Object(o.clone()).to_string(),
             };
             (k, rendered)
         })
@@ -147,6 +149,7 @@ impl TemplateExecutor for CountingExecutor {
                 plugins: Vec::new(),
             },
             complete_answers,
+            Default::default(),
         );
         Ok((archive, state, session_id.to_string()))
     }
@@ -601,6 +604,7 @@ fn poisoned_entry_self_heals() {
         &crate::cache::CacheEntry {
             archive: b"not a real archive".to_vec(),
             state: HashMap::new(),
+            secrets: Default::default(),
         },
     );
     assert!(store.get(&poison_key).is_some(), "poisoned entry seeded");
@@ -44,9 +44,10 @@ impl VfsLayerer for DefaultVfsLayerer {
         // Layer each subsequent VFS (later ones overwrite earlier ones)
         for vfs in &vfs_list[1..] {
             for path in vfs.get_paths() {
-                if let Some(content) = vfs.get_file(&path) {
-                    result.add_file(path, content.clone());
-                }
+                result.copy_entry(vfs, &path);
+            }
+            for dir in vfs.get_dirs() {
+                result.add_dir(dir);
             }
         }
 
@@ -58,6 +59,21 @@ impl VfsLayerer for DefaultVfsLayerer {
     }
 }
 
+/// Give every file in `result` the metadata (mode, symlink) of the last layer that
+/// produced it, and keep every layer's explicit directories.
+fn layer_metadata(result: &mut VirtualFileSystem, vfs_list: &[VirtualFileSystem]) {
+    for path in result.get_paths() {
+        if let Some(vfs) = vfs_list.iter().rev().find(|v| v.get_file(&path).is_some()) {
+            result.set_meta(path.clone(), vfs.get_meta(&path));
+        }
+    }
+    for vfs in vfs_list {
+        for dir in vfs.get_dirs() {
+            result.add_dir(dir);
+        }
+    }
+}
+
 /// Resolver-aware VFS layerer that handles conflicts using resolvers
 ///
 /// When multiple templates produce the same file:
@@ -112,11 +128,6 @@ impl ResolverAwareLayerer {
         paths
     }
 
-    /// Convert bytes to string for resolver input
-    fn bytes_to_string(bytes: &[u8]) -> String {
-        String::from_utf8_lossy(bytes).to_string()
-    }
-
     /// Get all variations of a file from different VFS
     fn get_file_variations(
         &self,
@@ -159,13 +170,16 @@ impl ResolverAwareLayerer {
         let path_str = path.to_string_lossy();
         let files: Vec<ResolverFile> = variations
             .iter()
-            .map(|(template_info, _, content)| ResolverFile {
-                path: path_str.to_string(),
-                content: Self::bytes_to_string(content),
-                origin: FileOrigin {
-                    template: template_info.template_id.clone(),
-                    layer: template_info.layer,
-                },
+            .map(|(template_info, _, content)| {
+                // Binary variations travel base64-encoded (see `ContentEncoding`)
+                ResolverFile::from_bytes(
+                    path_str.to_string(),
+                    content,
+                    FileOrigin {
+                        template: template_info.template_id.clone(),
+                        layer: template_info.layer,
+                    },
+                )
             })
             .collect();
 
@@ -177,8 +191,13 @@ impl ResolverAwareLayerer {
         // Call resolver via HTTP
         let output = self.client.resolve_files(&resolver.id, &input)?;
 
-        // Return resolved content
-        Ok(output.content.into_bytes())
+        // Return resolved content, decoded if the resolver returned binary
+        output.into_bytes().map_err(|e| {
+            Box::new(std::io::Error::other(format!(
+                "resolver {} returned invalid base64 for {path_str}: {e}",
+                resolver.id
+            ))) as Box<dyn Error + Send>
+        })
     }
 
     /// Create a FileConflictEntry for tracking
@@ -363,6 +382,8 @@ impl VfsLayerer for ResolverAwareLayerer {
             }
         }
 
+        layer_metadata(&mut result, vfs_list);
+
         crate::cprogress!(
             self.headless,
             "🔄 Layered {} VFS outputs with resolver-aware conflict resolution",
@@ -2,7 +2,7 @@ use cyanprompt::domain::models::answer::Answer;
 use cyanprompt::domain::models::cyan::Cyan;
 use cyanprompt::domain::services::template::states::TemplateState;
 use cyanregistry::http::models::template_res::TemplateVersionRes;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::path::Path;
 
@@ -11,7 +11,7 @@ use crate::client::CyanCoordinatorClient;
 use crate::conflict_file_resolver::{
     ConflictFileResolverRegistry, FileConflictEntry, ResolverInstance, TemplateInfo,
 };
-use crate::fs::VirtualFileSystem;
+use crate::fs::{MergeLabels, VirtualFileSystem};
 use crate::operations::TemplateOperator;
 
 use super::layerer::{DefaultVfsLayerer, ResolverAwareLayerer, VfsLayerer};
@@ -166,9 +166,9 @@ impl CompositionOperator {
     }
 
     /// Cache hit/total summary across this operator's lifetime, or `None` when the
-    /// cache is disabled. Backs [`Self::print_cache_summary`] (the only external
-    /// caller) and the crate's composition tests; not part of the public API. (FR15)
-    pub(crate) fn cache_summary(&self) -> Option<(usize, usize)> {
+    /// cache is disabled. Backs [`Self::print_cache_summary`] and the `--output json`
+    /// run report. (FR15)
+    pub fn cache_summary(&self) -> Option<(usize, usize)> {
         if self.cache.enabled() {
             Some((self.cache.hits(), self.cache.total()))
         } else {
@@ -386,6 +386,7 @@ impl CompositionOperator {
                                     plugins: Vec::new(),
                                 },
                                 entry.state,
+                                entry.secrets,
                             );
                             let namespaced = namespace_template_state(
                                 &replay_state,
@@ -498,12 +499,13 @@ impl CompositionOperator {
                 // stored so a later replay can re-namespace per consuming dependency.
                 // QnA panics upstream, Err short-circuits via `?` above, and a NeedInput
                 // already returned, so failures/partials are never cached. (FR13)
-                if let (Some(key), TemplateState::Complete(_, ref answers)) =
+                if let (Some(key), TemplateState::Complete(_, ref answers, ref secrets)) =
                     (cache_key.as_ref(), &template_state)
                 {
                     let entry = CacheEntry {
                         archive: to_store.expect("to_store is Some iff cache_key is Some"),
                         state: answers.clone(),
+                        secrets: secrets.clone(),
                     };
                     self.cache.store(template, key, &entry);
                 }
@@ -592,6 +594,7 @@ impl CompositionOperator {
 
         let shared_state = CompositionState {
             shared_answers: answers.clone(),
+            secret_answers: HashSet::new(),
             shared_deterministic_states: deterministic_states.clone(),
             execution_order: Vec::new(),
             need_input: None,
@@ -653,14 +656,17 @@ impl CompositionOperator {
         Ok(result)
     }
 
-    /// 3-way merge: (base, local, incoming) -> merged.
+    /// 3-way merge: (base, local, incoming) -> merged. Conflicted hunks carry `labels`.
     pub fn merge(
         &self,
         base: &VirtualFileSystem,
         local: &VirtualFileSystem,
         incoming: &VirtualFileSystem,
+        labels: &MergeLabels,
     ) -> Result<VirtualFileSystem, Box<dyn Error + Send>> {
-        self.template_operator.vfs.merge(base, local, incoming)
+        self.template_operator
+            .vfs
+            .merge(base, local, incoming, labels)
     }
 
     /// Load local files from target directory.
@@ -794,23 +800,26 @@ fn namespace_template_state(
         TemplateState::NeedInput(question, det) => {
             TemplateState::NeedInput(rename_question(question, ns), det.clone())
         }
-        TemplateState::Complete(cyan, answers) => {
-            let namespaced: HashMap<String, Answer> = answers
-                .iter()
-                .map(|(k, v)| {
-                    let scoped = format!("{ns}/{k}");
-                    if supplied_answers.contains_key(&scoped) {
-                        // Caller targeted this dependency's answer explicitly — keep it
-                        // scoped so it cannot collide with a sibling's same-named answer.
-                        (scoped, v.clone())
-                    } else {
-                        // Global or derived answer — stays raw so it propagates to
-                        // sibling dependencies (flat-shared-state / cache replay).
-                        (k.clone(), v.clone())
-                    }
-                })
-                .collect();
-            TemplateState::Complete(cyan.clone(), namespaced)
+        TemplateState::Complete(cyan, answers, secrets) => {
+            let rekey = |k: &String| {
+                let scoped = format!("{ns}/{k}");
+                if supplied_answers.contains_key(&scoped) {
+                    // Caller targeted this dependency's answer explicitly — keep it
+                    // scoped so it cannot collide with a sibling's same-named answer.
+                    scoped
+                } else {
+                    // Global or derived answer — stays raw so it propagates to
+                    // sibling dependencies (flat-shared-state / cache replay).
+                    k.clone()
+                }
+            };
+            let namespaced: HashMap<String, Answer> =
+                answers.iter().map(|(k, v)| (rekey(k), v.clone())).collect();
+            TemplateState::Complete(
+                cyan.clone(),
+                namespaced,
+                secrets.iter().map(rekey).collect(),
+            )
         }
         // QnA is never terminal; Err carries no ids. Clone unchanged.
         other => other.clone(),
@@ -824,8 +833,8 @@ fn rename_question(
     namespace: &str,
 ) -> cyanprompt::domain::models::question::Question {
     use cyanprompt::domain::models::question::{
-        CheckboxQuestion, ConfirmQuestion, DateQuestion, PasswordQuestion, Question,
-        SelectQuestion, TextQuestion,
+        CheckboxQuestion, ConfirmQuestion, DateQuestion, EditorQuestion, ListQuestion,
+        NumberQuestion, PasswordQuestion, PathQuestion, Question, SelectQuestion, TextQuestion,
     };
 
     let prefixed = |id: &str| format!("{namespace}/{id}");
@@ -854,6 +863,22 @@ fn rename_question(
             id: prefixed(&q.id),
             ..q.clone()
         }),
+        Question::Number(q) => Question::Number(NumberQuestion {
+            id: prefixed(&q.id),
+            ..q.clone()
+        }),
+        Question::Editor(q) => Question::Editor(EditorQuestion {
+            id: prefixed(&q.id),
+            ..q.clone()
+        }),
+        Question::Path(q) => Question::Path(PathQuestion {
+            id: prefixed(&q.id),
+            ..q.clone()
+        }),
+        Question::List(q) => Question::List(ListQuestion {
+            id: prefixed(&q.id),
+            ..q.clone()
+        }),
     }
 }
 
@@ -14,11 +14,14 @@ pub struct ResolvedDependency {
 }
 
 /// Convert a serde_json::Value to an Answer enum.
-/// Returns None for unsupported types (caller should skip).
+/// Returns None for unsupported types — null and arrays that are not all strings
+/// (caller should skip).
 pub fn serde_json_value_to_answer(value: &serde_json::Value) -> Option<Answer> {
     match value {
         serde_json::Value::String(s) => Some(Answer::String(s.clone())),
         serde_json::Value::Bool(b) => Some(Answer::Bool(*b)),
+        serde_json::Value::Number(n) => n.as_f64().map(Answer::Number),
+        serde_json::Value::Object(map) => Some(Answer::Object(map.clone())),
         serde_json::Value::Array(arr) => {
             let strings: Vec<String> = arr
                 .iter()
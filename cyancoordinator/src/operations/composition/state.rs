@@ -1,6 +1,6 @@
 use cyanprompt::domain::models::answer::Answer;
 use cyanprompt::domain::models::question::Question;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::mem::discriminant;
 
@@ -8,6 +8,8 @@ use std::mem::discriminant;
 #[derive(Debug, Clone)]
 pub struct CompositionState {
     pub shared_answers: HashMap<String, Answer>,
+    /// Ids in `shared_answers` that were given to `Password` questions.
+    pub secret_answers: HashSet<String>,
     pub shared_deterministic_states: HashMap<String, String>,
     pub execution_order: Vec<String>, // Template IDs in execution order
     /// Set in headless mode when a template's Q&A reached an unanswered question.
@@ -25,6 +27,7 @@ impl CompositionState {
     pub fn new() -> Self {
         Self {
             shared_answers: HashMap::new(),
+            secret_answers: HashSet::new(),
             shared_deterministic_states: HashMap::new(),
             execution_order: Vec::new(),
             need_input: None,
@@ -46,7 +49,7 @@ impl CompositionState {
         use cyanprompt::domain::services::template::states::TemplateState;
 
         match template_state {
-            TemplateState::Complete(_, answers) => {
+            TemplateState::Complete(_, answers, secrets) => {
                 // Merge answers into shared state
                 for (key, value) in answers.iter() {
                     if let Some(existing) = self.shared_answers.get(key) {
@@ -63,6 +66,7 @@ impl CompositionState {
                     }
                     self.shared_answers.insert(key.clone(), value.clone());
                 }
+                self.secret_answers.extend(secrets.iter().cloned());
             }
             TemplateState::NeedInput(question, _) => {
                 // Headless: record the unanswered question (domain type) so the
@@ -582,16 +582,16 @@ mod tests {
         }
     }
 
-    /// Test that serde_json_value_to_answer returns None for Number values
+    /// Test that serde_json_value_to_answer converts Number values to Answer::Number
     #[test]
     fn test_serde_json_value_to_answer_number() {
         let json_number = serde_json::json!(42);
         let result = serde_json_value_to_answer(&json_number);
-        assert!(result.is_none(), "Number value should return None");
+        assert_eq!(result, Some(Answer::Number(42.0)));
 
         let json_float = serde_json::json!(42.5);
         let result_float = serde_json_value_to_answer(&json_float);
-        assert!(result_float.is_none(), "Float value should return None");
+        assert_eq!(result_float, Some(Answer::Number(42.5)));
     }
 
     /// Test that serde_json_value_to_answer returns None for Null values
@@ -610,12 +610,18 @@ mod tests {
         assert!(result.is_none(), "Mixed array should return None");
     }
 
-    /// Test that serde_json_value_to_answer returns None for object values
+    /// Test that serde_json_value_to_answer converts object values to Answer::Object
     #[test]
     fn test_serde_json_value_to_answer_object() {
-        let json_obj = serde_json::json!({"key": "value"});
+        let json_obj = serde_json::json!({"key": "value", "n": 1});
         let result = serde_json_value_to_answer(&json_obj);
-        assert!(result.is_none(), "Object value should return None");
+        match result {
+            Some(Answer::Object(map)) => {
+                assert_eq!(map["key"], "value");
+                assert_eq!(map["n"], 1);
+            }
+            other => panic!("Expected Answer::Object, got {other:?}"),
+        }
     }
 
     // =========================================================================
@@ -861,7 +867,7 @@ mod tests {
         preset.insert("string_key".to_string(), serde_json::json!("string_value"));
         preset.insert("bool_key".to_string(), serde_json::json!(true));
         preset.insert("array_key".to_string(), serde_json::json!(["a", "b", "c"]));
-        preset.insert("number_key".to_string(), serde_json::json!(42)); // Should be skipped
+        preset.insert("number_key".to_string(), serde_json::json!(42));
         preset.insert("null_key".to_string(), serde_json::json!(null)); // Should be skipped
 
         let dep_ref = TemplateVersionTemplateRefRes {
@@ -881,9 +887,10 @@ mod tests {
 
         assert_eq!(
             converted.len(),
-            3,
-            "Only String, Bool, and StringArray should be converted; Number and Null skipped"
+            4,
+            "String, Bool, StringArray and Number should be converted; Null skipped"
         );
+        assert_eq!(converted.get("number_key"), Some(&Answer::Number(42.0)));
 
         if let Some(Answer::String(s)) = converted.get("string_key") {
             assert_eq!(s, "string_value");
@@ -903,10 +910,6 @@ mod tests {
             panic!("Expected Answer::StringArray for array_key");
         }
 
-        assert!(
-            !converted.contains_key("number_key"),
-            "Number should be filtered out"
-        );
         assert!(
             !converted.contains_key("null_key"),
             "Null should be filtered out"
@@ -1710,6 +1713,7 @@ mod tests {
                             plugins: vec![],
                         },
                         HashMap::new(),
+                        Default::default(),
                     ),
                     format!("session-{idx}"),
                 ))
@@ -1786,6 +1790,7 @@ mod tests {
             default: None,
             desc: None,
             initial: None,
+            constraints: Default::default(),
             id: "name".to_string(),
         });
         let need_state = TemplateState::NeedInput(question, HashMap::new());
@@ -1832,6 +1837,7 @@ mod tests {
                 plugins: vec![],
             },
             HashMap::new(),
+            Default::default(),
         );
         let executor = StubExecutor {
             scripted: std::sync::Mutex::new(vec![(Vec::new(), complete, "s1".to_string())]),
@@ -1876,6 +1882,7 @@ mod tests {
             default: None,
             desc: None,
             initial: None,
+            constraints: Default::default(),
             id: "name".to_string(),
         });
 
@@ -1935,6 +1942,7 @@ mod tests {
                             plugins: vec![],
                         },
                         ans,
+                        Default::default(),
                     ),
                     "s".to_string(),
                 ))
@@ -2021,6 +2029,7 @@ mod tests {
             default: None,
             desc: None,
             initial: None,
+            constraints: Default::default(),
             id: "name".to_string(),
         });
         let need_state = TemplateState::NeedInput(question, HashMap::new());
@@ -2092,6 +2101,7 @@ mod tests {
                                 plugins: vec![],
                             },
                             ans,
+                            Default::default(),
                         ),
                         "s".to_string(),
                     ))
@@ -2101,6 +2111,7 @@ mod tests {
                         default: None,
                         desc: None,
                         initial: None,
+                        constraints: Default::default(),
                         id: "name".to_string(),
                     });
                     Ok((
@@ -2200,6 +2211,7 @@ mod tests {
                             plugins: vec![],
                         },
                         ans,
+                        Default::default(),
                     ),
                     "s".to_string(),
                 ))
@@ -2284,6 +2296,7 @@ mod tests {
                         plugins: vec![],
                     },
                     HashMap::new(),
+                    Default::default(),
                 ),
                 "s".to_string(),
             ))
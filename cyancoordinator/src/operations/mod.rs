@@ -5,7 +5,7 @@ use std::error::Error;
 use std::path::Path;
 use std::rc::Rc;
 
-use crate::fs::{Vfs, VirtualFileSystem};
+use crate::fs::{MergeLabels, Vfs, VirtualFileSystem};
 use crate::session::SessionIdGenerator;
 use crate::template::{TemplateExecutor, TemplateHistory};
 use cyanregistry::http::client::CyanRegistryClient;
@@ -124,7 +124,12 @@ impl TemplateOperations for TemplateOperator {
         let local_vfs = self.vfs.load_local_files(target_dir, &paths)?;
 
         // Merge with base=empty, local=target folder, incoming=VFS
-        let merged_vfs = self.vfs.merge(&base_vfs, &local_vfs, &incoming_vfs)?;
+        let merged_vfs = self.vfs.merge(
+            &base_vfs,
+            &local_vfs,
+            &incoming_vfs,
+            &MergeLabels::default(),
+        )?;
 
         // Clean up files that were deleted during merge
         let deleted = self
@@ -200,7 +205,12 @@ impl TemplateOperations for TemplateOperator {
         let local_vfs = self.vfs.load_local_files(target_dir, &all_paths)?;
 
         // Perform 3-way merge with base=prev template, local=target folder, incoming=current template
-        let merged_vfs = self.vfs.merge(&base_vfs, &local_vfs, &incoming_vfs)?;
+        let merged_vfs = self.vfs.merge(
+            &base_vfs,
+            &local_vfs,
+            &incoming_vfs,
+            &MergeLabels::default(),
+        )?;
 
         // Clean up files that were deleted during merge
         let deleted = self
@@ -282,7 +292,12 @@ impl TemplateOperations for TemplateOperator {
         let local_vfs = self.vfs.load_local_files(target_dir, &all_paths)?;
 
         // Perform 3-way merge with base=prev template, local=target folder, incoming=current template
-        let merged_vfs = self.vfs.merge(&base_vfs, &local_vfs, &incoming_vfs)?;
+        let merged_vfs = self.vfs.merge(
+            &base_vfs,
+            &local_vfs,
+            &incoming_vfs,
+            &MergeLabels::default(),
+        )?;
 
         // Clean up files that were deleted during merge
         let deleted = self
@@ -0,0 +1,263 @@
+//! Where a project's state lives and how it is serialised.
+//!
+//! The state file defaults to `.cyan_state.yaml` in the project root. Both its path
+//! (relative to the project) and its format can be changed with `--state-file` /
+//! `--state-format` (or `CYANPRINT_STATE_FILE` / `CYANPRINT_STATE_FORMAT`); the format
+//! of an existing file is always taken from its extension.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use serde_yaml::Value;
+
+/// Environment variable overriding the state file path, relative to the project.
+pub const ENV_STATE_FILE: &str = "CYANPRINT_STATE_FILE";
+/// Environment variable selecting the state file format.
+pub const ENV_STATE_FORMAT: &str = "CYANPRINT_STATE_FORMAT";
+
+/// File stem of the default state file.
+const DEFAULT_STATE_STEM: &str = ".cyan_state";
+
+/// Serialisation format of the state file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StateFormat {
+    #[default]
+    Yaml,
+    Json,
+    Toml,
+}
+
+impl StateFormat {
+    pub const ALL: [StateFormat; 3] = [StateFormat::Yaml, StateFormat::Json, StateFormat::Toml];
+
+    /// Extension written for this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            StateFormat::Yaml => "yaml",
+            StateFormat::Json => "json",
+            StateFormat::Toml => "toml",
+        }
+    }
+
+    /// The format a file's extension names, if any.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "yaml" | "yml" => Some(StateFormat::Yaml),
+            "json" => Some(StateFormat::Json),
+            "toml" => Some(StateFormat::Toml),
+            _ => None,
+        }
+    }
+
+    /// Parse a state document.
+    pub fn parse(self, text: &str) -> Result<Value, String> {
+        match self {
+            StateFormat::Yaml => serde_yaml::from_str(text).map_err(|e| e.to_string()),
+            // An empty file is an empty state whatever its format
+            _ if text.trim().is_empty() => Ok(Value::Null),
+            StateFormat::Json => serde_json::from_str(text).map_err(|e| e.to_string()),
+            StateFormat::Toml => toml::from_str(text).map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Render a state document.
+    pub fn render(self, doc: &Value) -> Result<String, String> {
+        match self {
+            StateFormat::Yaml => serde_yaml::to_string(doc).map_err(|e| e.to_string()),
+            StateFormat::Json => serde_json::to_string_pretty(doc)
+                .map(|s| s + "\n")
+                .map_err(|e| e.to_string()),
+            StateFormat::Toml => toml::to_string_pretty(doc).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for StateFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.extension())
+    }
+}
+
+impl FromStr for StateFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "yaml" | "yml" => Ok(StateFormat::Yaml),
+            "json" => Ok(StateFormat::Json),
+            "toml" => Ok(StateFormat::Toml),
+            _ => Err(format!(
+                "invalid state format '{s}': expected yaml, json or toml"
+            )),
+        }
+    }
+}
+
+/// Where the state file lives, relative to the project root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateLocation {
+    file: PathBuf,
+}
+
+static LOCATION: OnceLock<StateLocation> = OnceLock::new();
+
+impl Default for StateLocation {
+    fn default() -> Self {
+        Self::new(None, None)
+    }
+}
+
+impl StateLocation {
+    /// `file` defaults to `.cyan_state.<ext>`; an explicit `format` replaces the
+    /// file's extension.
+    pub fn new(file: Option<PathBuf>, format: Option<StateFormat>) -> Self {
+        let file = match (file, format) {
+            (Some(file), Some(format)) => file.with_extension(format.extension()),
+            (Some(file), None) => file,
+            (None, format) => PathBuf::from(DEFAULT_STATE_STEM)
+                .with_extension(format.unwrap_or_default().extension()),
+        };
+        Self { file }
+    }
+
+    /// Resolve from [`ENV_STATE_FILE`] and [`ENV_STATE_FORMAT`]; an unparsable format
+    /// is ignored.
+    pub fn from_env() -> Self {
+        let file = std::env::var_os(ENV_STATE_FILE).map(PathBuf::from);
+        let format = std::env::var(ENV_STATE_FORMAT)
+            .ok()
+            .and_then(|f| f.parse().ok());
+        Self::new(file, format)
+    }
+
+    /// Make this the location used for the rest of the process. Only the first call
+    /// takes effect; returns whether it did.
+    pub fn install(self) -> bool {
+        LOCATION.set(self).is_ok()
+    }
+
+    /// The installed location, or the one the environment selects.
+    pub fn current() -> &'static StateLocation {
+        LOCATION.get_or_init(Self::from_env)
+    }
+
+    /// The configured file, relative to the project root.
+    pub fn file(&self) -> &Path {
+        &self.file
+    }
+
+    /// Format new state is written in.
+    pub fn format(&self) -> StateFormat {
+        StateFormat::from_path(&self.file).unwrap_or_default()
+    }
+
+    /// The state file of the project at `target_dir`, relative to it: the configured
+    /// file, or the same file in another format when only that exists (e.g. after
+    /// `cyanprint state migrate`).
+    pub fn relative(&self, target_dir: &Path) -> PathBuf {
+        if target_dir.join(&self.file).exists() {
+            return self.file.clone();
+        }
+        StateFormat::ALL
+            .iter()
+            .map(|f| self.file.with_extension(f.extension()))
+            .find(|f| target_dir.join(f).exists())
+            .unwrap_or_else(|| self.file.clone())
+    }
+
+    /// [`Self::relative`], joined onto `target_dir`.
+    pub fn path(&self, target_dir: &Path) -> PathBuf {
+        target_dir.join(self.relative(target_dir))
+    }
+
+    /// Whether the project-relative `path` is this state file, in any format.
+    pub fn is_state_file(&self, path: &Path) -> bool {
+        StateFormat::from_path(path).is_some()
+            && path.with_extension("") == self.file.with_extension("")
+    }
+
+    /// Whether the project-relative `path` is this project's state file or that of a
+    /// project nested in a subdirectory.
+    pub fn is_any_state_file(&self, path: &Path) -> bool {
+        StateFormat::from_path(path).is_some()
+            && path
+                .with_extension("")
+                .ends_with(self.file.with_extension(""))
+    }
+}
+
+/// The state file of the project at `target_dir` under the [current](StateLocation::current)
+/// location.
+pub fn state_file(target_dir: &Path) -> PathBuf {
+    StateLocation::current().path(target_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn location_defaults_and_overrides() {
+        assert_eq!(
+            StateLocation::default().file(),
+            Path::new(".cyan_state.yaml")
+        );
+        let json = StateLocation::new(None, Some(StateFormat::Json));
+        assert_eq!(json.file(), Path::new(".cyan_state.json"));
+        assert_eq!(json.format(), StateFormat::Json);
+
+        let nested = StateLocation::new(Some(".config/cyan/state.yaml".into()), None);
+        assert_eq!(nested.format(), StateFormat::Yaml);
+        let nested_toml = StateLocation::new(
+            Some(".config/cyan/state.yaml".into()),
+            Some(StateFormat::Toml),
+        );
+        assert_eq!(nested_toml.file(), Path::new(".config/cyan/state.toml"));
+    }
+
+    #[test]
+    fn resolves_an_existing_file_in_another_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = StateLocation::default();
+        assert_eq!(location.relative(dir.path()), Path::new(".cyan_state.yaml"));
+
+        std::fs::write(dir.path().join(".cyan_state.json"), "{}").unwrap();
+        assert_eq!(location.relative(dir.path()), Path::new(".cyan_state.json"));
+
+        std::fs::write(dir.path().join(".cyan_state.yaml"), "").unwrap();
+        assert_eq!(location.relative(dir.path()), Path::new(".cyan_state.yaml"));
+    }
+
+    #[test]
+    fn recognises_state_files() {
+        let location = StateLocation::new(Some(".config/cyan/state.yaml".into()), None);
+        assert!(location.is_state_file(Path::new(".config/cyan/state.yaml")));
+        assert!(location.is_state_file(Path::new(".config/cyan/state.json")));
+        assert!(!location.is_state_file(Path::new(".config/cyan/state.txt")));
+        assert!(!location.is_state_file(Path::new("state.yaml")));
+        assert!(location.is_any_state_file(Path::new("sub/.config/cyan/state.toml")));
+        assert!(!location.is_any_state_file(Path::new("config/state.yaml")));
+
+        let default = StateLocation::default();
+        assert!(default.is_any_state_file(Path::new("sub/.cyan_state.yaml")));
+        assert!(!default.is_state_file(Path::new("sub/.cyan_state.yaml")));
+    }
+
+    #[test]
+    fn formats_parse_and_round_trip() {
+        assert_eq!("JSON".parse::<StateFormat>(), Ok(StateFormat::Json));
+        assert_eq!("yml".parse::<StateFormat>(), Ok(StateFormat::Yaml));
+        assert!("xml".parse::<StateFormat>().is_err());
+
+        let doc: Value =
+            serde_yaml::from_str("schema_version: 2\nalice/web:\n  active: true\n  history: []\n")
+                .unwrap();
+        for format in StateFormat::ALL {
+            let text = format.render(&doc).unwrap();
+            assert_eq!(format.parse(&text).unwrap(), doc, "{format}:\n{text}");
+            assert_eq!(format.parse("").unwrap(), Value::Null);
+        }
+    }
+}
@@ -1,8 +1,12 @@
+pub mod location;
 pub mod models;
+pub mod secrets;
 pub mod services;
 pub mod traits;
 
 // Re-export common components
+pub use location::*;
 pub use models::*;
+pub use secrets::*;
 pub use services::*;
 pub use traits::*;
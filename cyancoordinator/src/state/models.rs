@@ -1,9 +1,12 @@
 use chrono::{DateTime, Utc};
 use cyanprompt::domain::models::answer::Answer;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
 
 use crate::conflict_file_resolver::FileConflictEntry;
+use crate::state::secrets::SealedAnswer;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TemplateHistoryEntry {
@@ -11,6 +14,41 @@ pub struct TemplateHistoryEntry {
     pub time: DateTime<Utc>,
     pub answers: HashMap<String, Answer>,
     pub deterministic_states: HashMap<String, String>,
+    /// Ids of the answers given to `Password` questions. Their values are not in
+    /// `answers` (except in state written before secrets were kept out of it). `None`
+    /// for entries written before these ids were recorded: which of their answers are
+    /// secret is unknown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secrets: Option<BTreeSet<String>>,
+    /// What the [`SecretPolicy`](crate::state::SecretPolicy) kept of the secret answers.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sealed: BTreeMap<String, SealedAnswer>,
+}
+
+impl TemplateHistoryEntry {
+    /// The answers to replay when the template runs again: `answers` plus the sealed
+    /// secrets this machine can open. Secrets that were omitted, or whose key or
+    /// variable is not set here, are missing and get asked again.
+    pub fn replay_answers(&self) -> HashMap<String, Answer> {
+        let mut answers = self.answers.clone();
+        for (id, sealed) in &self.sealed {
+            if let Some(answer) = sealed.open() {
+                answers.insert(id.clone(), answer);
+            }
+        }
+        answers
+    }
+
+    /// Secret ids [`Self::replay_answers`] cannot supply.
+    pub fn missing_secrets(&self) -> BTreeSet<String> {
+        let answers = self.replay_answers();
+        self.secrets
+            .iter()
+            .flatten()
+            .filter(|id| !answers.contains_key(*id))
+            .cloned()
+            .collect()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -18,10 +56,155 @@ pub struct TemplateState {
     pub active: bool,
     pub history: Vec<TemplateHistoryEntry>,
 
-    /// Normalized, path-only snapshot of the files this template produced on the
-    /// most recent run. Empty for templates that produced nothing.
+    /// The files this template produced on the most recent run, with the hash of what
+    /// it generated for each. Empty for templates that produced nothing.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub files: Vec<String>,
+    pub files: Vec<ManagedFile>,
+
+    /// `cyanprint update` leaves a pinned template at its current version unless an
+    /// explicit `--to` version is given.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+
+    /// Caps the versions `cyanprint update` may move this template to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub constraint: Option<VersionConstraint>,
+}
+
+impl TemplateState {
+    /// The normalized paths in [`Self::files`].
+    pub fn paths(&self) -> Vec<&str> {
+        self.files.iter().map(|f| f.path.as_str()).collect()
+    }
+}
+
+/// One file a template produced: its normalized path plus the SHA-256 and size of the
+/// content the template generated for it. Entries migrated from path-only state files
+/// have neither until the template next runs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManagedFile {
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+}
+
+impl ManagedFile {
+    /// Record `path` as generated with `content`.
+    pub fn new(path: String, content: &[u8]) -> Self {
+        Self {
+            path,
+            sha256: Some(hex::encode(Sha256::digest(content))),
+            size: Some(content.len() as u64),
+        }
+    }
+
+    /// Whether `content` is exactly what was generated. Always false for entries
+    /// without a hash.
+    pub fn matches(&self, content: &[u8]) -> bool {
+        self.size == Some(content.len() as u64)
+            && self
+                .sha256
+                .as_deref()
+                .is_some_and(|h| h == hex::encode(Sha256::digest(content)))
+    }
+}
+
+/// A range of template versions, such as `<=14`, `>=3,<5` or `=12` (a bare `12` is
+/// exact too). Comma-separated bounds must all hold.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct VersionConstraint {
+    bounds: Vec<(VersionOp, i64)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VersionOp {
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl VersionOp {
+    fn symbol(self) -> &'static str {
+        match self {
+            VersionOp::Eq => "=",
+            VersionOp::Lt => "<",
+            VersionOp::Le => "<=",
+            VersionOp::Gt => ">",
+            VersionOp::Ge => ">=",
+        }
+    }
+}
+
+impl VersionConstraint {
+    /// Whether `version` satisfies every bound.
+    pub fn allows(&self, version: i64) -> bool {
+        self.bounds.iter().all(|&(op, bound)| match op {
+            VersionOp::Eq => version == bound,
+            VersionOp::Lt => version < bound,
+            VersionOp::Le => version <= bound,
+            VersionOp::Gt => version > bound,
+            VersionOp::Ge => version >= bound,
+        })
+    }
+}
+
+impl std::str::FromStr for VersionConstraint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bounds = s
+            .split(',')
+            .map(|part| {
+                let part = part.trim();
+                // Two-character operators first so `<=` is not read as `<` + `=12`
+                let (op, rest) = [
+                    ("<=", VersionOp::Le),
+                    (">=", VersionOp::Ge),
+                    ("<", VersionOp::Lt),
+                    (">", VersionOp::Gt),
+                    ("=", VersionOp::Eq),
+                ]
+                .iter()
+                .find_map(|(sym, op)| part.strip_prefix(sym).map(|rest| (*op, rest)))
+                .unwrap_or((VersionOp::Eq, part));
+                rest.trim()
+                    .parse::<i64>()
+                    .map(|v| (op, v))
+                    .map_err(|_| format!("invalid version constraint '{s}'"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { bounds })
+    }
+}
+
+impl fmt::Display for VersionConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = self
+            .bounds
+            .iter()
+            .map(|(op, v)| format!("{}{v}", op.symbol()))
+            .collect();
+        write!(f, "{}", parts.join(","))
+    }
+}
+
+impl TryFrom<String> for VersionConstraint {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<VersionConstraint> for String {
+    fn from(c: VersionConstraint) -> Self {
+        c.to_string()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -37,6 +220,26 @@ pub struct CyanState {
     /// File conflicts resolved during layering
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub file_conflicts: Vec<FileConflictEntry>,
+
+    /// Set while an update's 3-way merge has left conflict markers in the working tree
+    /// and the update has not been finalised (`cyanprint resolve`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_merge: Option<PendingMerge>,
+}
+
+/// An update whose 3-way merge could not be completed automatically. The merged files
+/// (with `<<<<<<<`/`=======`/`>>>>>>>` markers) are on disk; `resolve --continue`
+/// finalises the update once the markers are gone, `resolve --abort` rolls it back.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct PendingMerge {
+    /// Normalized paths written with conflict markers.
+    pub unresolved: Vec<String>,
+    /// Normalized paths the update created; `--abort` removes them again.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added: Vec<String>,
+    /// Post-template commands held back until the conflicts are resolved.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commands: Vec<String>,
 }
 
 impl CyanState {
@@ -45,20 +248,37 @@ impl CyanState {
     ///
     /// `managed_by_template` is keyed by `"<user>/<template>"` and contains an
     /// entry only for templates that were active this run. Every template tracked
-    /// in `self.templates` has its `files` set to its collected paths, or cleared
+    /// in `self.templates` has its `files` set to its collected files, or cleared
     /// to `[]` when absent (e.g. a deactivated template). The top-level
-    /// `managed_files` becomes the sorted, de-duplicated union across all collected
-    /// templates.
-    pub fn set_managed_files(&mut self, managed_by_template: &HashMap<String, Vec<String>>) {
+    /// `managed_files` becomes the sorted, de-duplicated union of their paths.
+    pub fn set_managed_files(&mut self, managed_by_template: &HashMap<String, Vec<ManagedFile>>) {
         for (key, ts) in self.templates.iter_mut() {
             ts.files = managed_by_template.get(key).cloned().unwrap_or_default();
         }
 
-        let mut all: Vec<String> = managed_by_template.values().flatten().cloned().collect();
+        let mut all: Vec<String> = managed_by_template
+            .values()
+            .flatten()
+            .map(|f| f.path.clone())
+            .collect();
         all.sort();
         all.dedup();
         self.managed_files = all;
     }
+
+    /// Whether the local `content` of the managed file at `path` is exactly what the
+    /// active templates generated for it on the last run, i.e. it was not edited since.
+    /// False when no active template recorded a hash for `path`, or when the templates
+    /// that produce it recorded different content.
+    pub fn is_untouched(&self, path: &str, content: &[u8]) -> bool {
+        let mut recorded = self
+            .templates
+            .values()
+            .filter(|ts| ts.active)
+            .flat_map(|ts| ts.files.iter().filter(|f| f.path == path))
+            .peekable();
+        recorded.peek().is_some() && recorded.all(|f| f.matches(content))
+    }
 }
 
 #[cfg(test)]
@@ -69,17 +289,25 @@ mod tests {
         TemplateState {
             active,
             history: Vec::new(),
-            files: files.into_iter().map(String::from).collect(),
+            files: files
+                .into_iter()
+                .map(|f| ManagedFile::new(f.to_string(), f.as_bytes()))
+                .collect(),
+            pinned: false,
+            constraint: None,
         }
     }
 
-    fn managed(pairs: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+    // Each file's content is its own path, so hashes differ per path.
+    fn managed(pairs: &[(&str, &[&str])]) -> HashMap<String, Vec<ManagedFile>> {
         pairs
             .iter()
             .map(|(k, v)| {
                 (
                     k.to_string(),
-                    v.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+                    v.iter()
+                        .map(|s| ManagedFile::new(s.to_string(), s.as_bytes()))
+                        .collect::<Vec<_>>(),
                 )
             })
             .collect()
@@ -112,12 +340,12 @@ mod tests {
             ]
         );
         assert_eq!(
-            state.templates["alice/a"].files,
-            vec!["a.txt".to_string(), "shared.txt".to_string()]
+            state.templates["alice/a"].paths(),
+            vec!["a.txt", "shared.txt"]
         );
         assert_eq!(
-            state.templates["bob/b"].files,
-            vec!["b.txt".to_string(), "shared.txt".to_string()]
+            state.templates["bob/b"].paths(),
+            vec!["b.txt", "shared.txt"]
         );
     }
 
@@ -139,7 +367,7 @@ mod tests {
         state.set_managed_files(&managed(&[("alice/a", &["a.txt"])]));
 
         assert_eq!(state.managed_files, vec!["a.txt".to_string()]);
-        assert_eq!(state.templates["alice/a"].files, vec!["a.txt".to_string()]);
+        assert_eq!(state.templates["alice/a"].paths(), vec!["a.txt"]);
         // Deactivated template's files are cleared; no stale contribution.
         assert!(state.templates["old/dead"].files.is_empty());
     }
@@ -166,7 +394,7 @@ mod tests {
         // Template drops b.txt → it disappears from both lists.
         state.set_managed_files(&managed(&[("alice/a", &["a.txt"])]));
         assert_eq!(state.managed_files, vec!["a.txt".to_string()]);
-        assert_eq!(state.templates["alice/a"].files, vec!["a.txt".to_string()]);
+        assert_eq!(state.templates["alice/a"].paths(), vec!["a.txt"]);
     }
 
     // AC8 (FR8): a pre-existing state file WITHOUT the new fields deserializes
@@ -207,6 +435,30 @@ alice/a:
         );
     }
 
+    // A pending merge round-trips under its own top-level key and is not mistaken
+    // for a template entry.
+    #[test]
+    fn pending_merge_round_trips() {
+        let mut state = CyanState::default();
+        state
+            .templates
+            .insert("alice/a".to_string(), template(true, vec![]));
+        state.pending_merge = Some(PendingMerge {
+            unresolved: vec!["src/main.rs".to_string()],
+            added: vec!["new.txt".to_string()],
+            commands: vec!["npm install".to_string()],
+        });
+
+        let yaml = serde_yaml::to_string(&state).expect("serializes");
+        let back: CyanState = serde_yaml::from_str(&yaml).expect("round-trips");
+        assert_eq!(back.pending_merge, state.pending_merge);
+        assert_eq!(back.templates.len(), 1);
+
+        state.pending_merge = None;
+        let yaml = serde_yaml::to_string(&state).expect("serializes");
+        assert!(!yaml.contains("pending_merge"), "got:\n{yaml}");
+    }
+
     // Round-trip with populated manifest preserves both lists.
     #[test]
     fn populated_manifest_round_trips() {
@@ -242,4 +494,61 @@ alice/a:
         assert!(back.templates.contains_key("alice/managed_files"));
         assert_eq!(back.templates.len(), 1);
     }
+
+    #[test]
+    fn version_constraint_parses_and_matches() {
+        let c: VersionConstraint = ">=3, <5".parse().unwrap();
+        assert!(!c.allows(2));
+        assert!(c.allows(3) && c.allows(4));
+        assert!(!c.allows(5));
+        assert_eq!(c.to_string(), ">=3,<5");
+
+        let exact: VersionConstraint = "12".parse().unwrap();
+        assert!(exact.allows(12) && !exact.allows(13));
+        assert_eq!(exact.to_string(), "=12");
+
+        assert!("<=x".parse::<VersionConstraint>().is_err());
+        assert!("".parse::<VersionConstraint>().is_err());
+    }
+
+    #[test]
+    fn pin_and_constraint_round_trip_and_default() {
+        let yaml = "active: true\nhistory: []\npinned: true\nconstraint: <=14\n";
+        let ts: TemplateState = serde_yaml::from_str(yaml).unwrap();
+        assert!(ts.pinned);
+        assert_eq!(ts.constraint, Some("<=14".parse().unwrap()));
+        assert_eq!(serde_yaml::to_string(&ts).unwrap(), yaml);
+
+        let plain: TemplateState = serde_yaml::from_str("active: true\nhistory: []\n").unwrap();
+        assert!(!plain.pinned && plain.constraint.is_none());
+        assert!(!serde_yaml::to_string(&plain).unwrap().contains("pinned"));
+    }
+
+    // A file is untouched only when every active template that produces it recorded
+    // exactly the local content.
+    #[test]
+    fn untouched_compares_recorded_hashes() {
+        let mut state = CyanState::default();
+        state.templates.insert(
+            "alice/a".to_string(),
+            template(true, vec!["a.txt", "shared.txt"]),
+        );
+        state
+            .templates
+            .insert("bob/b".to_string(), template(true, vec!["shared.txt"]));
+        state
+            .templates
+            .insert("old/dead".to_string(), template(false, vec!["a.txt"]));
+        state.templates.get_mut("old/dead").unwrap().files[0] =
+            ManagedFile::new("a.txt".to_string(), b"stale");
+
+        assert!(state.is_untouched("a.txt", b"a.txt"));
+        assert!(!state.is_untouched("a.txt", b"a.txt edited"));
+        assert!(state.is_untouched("shared.txt", b"shared.txt"));
+        assert!(!state.is_untouched("unknown.txt", b"unknown.txt"));
+
+        state.templates.get_mut("bob/b").unwrap().files[0] =
+            ManagedFile::new("shared.txt".to_string(), b"bob's version");
+        assert!(!state.is_untouched("shared.txt", b"shared.txt"));
+    }
 }
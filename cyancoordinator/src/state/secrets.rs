@@ -0,0 +1,260 @@
+//! How answers to `Password` questions are kept in the state file.
+//!
+//! The state file is meant to be committed, so secret answers never go into it in
+//! plaintext. By default they are left out and asked for again whenever the template
+//! re-runs. `--secrets encrypt` (or `CYANPRINT_SECRETS=encrypt`) stores them
+//! age-encrypted to the identity in `CYANPRINT_SECRET_KEY`; `--secrets env` records the
+//! name of an environment variable to read them from instead.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use age::x25519::Identity;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use cyanprompt::domain::models::answer::Answer;
+use serde::{Deserialize, Serialize};
+
+/// Environment variable selecting the [`SecretPolicy`].
+pub const ENV_SECRETS: &str = "CYANPRINT_SECRETS";
+/// Environment variable holding the age identity (`AGE-SECRET-KEY-1…`) secrets are
+/// encrypted to.
+pub const ENV_SECRET_KEY: &str = "CYANPRINT_SECRET_KEY";
+
+/// Prefix of the environment variables `--secrets env` reads answers from.
+const ENV_ANSWER_PREFIX: &str = "CYANPRINT_ANSWER_";
+
+/// What to keep of a secret answer in the state file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SecretPolicy {
+    /// Keep nothing; the question is asked again.
+    #[default]
+    Omit,
+    /// Keep the answer encrypted to [`ENV_SECRET_KEY`].
+    Encrypt,
+    /// Keep the name of the environment variable to read the answer from.
+    Env,
+}
+
+static POLICY: OnceLock<SecretPolicy> = OnceLock::new();
+
+impl SecretPolicy {
+    /// Resolve from [`ENV_SECRETS`]; an unparsable value is ignored.
+    pub fn from_env() -> Self {
+        std::env::var(ENV_SECRETS)
+            .ok()
+            .and_then(|p| p.parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// Make this the policy used for the rest of the process. Only the first call takes
+    /// effect; returns whether it did.
+    pub fn install(self) -> bool {
+        POLICY.set(self).is_ok()
+    }
+
+    /// The installed policy, or the one the environment selects.
+    pub fn current() -> SecretPolicy {
+        *POLICY.get_or_init(Self::from_env)
+    }
+
+    /// What to record for the answer to `id`, or `None` to record nothing.
+    pub fn seal(self, id: &str, answer: &Answer) -> Result<Option<SealedAnswer>, String> {
+        match self {
+            SecretPolicy::Omit => Ok(None),
+            SecretPolicy::Env => Ok(Some(SealedAnswer::Env {
+                env: env_var_name(id),
+            })),
+            SecretPolicy::Encrypt => {
+                let identity = secret_key()?.ok_or_else(|| {
+                    format!("--secrets encrypt needs an age identity in {ENV_SECRET_KEY}")
+                })?;
+                let plaintext = serde_json::to_vec(answer).map_err(|e| e.to_string())?;
+                let ciphertext = age::encrypt(&identity.to_public(), &plaintext)
+                    .map_err(|e| format!("failed to encrypt the answer to '{id}': {e}"))?;
+                Ok(Some(SealedAnswer::Encrypted {
+                    encrypted: STANDARD.encode(ciphertext),
+                }))
+            }
+        }
+    }
+}
+
+impl fmt::Display for SecretPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SecretPolicy::Omit => "omit",
+            SecretPolicy::Encrypt => "encrypt",
+            SecretPolicy::Env => "env",
+        })
+    }
+}
+
+impl FromStr for SecretPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "omit" => Ok(SecretPolicy::Omit),
+            "encrypt" => Ok(SecretPolicy::Encrypt),
+            "env" => Ok(SecretPolicy::Env),
+            _ => Err(format!(
+                "invalid secrets policy '{s}': expected omit, encrypt or env"
+            )),
+        }
+    }
+}
+
+/// A secret answer as recorded in state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SealedAnswer {
+    /// Base64 age ciphertext of the answer.
+    Encrypted { encrypted: String },
+    /// The environment variable holding the answer.
+    Env { env: String },
+}
+
+impl SealedAnswer {
+    /// The answer, when this machine can recover it: the key is set and matches, or the
+    /// variable is set.
+    pub fn open(&self) -> Option<Answer> {
+        match self {
+            SealedAnswer::Env { env } => std::env::var(env).ok().map(Answer::String),
+            SealedAnswer::Encrypted { encrypted } => {
+                let identity = secret_key().ok()??;
+                let ciphertext = STANDARD.decode(encrypted).ok()?;
+                let plaintext = age::decrypt(&identity, &ciphertext).ok()?;
+                serde_json::from_slice(&plaintext).ok()
+            }
+        }
+    }
+}
+
+/// The environment variable `--secrets env` reads the answer to `id` from:
+/// `CYANPRINT_ANSWER_` followed by the id upper-cased, with anything but letters and
+/// digits replaced by `_`.
+pub fn env_var_name(id: &str) -> String {
+    let suffix: String = id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{ENV_ANSWER_PREFIX}{suffix}")
+}
+
+/// The answer to `id` from its [`env_var_name`] variable, whatever policy it was recorded
+/// under: how an omitted secret is supplied without prompting.
+pub fn env_answer(id: &str) -> Option<Answer> {
+    std::env::var(env_var_name(id)).ok().map(Answer::String)
+}
+
+/// Split the `secrets` out of `answers` and seal them under `policy`. Returns what the
+/// policy keeps of them.
+pub fn seal_secrets(
+    policy: SecretPolicy,
+    answers: &mut HashMap<String, Answer>,
+    secrets: &BTreeSet<String>,
+) -> Result<BTreeMap<String, SealedAnswer>, Box<dyn Error + Send>> {
+    let mut sealed = BTreeMap::new();
+    for id in secrets {
+        let Some(answer) = answers.remove(id) else {
+            continue;
+        };
+        if let Some(s) = policy
+            .seal(id, &answer)
+            .map_err(|e| Box::new(std::io::Error::other(e)) as Box<dyn Error + Send>)?
+        {
+            sealed.insert(id.clone(), s);
+        }
+    }
+    Ok(sealed)
+}
+
+fn secret_key() -> Result<Option<Identity>, String> {
+    let Ok(key) = std::env::var(ENV_SECRET_KEY) else {
+        return Ok(None);
+    };
+    key.trim()
+        .parse::<Identity>()
+        .map(Some)
+        .map_err(|e| format!("{ENV_SECRET_KEY} is not an age identity: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use age::secrecy::ExposeSecret;
+
+    fn generate_secret_key() -> String {
+        Identity::generate().to_string().expose_secret().to_string()
+    }
+
+    #[test]
+    fn policies_parse_and_display() {
+        for policy in [SecretPolicy::Omit, SecretPolicy::Encrypt, SecretPolicy::Env] {
+            assert_eq!(policy.to_string().parse::<SecretPolicy>(), Ok(policy));
+        }
+        assert_eq!("ENV".parse::<SecretPolicy>(), Ok(SecretPolicy::Env));
+        assert!("plain".parse::<SecretPolicy>().is_err());
+        assert_eq!(env_var_name("db.password"), "CYANPRINT_ANSWER_DB_PASSWORD");
+    }
+
+    // The only test touching the key and answer variables, so no other test races it.
+    #[test]
+    fn sealed_answers_open_only_with_their_key_or_variable() {
+        let answer = Answer::String("s3cr3t".to_string());
+        let secrets = BTreeSet::from(["token".to_string()]);
+        let answers = || {
+            HashMap::from([
+                ("token".to_string(), answer.clone()),
+                ("name".to_string(), Answer::String("demo".to_string())),
+            ])
+        };
+
+        let mut omitted = answers();
+        let sealed = seal_secrets(SecretPolicy::Omit, &mut omitted, &secrets).unwrap();
+        assert!(sealed.is_empty());
+        assert_eq!(omitted.keys().collect::<Vec<_>>(), ["name"]);
+
+        std::env::remove_var(ENV_SECRET_KEY);
+        assert!(seal_secrets(SecretPolicy::Encrypt, &mut answers(), &secrets).is_err());
+
+        std::env::set_var(ENV_SECRET_KEY, generate_secret_key());
+        let mut encrypted = answers();
+        let sealed = seal_secrets(SecretPolicy::Encrypt, &mut encrypted, &secrets).unwrap();
+        assert!(!encrypted.contains_key("token"));
+        let yaml = serde_yaml::to_string(&sealed).unwrap();
+        assert!(yaml.starts_with("token:\n  encrypted: "), "got: {yaml}");
+        assert!(!yaml.contains("s3cr3t"));
+        assert_eq!(sealed["token"].open(), Some(answer.clone()));
+
+        // Another machine without the key (or with a different one) cannot open it
+        std::env::set_var(ENV_SECRET_KEY, generate_secret_key());
+        assert_eq!(sealed["token"].open(), None);
+        std::env::remove_var(ENV_SECRET_KEY);
+        assert_eq!(sealed["token"].open(), None);
+
+        let sealed = seal_secrets(SecretPolicy::Env, &mut answers(), &secrets).unwrap();
+        let var = "CYANPRINT_ANSWER_TOKEN";
+        assert_eq!(
+            sealed["token"],
+            SealedAnswer::Env {
+                env: var.to_string()
+            }
+        );
+        std::env::remove_var(var);
+        assert_eq!(sealed["token"].open(), None);
+        std::env::set_var(var, "s3cr3t");
+        assert_eq!(sealed["token"].open(), Some(answer));
+        std::env::remove_var(var);
+    }
+}
@@ -2,31 +2,166 @@ use chrono::Utc;
 use cyanprompt::domain::models::answer::Answer;
 use cyanprompt::domain::services::template::states::TemplateState;
 use cyanregistry::http::models::template_res::TemplateVersionRes;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::error::Error;
 use std::fs;
-use std::io::BufReader;
 use std::path::Path;
 
+use serde_yaml::{Mapping, Value};
+
+use crate::state::location::{StateFormat, state_file};
 use crate::state::models::{CyanState, TemplateHistoryEntry, TemplateState as YamlTemplateState};
+use crate::state::secrets::{SecretPolicy, seal_secrets};
 use crate::state::traits::{StateManager, StateReader, StateWriter};
 
-/// Default implementation of StateManager
+/// Layout version of the state file this build reads and writes, recorded in the
+/// file's top-level `schema_version` key.
+///
+/// History:
+/// - 1: no `schema_version` key; templates at the root, optionally alongside
+///   `managed_files`, `file_conflicts` and `pending_merge`, with each template's
+///   `files` a list of bare paths.
+/// - 2: `schema_version` recorded; each template's `files` entry is
+///   `{path, sha256, size}`.
+pub const STATE_SCHEMA_VERSION: u64 = 2;
+
+/// Top-level key holding the layout version.
+const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// Upgrades a state document by one version, in place.
+type Migration = fn(&mut Mapping) -> Result<(), String>;
+
+/// `MIGRATIONS[i]` upgrades a version `i + 1` document to version `i + 2`.
+const MIGRATIONS: &[Migration] = &[migrate_v1_to_v2];
+
+/// 1 → 2: bare paths in each template's `files` become `{path}` entries (hash unknown
+/// until the template next runs).
+fn migrate_v1_to_v2(doc: &mut Mapping) -> Result<(), String> {
+    for template in doc.values_mut() {
+        let Some(files) = template_files(template) else {
+            continue;
+        };
+        for file in files.iter_mut() {
+            if let Value::String(path) = file {
+                let mut entry = Mapping::new();
+                entry.insert("path".into(), Value::String(std::mem::take(path)));
+                *file = Value::Mapping(entry);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The `files` list of a root value shaped like a template entry (a mapping holding
+/// `history` or `active`); `None` for the bookkeeping keys, whatever they are named.
+fn template_files(value: &mut Value) -> Option<&mut Vec<Value>> {
+    let template = value.as_mapping_mut()?;
+    if !template.contains_key("history") && !template.contains_key("active") {
+        return None;
+    }
+    template.get_mut("files")?.as_sequence_mut()
+}
+
+/// Bring a parsed state document up to [`STATE_SCHEMA_VERSION`] and strip the version
+/// key, leaving the shape [`CyanState`] deserializes. Documents from a newer cyanprint
+/// are refused rather than misread.
+pub fn migrate_state_document(doc: Value) -> Result<Mapping, String> {
+    let mut doc = match doc {
+        Value::Mapping(m) => m,
+        // An empty file
+        Value::Null => return Ok(Mapping::new()),
+        other => {
+            return Err(format!(
+                "state file must be a mapping, found {}",
+                value_kind(&other)
+            ));
+        }
+    };
+
+    let version = match doc.remove(SCHEMA_VERSION_KEY) {
+        None => 1,
+        Some(v) => v
+            .as_u64()
+            .filter(|v| *v >= 1)
+            .ok_or_else(|| format!("invalid {SCHEMA_VERSION_KEY}: {v:?}"))?,
+    };
+    if version > STATE_SCHEMA_VERSION {
+        return Err(format!(
+            "state file has {SCHEMA_VERSION_KEY} {version}, but this cyanprint only \
+             understands up to {STATE_SCHEMA_VERSION}; upgrade cyanprint to use this project"
+        ));
+    }
+
+    for migration in &MIGRATIONS[(version - 1) as usize..] {
+        migration(&mut doc)?;
+    }
+    Ok(doc)
+}
+
+/// Serialize `state` as a current-version document, `schema_version` first.
+pub fn state_document(state: &CyanState) -> Result<Value, serde_yaml::Error> {
+    let mut doc = Mapping::new();
+    doc.insert(SCHEMA_VERSION_KEY.into(), STATE_SCHEMA_VERSION.into());
+    if let Value::Mapping(body) = serde_yaml::to_value(state)? {
+        doc.extend(body);
+    }
+    Ok(Value::Mapping(doc))
+}
+
+fn value_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Sequence(_) => "a list",
+        Value::Mapping(_) => "a mapping",
+        Value::Tagged(_) => "a tagged value",
+    }
+}
+
+fn other_error(msg: String) -> Box<dyn Error + Send> {
+    Box::new(std::io::Error::other(msg))
+}
+
+/// Default implementation of StateManager: a state file in YAML, JSON or TOML, picked
+/// by the file's extension unless a format is fixed with [`Self::with_format`].
 #[derive(Debug, Default)]
-pub struct DefaultStateManager;
+pub struct DefaultStateManager {
+    format: Option<StateFormat>,
+}
 
 impl DefaultStateManager {
     pub fn new() -> Self {
-        Self
+        Self { format: None }
+    }
+
+    /// Read and write `format` whatever the file's extension.
+    pub fn with_format(format: StateFormat) -> Self {
+        Self {
+            format: Some(format),
+        }
+    }
+
+    fn format_of(&self, path: &Path) -> StateFormat {
+        self.format
+            .or_else(|| StateFormat::from_path(path))
+            .unwrap_or_default()
     }
 }
 
 impl StateReader for DefaultStateManager {
     fn load_state_file(&self, path: &Path) -> Result<CyanState, Box<dyn Error + Send>> {
         if path.exists() {
-            let file = fs::File::open(path).map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
-            let reader = BufReader::new(file);
-            let state: CyanState = serde_yaml::from_reader(reader)
+            let text =
+                fs::read_to_string(path).map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
+            let doc = self
+                .format_of(path)
+                .parse(&text)
+                .map_err(|e| other_error(format!("{}: {e}", path.display())))?;
+            let doc = migrate_state_document(doc)
+                .map_err(|e| other_error(format!("{}: {e}", path.display())))?;
+            let state: CyanState = serde_yaml::from_value(Value::Mapping(doc))
                 .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
             Ok(state)
         } else {
@@ -42,8 +177,12 @@ impl StateWriter for DefaultStateManager {
             fs::create_dir_all(parent).map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
         }
 
-        let file = fs::File::create(path).map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
-        serde_yaml::to_writer(file, state).map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
+        let doc = state_document(state).map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
+        let text = self
+            .format_of(path)
+            .render(&doc)
+            .map_err(|e| other_error(format!("{}: {e}", path.display())))?;
+        fs::write(path, text).map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
         Ok(())
     }
 
@@ -52,10 +191,10 @@ impl StateWriter for DefaultStateManager {
         target_dir: &Path,
         template: &TemplateVersionRes,
         answers: &HashMap<String, Answer>,
-        _template_state: &TemplateState,
+        template_state: &TemplateState,
         username: &str,
     ) -> Result<(), Box<dyn Error + Send>> {
-        let state_file_path = target_dir.join(".cyan_state.yaml");
+        let state_file_path = state_file(target_dir);
 
         let mut state = self.load_state_file(&state_file_path)?;
 
@@ -63,11 +202,37 @@ impl StateWriter for DefaultStateManager {
 
         let deterministic_states = HashMap::new();
 
+        // Answers replayed from the previous run were not asked again, so they keep the
+        // secrecy recorded then
+        let previous = state
+            .templates
+            .get(&template_key)
+            .and_then(|ts| ts.history.last())
+            .and_then(|entry| entry.secrets.as_ref());
+        let asked = match template_state {
+            TemplateState::Complete(_, _, secrets) => Some(secrets),
+            _ => None,
+        };
+        let secrets = previous
+            .into_iter()
+            .flatten()
+            .chain(asked.into_iter().flatten())
+            .filter(|id| answers.contains_key(*id))
+            .cloned()
+            .collect();
+
+        // Secret answers never reach the file in plaintext
+        let policy = SecretPolicy::current();
+        let mut answers = answers.clone();
+        let sealed = seal_secrets(policy, &mut answers, &secrets)?;
+
         let history_entry = TemplateHistoryEntry {
             version: template.principal.version,
             time: Utc::now(),
-            answers: answers.clone(),
+            answers,
             deterministic_states,
+            secrets: Some(secrets),
+            sealed,
         };
 
         let template_state_entry =
@@ -78,8 +243,34 @@ impl StateWriter for DefaultStateManager {
                     active: true,
                     history: Vec::new(),
                     files: Vec::new(),
+                    pinned: false,
+                    constraint: None,
                 });
 
+        // Earlier runs may still hold these answers in plaintext (state written before
+        // secrets were kept out of it): seal them there as well
+        for entry in &mut template_state_entry.history {
+            let exposed: BTreeSet<String> = history_entry
+                .secrets
+                .iter()
+                .flatten()
+                .filter(|id| entry.answers.contains_key(*id))
+                .cloned()
+                .collect();
+            if exposed.is_empty() {
+                continue;
+            }
+            entry
+                .sealed
+                .extend(seal_secrets(policy, &mut entry.answers, &exposed)?);
+            // An entry that never recorded its secrets stays unknown: others may remain
+            if let Some(ids) = &mut entry.secrets {
+                ids.extend(exposed);
+            }
+        }
+
+        // Running a template makes it part of the project again if it had been removed
+        template_state_entry.active = true;
         template_state_entry.history.push(history_entry);
 
         self.save_state_file(&state, &state_file_path)?;
@@ -90,3 +281,306 @@ impl StateWriter for DefaultStateManager {
 
 // Implement the combined trait
 impl StateManager for DefaultStateManager {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::models::ManagedFile;
+
+    // The original shape: templates only, no version key.
+    const V1_TEMPLATES_ONLY: &str = "\
+alice/web:
+  active: true
+  history:
+  - version: 3
+    time: 2024-01-02T03:04:05Z
+    answers:
+      name:
+        type: String
+        value: demo
+    deterministic_states: {}
+";
+
+    // Unversioned, with the manifest and merge bookkeeping added later.
+    const V1_WITH_MANIFEST: &str = "\
+alice/web:
+  active: true
+  history: []
+  files:
+  - a.txt
+  - dir/b.txt
+  pinned: true
+  constraint: <=4
+bob/api:
+  active: false
+  history: []
+managed_files:
+- a.txt
+- dir/b.txt
+file_conflicts:
+- path: a.txt
+  resolution: lww_all_no_resolver
+  variations: []
+pending_merge:
+  unresolved:
+  - a.txt
+";
+
+    const V2: &str = "\
+schema_version: 2
+alice/web:
+  active: true
+  history: []
+  files:
+  - path: a.txt
+    sha256: 18b7cb099a9ea3f50ba899b5ba81e0d377a5f3b16f8f6eeb8b3e58cd4692b993
+    size: 5
+  - path: legacy.txt
+managed_files:
+- a.txt
+- legacy.txt
+";
+
+    fn load(yaml: &str) -> Result<CyanState, String> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".cyan_state.yaml");
+        fs::write(&path, yaml).unwrap();
+        DefaultStateManager::new()
+            .load_state_file(&path)
+            .map_err(|e| e.to_string())
+    }
+
+    // Load → save → load yields the same state, written at the current version.
+    fn round_trip(yaml: &str) -> (CyanState, String) {
+        let state = load(yaml).expect("historic shape loads");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".cyan_state.yaml");
+        let manager = DefaultStateManager::new();
+        manager.save_state_file(&state, &path).unwrap();
+        let saved = fs::read_to_string(&path).unwrap();
+        assert!(
+            saved.starts_with(&format!("schema_version: {STATE_SCHEMA_VERSION}\n")),
+            "got:\n{saved}"
+        );
+        let back = manager.load_state_file(&path).unwrap();
+        assert_eq!(
+            serde_yaml::to_value(&back).unwrap(),
+            serde_yaml::to_value(&state).unwrap()
+        );
+        (state, saved)
+    }
+
+    #[test]
+    fn v1_templates_only_round_trips() {
+        let (state, _) = round_trip(V1_TEMPLATES_ONLY);
+        let history = &state.templates["alice/web"].history;
+        assert_eq!(history[0].version, 3);
+        assert_eq!(
+            history[0].answers["name"],
+            Answer::String("demo".to_string())
+        );
+        assert!(state.managed_files.is_empty());
+    }
+
+    #[test]
+    fn v1_path_only_files_migrate() {
+        let (state, saved) = round_trip(V1_WITH_MANIFEST);
+        let web = &state.templates["alice/web"];
+        assert_eq!(web.paths(), vec!["a.txt", "dir/b.txt"]);
+        assert!(web.files.iter().all(|f| f.sha256.is_none()));
+        assert!(web.pinned);
+        assert!(!state.templates["bob/api"].active);
+        assert_eq!(state.managed_files, vec!["a.txt", "dir/b.txt"]);
+        assert_eq!(state.file_conflicts.len(), 1);
+        assert_eq!(state.pending_merge.unwrap().unresolved, vec!["a.txt"]);
+        assert!(saved.contains("  - path: a.txt\n"), "got:\n{saved}");
+    }
+
+    // The path-only `files` shape as the hashing manifest first read it: the entries
+    // load without a hash, never count as untouched, and are hashed on the next save.
+    #[test]
+    fn v1_path_only_files_round_trip() {
+        let (mut state, _) = round_trip(
+            "\
+alice/a:
+  active: true
+  history: []
+  files:
+  - a.txt
+  - dir/b.txt
+managed_files:
+- a.txt
+- dir/b.txt
+",
+        );
+        let ts = &state.templates["alice/a"];
+        assert_eq!(ts.paths(), vec!["a.txt", "dir/b.txt"]);
+        assert!(
+            ts.files
+                .iter()
+                .all(|f| f.sha256.is_none() && f.size.is_none())
+        );
+        assert!(!state.is_untouched("a.txt", b"a.txt"));
+
+        let files = ["a.txt", "dir/b.txt"]
+            .iter()
+            .map(|p| ManagedFile::new(p.to_string(), p.as_bytes()))
+            .collect();
+        state.set_managed_files(&HashMap::from([("alice/a".to_string(), files)]));
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".cyan_state.yaml");
+        DefaultStateManager::new()
+            .save_state_file(&state, &path)
+            .unwrap();
+        let saved = fs::read_to_string(&path).unwrap();
+        assert!(
+            saved.contains(
+                "  - path: a.txt\n    sha256: 18b7cb099a9ea3f50ba899b5ba81e0d377a5f3b16f8f6eeb8b3e58cd4692b993\n    size: 5\n"
+            ),
+            "got:\n{saved}"
+        );
+        let (back, _) = round_trip(&saved);
+        assert!(back.is_untouched("a.txt", b"a.txt"));
+    }
+
+    // Templates are recognised by their shape, not their key; bookkeeping is left alone.
+    #[test]
+    fn migration_detects_templates_by_structure() {
+        let mut doc: Mapping = serde_yaml::from_str(
+            "\
+web:
+  history: []
+  files:
+  - a.txt
+notes:
+  files:
+  - keep-as-is
+",
+        )
+        .unwrap();
+        migrate_v1_to_v2(&mut doc).unwrap();
+        assert_eq!(
+            doc["web"]["files"][0]["path"],
+            Value::String("a.txt".to_string())
+        );
+        assert_eq!(
+            doc["notes"]["files"][0],
+            Value::String("keep-as-is".to_string())
+        );
+    }
+
+    // A rerun that learns an answer is secret seals it in the earlier runs too.
+    #[test]
+    fn saving_metadata_seals_secrets_in_earlier_history() {
+        use cyanprompt::domain::models::cyan::Cyan;
+        use cyanregistry::http::models::template_res::{
+            TemplatePrincipalRes, TemplateVersionPrincipalRes,
+        };
+        use std::collections::HashSet;
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            state_file(dir.path()),
+            "\
+alice/web:
+  active: true
+  history:
+  - version: 1
+    time: 2024-01-02T03:04:05Z
+    answers:
+      token:
+        type: String
+        value: legacy-s3cr3t
+    deterministic_states: {}
+  - version: 2
+    time: 2024-02-02T03:04:05Z
+    answers:
+      token:
+        type: String
+        value: s3cr3t
+    deterministic_states: {}
+    secrets: []
+",
+        )
+        .unwrap();
+
+        let template = TemplateVersionRes {
+            principal: TemplateVersionPrincipalRes {
+                id: "v3".to_string(),
+                version: 3,
+                created_at: "2025-01-01T00:00:00Z".to_string(),
+                description: String::new(),
+                properties: None,
+            },
+            template: TemplatePrincipalRes {
+                id: "web".to_string(),
+                name: "web".to_string(),
+                project: String::new(),
+                source: String::new(),
+                email: String::new(),
+                tags: vec![],
+                description: String::new(),
+                readme: String::new(),
+                user_id: String::new(),
+            },
+            plugins: vec![],
+            processors: vec![],
+            templates: vec![],
+            resolvers: vec![],
+            commands: vec![],
+        };
+        let answers = HashMap::from([("token".to_string(), Answer::String("new".to_string()))]);
+        let complete = TemplateState::Complete(
+            Cyan {
+                processors: Vec::new(),
+                plugins: Vec::new(),
+            },
+            HashMap::new(),
+            HashSet::from(["token".to_string()]),
+        );
+        let manager = DefaultStateManager::new();
+        manager
+            .save_template_metadata(dir.path(), &template, &answers, &complete, "alice")
+            .unwrap();
+
+        let saved = fs::read_to_string(state_file(dir.path())).unwrap();
+        assert!(!saved.contains("s3cr3t"), "got:\n{saved}");
+        let state = manager.load_state_file(&state_file(dir.path())).unwrap();
+        let history = &state.templates["alice/web"].history;
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].secrets, None, "a legacy entry stays unknown");
+        assert_eq!(
+            history[1].secrets,
+            Some(BTreeSet::from(["token".to_string()]))
+        );
+    }
+
+    #[test]
+    fn v2_round_trips() {
+        let (state, saved) = round_trip(V2);
+        let files = &state.templates["alice/web"].files;
+        assert!(files[0].matches(b"a.txt"));
+        assert!(files[1].sha256.is_none());
+        assert_eq!(saved, V2);
+    }
+
+    #[test]
+    fn newer_or_invalid_versions_are_refused() {
+        let err = load("schema_version: 99\n").unwrap_err();
+        assert!(err.contains("schema_version 99"), "got: {err}");
+        assert!(err.contains("upgrade cyanprint"), "got: {err}");
+
+        assert!(load("schema_version: 0\n").is_err());
+        assert!(load("schema_version: two\n").is_err());
+        assert!(load("- a\n").unwrap_err().contains("must be a mapping"));
+    }
+
+    #[test]
+    fn empty_or_missing_file_is_a_fresh_state() {
+        assert!(load("").unwrap().templates.is_empty());
+        let missing = DefaultStateManager::new()
+            .load_state_file(Path::new("/nonexistent/.cyan_state.yaml"))
+            .unwrap();
+        assert!(missing.templates.is_empty());
+    }
+}
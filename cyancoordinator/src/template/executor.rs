@@ -215,11 +215,11 @@ impl TemplateExecutor for DefaultTemplateExecutor {
             if !headless {
                 println!("✅ Received all answers!");
             }
-            tx22.blocking_send(state)
+            let _ = tx22.blocking_send(state);
         });
 
         let _ = runtime.block_on(h21).unwrap();
-        let _ = runtime.block_on(h22).unwrap();
+        runtime.block_on(h22).unwrap();
 
         let executor_started = rx21.blocking_recv().unwrap();
         let executor_started = match executor_started {
@@ -257,7 +257,7 @@ impl TemplateExecutor for DefaultTemplateExecutor {
             TemplateState::NeedInput(_, _) => {
                 unreachable!("NeedInput is handled by the early return above")
             }
-            TemplateState::Complete(ref c, _) => {
+            TemplateState::Complete(ref c, ..) => {
                 cprogress!(headless, "✅ Cyan Response obtained");
                 Ok(c.clone())
             }
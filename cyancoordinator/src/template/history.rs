@@ -72,7 +72,7 @@ impl TemplateHistory for DefaultTemplateHistory {
         template: &TemplateVersionRes,
         username: &str,
     ) -> Result<TemplateUpdateType, Box<dyn Error + Send>> {
-        let state_file_path = target_dir.join(".cyan_state.yaml");
+        let state_file_path = crate::state::state_file(target_dir);
 
         // If state file doesn't exist, it's a new template
         if !state_file_path.exists() {
@@ -85,7 +85,8 @@ impl TemplateHistory for DefaultTemplateHistory {
         // Check if this template exists in history
         let template_key = format!("{}/{}", username, template.template.name);
 
-        if let Some(template_state) = state.templates.get(&template_key) {
+        // A removed (inactive) template is not part of the project: adding it back is new
+        if let Some(template_state) = state.templates.get(&template_key).filter(|ts| ts.active) {
             // We found a matching template, get the most recent entry
             if let Some(latest_entry) = template_state.history.last() {
                 // Check if versions match
@@ -93,14 +94,14 @@ impl TemplateHistory for DefaultTemplateHistory {
                     // Same version - user wants to re-run
                     Ok(TemplateUpdateType::RerunTemplate {
                         previous_version: latest_entry.version,
-                        previous_answers: latest_entry.answers.clone(),
+                        previous_answers: latest_entry.replay_answers(),
                         previous_states: latest_entry.deterministic_states.clone(),
                     })
                 } else {
                     // Different version - upgrade flow
                     Ok(TemplateUpdateType::UpgradeTemplate {
                         previous_version: latest_entry.version,
-                        previous_answers: latest_entry.answers.clone(),
+                        previous_answers: latest_entry.replay_answers(),
                         previous_states: latest_entry.deterministic_states.clone(),
                     })
                 }
@@ -121,7 +122,7 @@ impl TemplateHistory for DefaultTemplateHistory {
         template_state: &TemplateState,
         username: &str,
     ) -> Result<(), Box<dyn Error + Send>> {
-        if let TemplateState::Complete(_, answers) = template_state {
+        if let TemplateState::Complete(_, answers, _) = template_state {
             self.state_manager.save_template_metadata(
                 target_dir,
                 template,
@@ -0,0 +1,354 @@
+//! `cyanprint answers`: export the answers a project was generated with, and create a
+//! new project from such an export.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+
+use cyancoordinator::client::CyanCoordinatorClient;
+use cyancoordinator::session::DefaultSessionIdGenerator;
+use cyancoordinator::state::{CyanState, DefaultStateManager, StateReader, state_file};
+use cyanprompt::domain::models::answer::Answer;
+use cyanregistry::http::client::CyanRegistryClient;
+use serde::{Deserialize, Serialize};
+
+use crate::headless::{CyanRunResult, parse_answers};
+use crate::run::cyan_run;
+use crate::update::parse_template_key;
+
+fn other(msg: String) -> Box<dyn Error + Send> {
+    Box::new(std::io::Error::other(msg))
+}
+
+/// The answers of a project's templates, in installation order.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AnswersExport {
+    pub templates: Vec<TemplateAnswers>,
+}
+
+/// One template's latest answers.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplateAnswers {
+    /// `<user>/<template>`
+    pub template: String,
+    pub version: i64,
+    /// `id -> {type, value}`, the shape `--answers` reads.
+    pub answers: serde_json::Value,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub deterministic_states: BTreeMap<String, String>,
+    /// Secret answers left out of the export; `import` asks for them again.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub omitted: Vec<String>,
+}
+
+impl TemplateAnswers {
+    /// The answer map, read the way `--answers` files are.
+    pub fn parsed_answers(&self) -> Result<HashMap<String, Answer>, Box<dyn Error + Send>> {
+        parse_answers(&self.answers.to_string())
+            .map_err(|e| other(format!("{}: {e}", self.template)))
+    }
+}
+
+impl AnswersExport {
+    /// The latest answers of every active template in `state`. Answers to `Password`
+    /// questions are omitted.
+    ///
+    /// Entries written before cyanprint recorded which answers are secret may hold
+    /// passwords in plaintext among the rest. They are refused unless
+    /// `allow_unknown_secrets`, in which case their answers are exported as they are.
+    pub fn from_state(
+        state: &CyanState,
+        allow_unknown_secrets: bool,
+    ) -> Result<Self, Box<dyn Error + Send>> {
+        let mut templates: Vec<_> = state
+            .templates
+            .iter()
+            .filter(|(_, ts)| ts.active)
+            .filter_map(|(key, ts)| Some((key, ts.history.last()?)))
+            .collect();
+        templates.sort_by(|(a_key, a), (b_key, b)| (a.time, a_key).cmp(&(b.time, b_key)));
+
+        let unknown: Vec<&str> = templates
+            .iter()
+            .filter(|(_, entry)| entry.secrets.is_none() && !entry.answers.is_empty())
+            .map(|(key, _)| key.as_str())
+            .collect();
+        if !unknown.is_empty() && !allow_unknown_secrets {
+            return Err(other(format!(
+                "the state of {} predates the recording of secret answers, so passwords \
+                 could be exported in plaintext; pass --allow-unknown-secrets to export \
+                 them anyway",
+                unknown.join(", ")
+            )));
+        }
+
+        let templates = templates
+            .into_iter()
+            .map(|(key, entry)| {
+                let secrets = entry.secrets.clone().unwrap_or_default();
+                let answers: BTreeMap<_, _> = entry
+                    .answers
+                    .iter()
+                    .filter(|(id, _)| !secrets.contains(*id))
+                    .collect();
+                TemplateAnswers {
+                    template: key.clone(),
+                    version: entry.version,
+                    answers: serde_json::to_value(answers).unwrap_or_default(),
+                    deterministic_states: entry
+                        .deterministic_states
+                        .iter()
+                        .map(|(k, v)| (k.clone(), v.clone()))
+                        .collect(),
+                    omitted: secrets.into_iter().collect(),
+                }
+            })
+            .collect();
+        Ok(Self { templates })
+    }
+
+    /// Export the answers of the project at `target_dir`; see [`Self::from_state`].
+    pub fn of_project(
+        target_dir: &Path,
+        allow_unknown_secrets: bool,
+    ) -> Result<Self, Box<dyn Error + Send>> {
+        let path = state_file(target_dir);
+        if !path.exists() {
+            return Err(other(format!("No state file at {}", path.display())));
+        }
+        let state = DefaultStateManager::new().load_state_file(&path)?;
+        Self::from_state(&state, allow_unknown_secrets)
+    }
+
+    /// Read an export written by `cyanprint answers export`.
+    pub fn read(path: &Path) -> Result<Self, Box<dyn Error + Send>> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| other(format!("failed to read {}: {e}", path.display())))?;
+        let export: Self = serde_json::from_str(&text)
+            .map_err(|e| other(format!("{} is not an answers export: {e}", path.display())))?;
+        for t in &export.templates {
+            if parse_template_key(&t.template).is_none() {
+                return Err(other(format!("invalid template '{}'", t.template)));
+            }
+        }
+        Ok(export)
+    }
+}
+
+/// Settings for `cyanprint answers import`.
+pub struct ImportOptions {
+    pub coordinator_endpoint: String,
+    pub debug: bool,
+    pub cache_config: cyancoordinator::cache::CacheConfig,
+    pub binary_policy: cyancoordinator::fs::BinaryMergePolicy,
+    pub headless: bool,
+    /// Extra answers, such as the secrets the export omitted; they win over the export.
+    pub answers: HashMap<String, Answer>,
+}
+
+/// Create the project at `target_dir` from `export`: run each template at its exported
+/// version with its exported answers and states, asking only for what they leave open.
+/// Templates already in the project are skipped, so a headless import that stopped for
+/// input picks up where it left off.
+pub fn cyan_import(
+    export: &AnswersExport,
+    target_dir: &Path,
+    registry: Rc<CyanRegistryClient>,
+    opts: &ImportOptions,
+) -> Result<CyanRunResult, Box<dyn Error + Send>> {
+    let coord_client = CyanCoordinatorClient::new(opts.coordinator_endpoint.clone());
+    let installed = installed_templates(target_dir)?;
+    let mut last = CyanRunResult::completed(Vec::new());
+
+    for t in &export.templates {
+        if installed.contains(&t.template) {
+            crate::hprogress!(
+                opts.headless,
+                "⏭️  {} is already in the project, skipping",
+                t.template
+            );
+            continue;
+        }
+        let (username, name) = parse_template_key(&t.template)
+            .ok_or_else(|| other(format!("invalid template '{}'", t.template)))?;
+        crate::hprogress!(opts.headless, "📥 Importing {} v{}", t.template, t.version);
+
+        let mut answers = t.parsed_answers()?;
+        answers.extend(opts.answers.clone());
+        let template = registry.get_template(username.clone(), name, Some(t.version))?;
+        let result = cyan_run(
+            Box::new(DefaultSessionIdGenerator),
+            Some(target_dir.to_string_lossy().into_owned()),
+            template,
+            coord_client.clone(),
+            username,
+            Rc::clone(&registry),
+            opts.debug,
+            opts.cache_config.clone(),
+            opts.binary_policy,
+            opts.headless,
+            answers,
+            t.deterministic_states.clone().into_iter().collect(),
+        )?;
+        if result.need_input.is_some() {
+            return Ok(result);
+        }
+        // Only the final result's sessions go back to the caller
+        for sid in std::mem::take(&mut last.session_ids) {
+            let _ = coord_client.clean(sid);
+        }
+        last = result;
+    }
+    Ok(last)
+}
+
+fn installed_templates(target_dir: &Path) -> Result<HashSet<String>, Box<dyn Error + Send>> {
+    let path = state_file(target_dir);
+    if !path.exists() {
+        return Ok(HashSet::new());
+    }
+    let state = DefaultStateManager::new().load_state_file(&path)?;
+    Ok(state
+        .templates
+        .iter()
+        .filter(|(_, ts)| ts.active)
+        .map(|(key, _)| key.clone())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use cyancoordinator::state::models::{TemplateHistoryEntry, TemplateState};
+    use std::collections::BTreeSet;
+
+    fn template(
+        version: i64,
+        secs: i64,
+        answers: &[(&str, Answer)],
+        secrets: Option<&[&str]>,
+    ) -> TemplateState {
+        TemplateState {
+            active: true,
+            history: vec![TemplateHistoryEntry {
+                version,
+                time: Utc.timestamp_opt(secs, 0).unwrap(),
+                answers: answers
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.clone()))
+                    .collect(),
+                deterministic_states: HashMap::from([("seed".to_string(), "42".to_string())]),
+                secrets: secrets
+                    .map(|ids| ids.iter().map(|s| s.to_string()).collect::<BTreeSet<_>>()),
+                sealed: BTreeMap::new(),
+            }],
+            files: Vec::new(),
+            pinned: false,
+            constraint: None,
+        }
+    }
+
+    #[test]
+    fn export_omits_secrets_and_orders_by_installation() {
+        let mut state = CyanState::default();
+        state.templates.insert(
+            "bob/api".to_string(),
+            template(
+                2,
+                20,
+                &[("port", Answer::String("80".to_string()))],
+                Some(&[]),
+            ),
+        );
+        state.templates.insert(
+            "alice/web".to_string(),
+            template(
+                5,
+                10,
+                &[
+                    ("name", Answer::String("demo".to_string())),
+                    ("token", Answer::String("s3cr3t".to_string())),
+                ],
+                Some(&["token"]),
+            ),
+        );
+
+        let export = AnswersExport::from_state(&state, false).unwrap();
+        let keys: Vec<_> = export.templates.iter().map(|t| &t.template).collect();
+        assert_eq!(keys, ["alice/web", "bob/api"]);
+
+        let web = &export.templates[0];
+        assert_eq!(web.version, 5);
+        assert_eq!(web.omitted, vec!["token".to_string()]);
+        let json = serde_json::to_string(&export).unwrap();
+        assert!(!json.contains("s3cr3t"), "got: {json}");
+
+        let answers = web.parsed_answers().unwrap();
+        assert_eq!(
+            answers,
+            HashMap::from([("name".to_string(), Answer::String("demo".to_string()))])
+        );
+        assert_eq!(web.deterministic_states["seed"], "42");
+    }
+
+    #[test]
+    fn export_refuses_state_without_recorded_secrets() {
+        let mut state = CyanState::default();
+        state.templates.insert(
+            "alice/web".to_string(),
+            template(
+                5,
+                10,
+                &[("token", Answer::String("s3cr3t".to_string()))],
+                None,
+            ),
+        );
+
+        let err = AnswersExport::from_state(&state, false).unwrap_err();
+        assert!(err.to_string().contains("alice/web"), "got: {err}");
+        assert!(
+            err.to_string().contains("--allow-unknown-secrets"),
+            "got: {err}"
+        );
+
+        let export = AnswersExport::from_state(&state, true).unwrap();
+        assert!(export.templates[0].omitted.is_empty());
+        assert_eq!(
+            export.templates[0].parsed_answers().unwrap()["token"],
+            Answer::String("s3cr3t".to_string())
+        );
+    }
+
+    #[test]
+    fn read_round_trips_an_export() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut state = CyanState::default();
+        state.templates.insert(
+            "alice/web".to_string(),
+            template(5, 10, &[("ok", Answer::Bool(true))], Some(&[])),
+        );
+        let path = dir.path().join("answers.json");
+        fs::write(
+            &path,
+            serde_json::to_string(&AnswersExport::from_state(&state, false).unwrap()).unwrap(),
+        )
+        .unwrap();
+
+        let export = AnswersExport::read(&path).unwrap();
+        assert_eq!(export.templates[0].template, "alice/web");
+        assert_eq!(
+            export.templates[0].parsed_answers().unwrap()["ok"],
+            Answer::Bool(true)
+        );
+
+        fs::write(
+            &path,
+            r#"{"templates":[{"template":"nope","version":1,"answers":{}}]}"#,
+        )
+        .unwrap();
+        assert!(AnswersExport::read(&path).is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use inquire::Confirm;
+use serde::Serialize;
 use std::error::Error;
 use std::path::Path;
 use std::process::Command;
@@ -16,6 +17,16 @@ pub struct CommandExecutionResult {
     pub failed_indices: Vec<usize>,
     /// Whether execution was aborted by user
     pub aborted: bool,
+    /// Every command that was run, in order
+    pub runs: Vec<CommandRun>,
+}
+
+/// One executed command and how it exited
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommandRun {
+    pub command: String,
+    /// `None` when the command was terminated by a signal
+    pub exit_code: Option<i32>,
 }
 
 impl CommandExecutionResult {
@@ -26,6 +37,7 @@ impl CommandExecutionResult {
             failed: 0,
             failed_indices: Vec::new(),
             aborted: false,
+            runs: Vec::new(),
         }
     }
 
@@ -94,6 +106,10 @@ impl CommandExecutor {
                 .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?
                 .wait()
                 .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
+            result.runs.push(CommandRun {
+                command: cmd.to_string(),
+                exit_code: exit_status.code(),
+            });
 
             if exit_status.success() {
                 println!("    ✅ Success");
@@ -191,6 +207,10 @@ impl CommandExecutor {
                     .wait()
                     .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?
             };
+            result.runs.push(CommandRun {
+                command: cmd.to_string(),
+                exit_code: exit_status.code(),
+            });
 
             if exit_status.success() {
                 result.succeeded += 1;
@@ -252,6 +272,7 @@ mod tests {
             failed: 0,
             failed_indices: vec![],
             aborted: false,
+            runs: vec![],
         };
         assert!(result.all_succeeded());
     }
@@ -264,6 +285,7 @@ mod tests {
             failed: 2,
             failed_indices: vec![1, 3],
             aborted: false,
+            runs: vec![],
         };
         assert!(!result.all_succeeded());
     }
@@ -276,6 +298,7 @@ mod tests {
             failed: 1,
             failed_indices: vec![1],
             aborted: true,
+            runs: vec![],
         };
         assert!(!result.all_succeeded());
     }
@@ -377,6 +400,8 @@ mod tests {
         .expect("the non-interactive path returns Ok even with a partial failure");
         assert_eq!(result.succeeded, 2);
         assert_eq!(result.failed, 1);
+        let codes: Vec<Option<i32>> = result.runs.iter().map(|r| r.exit_code).collect();
+        assert_eq!(codes, vec![Some(0), Some(1), Some(0)]);
         assert!(
             !result.all_succeeded(),
             "a partial failure must not be reported as all_succeeded"
@@ -1,4 +1,6 @@
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use cyancoordinator::fs::BinaryMergePolicy;
+use cyancoordinator::state::{SecretPolicy, StateFormat, VersionConstraint};
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -43,6 +45,58 @@ pub struct Cli {
                 ~/Library/Caches/cyanprint on macOS; also via CYANPRINT_CACHE)"
     )]
     pub cache_dir: Option<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "POLICY",
+        default_value = "keep-local",
+        env = "CYANPRINT_BINARY_MERGE",
+        help = "What to do with a binary file changed both locally and by the template \
+                during a 3-way merge: keep-local, take-incoming or fail"
+    )]
+    pub binary_merge: BinaryMergePolicy,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        env = "CYANPRINT_STATE_FILE",
+        help = "Project state file, relative to the project (default: .cyan_state.yaml)"
+    )]
+    pub state_file: Option<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "FORMAT",
+        env = "CYANPRINT_STATE_FORMAT",
+        help = "Format of a new state file: yaml, json or toml. An existing state file \
+                is read in the format its extension names"
+    )]
+    pub state_format: Option<StateFormat>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "POLICY",
+        default_value = "omit",
+        env = "CYANPRINT_SECRETS",
+        help = "What the state file keeps of answers to password questions: omit (ask \
+                again on the next run), encrypt (age-encrypted to the identity in \
+                CYANPRINT_SECRET_KEY) or env (the name of a CYANPRINT_ANSWER_<ID> \
+                variable to read them from)"
+    )]
+    pub secrets: SecretPolicy,
+}
+
+/// How a command reports its result on stdout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text.
+    Text,
+    /// A single JSON document.
+    Json,
 }
 
 #[derive(Subcommand)]
@@ -102,6 +156,62 @@ pub enum Commands {
             help = "Path to a JSON file of answers (id -> {type,value}); reads stdin when omitted and piped"
         )]
         answers: Option<String>,
+
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = OutputFormat::Text,
+            help = "text, or json for a report of the versions, files, conflicts, cache hits \
+                    and commands of the run (in the headless `done` envelope, else as the \
+                    only output on stdout, with progress on stderr)"
+        )]
+        output: OutputFormat,
+    },
+
+    #[command(
+        about = "Add a template to an existing project",
+        long_about = "Add a template to an existing project.\n\n\
+            The new template is layered together with every template already installed, \
+            so resolvers and installation order apply to the files they share."
+    )]
+    Add {
+        template_ref: String,
+
+        #[arg(default_value = ".")]
+        path: String,
+
+        #[arg(
+            short,
+            long,
+            value_name = "COORDINATOR_ENDPOINT",
+            default_value = "http://coord.cyanprint.dev:9000",
+            env = "CYANPRINT_COORDINATOR"
+        )]
+        coordinator_endpoint: String,
+
+        #[arg(
+            long,
+            help = "Run non-interactively: emit the next unanswered question as JSON instead of prompting",
+            default_value_t = false
+        )]
+        headless: bool,
+
+        #[arg(
+            long,
+            value_name = "ANSWERS_FILE",
+            help = "Path to a JSON file of answers (id -> {type,value}); reads stdin when omitted and piped"
+        )]
+        answers: Option<String>,
+
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = OutputFormat::Text,
+            help = "text, or json for a report of the versions, files, conflicts, cache hits \
+                    and commands of the run (in the headless `done` envelope, else as the \
+                    only output on stdout, with progress on stderr)"
+        )]
+        output: OutputFormat,
     },
 
     #[command(
@@ -112,6 +222,22 @@ pub enum Commands {
         #[arg(default_value = ".")]
         path: String,
 
+        #[arg(
+            last = true,
+            value_name = "TEMPLATE",
+            value_parser = crate::update::parse_template_target,
+            help = "Only update these templates, named after `--` (e.g. `update . -- alice/web`)"
+        )]
+        targets: Vec<String>,
+
+        #[arg(
+            long = "to",
+            value_name = "TEMPLATE:VERSION",
+            value_parser = crate::update::parse_version_target,
+            help = "Move a template to an exact version, e.g. alice/web:12 (overrides pins)"
+        )]
+        to: Vec<(String, i64)>,
+
         #[arg(
             short,
             long,
@@ -149,6 +275,236 @@ pub enum Commands {
             help = "Path to a JSON file of answers (id -> {type,value}); reads stdin when omitted and piped"
         )]
         answers: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "QUESTION_ID",
+            help = "Forget the recorded answer to this question so the template asks again; \
+                    repeatable"
+        )]
+        reanswer: Vec<String>,
+
+        #[arg(
+            long,
+            conflicts_with = "reanswer",
+            help = "Forget every recorded answer of the updated templates and ask them all again"
+        )]
+        reask: bool,
+
+        #[arg(
+            short,
+            long,
+            help = "Update every project with a state file below the directory",
+            conflicts_with_all = ["interactive", "headless", "answers", "reanswer", "reask"]
+        )]
+        recursive: bool,
+
+        #[arg(
+            short,
+            long,
+            value_name = "N",
+            default_value_t = 4,
+            requires = "recursive",
+            help = "Maximum number of projects updated at once"
+        )]
+        jobs: usize,
+
+        #[arg(
+            long,
+            value_name = "NAME",
+            conflicts_with = "recursive",
+            help = "Update on a new git branch NAME and commit the result with a message \
+                    listing each version bump and conflict"
+        )]
+        branch: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "FILE",
+            requires = "branch",
+            help = "Also write a Markdown pull request description of the update to FILE"
+        )]
+        pr_description: Option<PathBuf>,
+
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = OutputFormat::Text,
+            help = "text, or json for a report of the versions, files, conflicts, cache hits \
+                    and commands of the run (in the headless `done` envelope, else as the \
+                    only output on stdout, with progress on stderr); with --recursive, the \
+                    per-project summary"
+        )]
+        output: OutputFormat,
+    },
+
+    #[command(about = "Preview the changes an update would make, without writing anything")]
+    Diff {
+        #[arg(default_value = ".")]
+        path: String,
+
+        #[arg(
+            short,
+            long,
+            value_name = "COORDINATOR_ENDPOINT",
+            default_value = "http://coord.cyanprint.dev:9000",
+            env = "CYANPRINT_COORDINATOR"
+        )]
+        coordinator_endpoint: String,
+
+        #[arg(
+            short,
+            long,
+            help = "Enable interactive mode to select specific versions",
+            default_value_t = false
+        )]
+        interactive: bool,
+    },
+
+    #[command(
+        about = "Report managed files that drifted from what their templates produce",
+        long_about = "Report managed files that drifted from what their templates produce.\n\n\
+            Every active template is re-run with its recorded answers; files edited or deleted \
+            locally, and unmanaged files a template would overwrite, are listed. Nothing is written."
+    )]
+    Status {
+        #[arg(default_value = ".")]
+        path: String,
+
+        #[arg(
+            short,
+            long,
+            value_name = "COORDINATOR_ENDPOINT",
+            default_value = "http://coord.cyanprint.dev:9000",
+            env = "CYANPRINT_COORDINATOR"
+        )]
+        coordinator_endpoint: String,
+
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text, help = "Report format")]
+        output: OutputFormat,
+
+        #[arg(
+            long,
+            help = "Exit with status 1 when any file drifted",
+            default_value_t = false
+        )]
+        exit_code: bool,
+    },
+
+    #[command(
+        about = "Finish or roll back an update that stopped on merge conflicts",
+        long_about = "Finish or roll back an update that stopped on merge conflicts.\n\n\
+            Without a flag, lists the conflicted files and whether each still has markers."
+    )]
+    Resolve {
+        #[arg(default_value = ".")]
+        path: String,
+
+        #[arg(
+            long = "continue",
+            help = "Finalise the update once no conflict markers remain",
+            conflicts_with = "abort",
+            default_value_t = false
+        )]
+        continue_merge: bool,
+
+        #[arg(
+            long,
+            help = "Restore the files and state from before the update",
+            default_value_t = false
+        )]
+        abort: bool,
+    },
+
+    #[command(
+        about = "Pin a template's version or cap the versions update may move it to",
+        long_about = "Pin a template's version or cap the versions update may move it to.\n\n\
+            Without a constraint the template is pinned: `update` leaves it alone unless \
+            it is moved explicitly with `--to`. A constraint such as `<=14` or `>=3,<5` \
+            instead caps the version `update` picks."
+    )]
+    Pin {
+        #[arg(
+            value_name = "TEMPLATE",
+            help = "Template to pin, as <user>/<template>"
+        )]
+        template: String,
+
+        #[arg(value_name = "CONSTRAINT", help = "Version constraint, e.g. <=14")]
+        constraint: Option<VersionConstraint>,
+
+        #[arg(long, default_value = ".", help = "Project directory")]
+        path: String,
+
+        #[arg(
+            long,
+            help = "Remove the pin and any constraint",
+            conflicts_with = "constraint",
+            default_value_t = false
+        )]
+        clear: bool,
+    },
+
+    #[command(
+        alias = "rm",
+        about = "Remove a template from a project",
+        long_about = "Remove a template from a project.\n\n\
+            Re-layers the remaining templates without it and deletes the files only it \
+            produced. Files you edited are kept and listed. The template's history stays \
+            in the state file, marked inactive."
+    )]
+    Remove {
+        #[arg(
+            value_name = "TEMPLATE",
+            help = "Template to remove, as <user>/<template>"
+        )]
+        template: String,
+
+        #[arg(long, default_value = ".", help = "Project directory")]
+        path: String,
+
+        #[arg(
+            short,
+            long,
+            value_name = "COORDINATOR_ENDPOINT",
+            default_value = "http://coord.cyanprint.dev:9000",
+            env = "CYANPRINT_COORDINATOR"
+        )]
+        coordinator_endpoint: String,
+
+        #[arg(long, help = "Remove even if git is dirty")]
+        force: bool,
+    },
+
+    #[command(
+        about = "Revert the last create/update of a template",
+        long_about = "Revert the last create/update of a template.\n\n\
+            Re-runs the template's previous version with its recorded answers, merges the \
+            result into the working tree and drops the undone entry from the state history. \
+            Undoing a template's creation removes it. Without a template, the most recently \
+            changed one is reverted."
+    )]
+    Undo {
+        #[arg(
+            value_name = "TEMPLATE",
+            help = "Template to revert, as <user>/<template>"
+        )]
+        template: Option<String>,
+
+        #[arg(long, default_value = ".", help = "Project directory")]
+        path: String,
+
+        #[arg(
+            short,
+            long,
+            value_name = "COORDINATOR_ENDPOINT",
+            default_value = "http://coord.cyanprint.dev:9000",
+            env = "CYANPRINT_COORDINATOR"
+        )]
+        coordinator_endpoint: String,
+
+        #[arg(long, help = "Revert even if git is dirty")]
+        force: bool,
     },
 
     #[command(
@@ -180,6 +536,96 @@ pub enum Commands {
         #[command(subcommand)]
         command: CacheCommands,
     },
+
+    #[command(about = "Manage the project state file")]
+    State {
+        #[command(subcommand)]
+        command: StateCommands,
+    },
+
+    #[command(about = "Export a project's answers, or create a project from an export")]
+    Answers {
+        #[command(subcommand)]
+        command: AnswersCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AnswersCommands {
+    #[command(
+        about = "Print the latest answers and deterministic states of each template as JSON; \
+                 answers to password questions are left out"
+    )]
+    Export {
+        #[arg(default_value = ".")]
+        path: String,
+
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Write the export to FILE instead of stdout"
+        )]
+        file: Option<PathBuf>,
+
+        #[arg(
+            long,
+            help = "Also export templates whose state predates the recording of secret \
+                    answers; their passwords, if any, are exported in plaintext"
+        )]
+        allow_unknown_secrets: bool,
+    },
+
+    #[command(
+        about = "Create a project from an answers export, asking only for the answers it \
+                 leaves out"
+    )]
+    Import {
+        #[arg(value_name = "EXPORT_FILE")]
+        file: PathBuf,
+
+        #[arg(default_value = ".")]
+        path: String,
+
+        #[arg(
+            short,
+            long,
+            value_name = "COORDINATOR_ENDPOINT",
+            default_value = "http://coord.cyanprint.dev:9000",
+            env = "CYANPRINT_COORDINATOR"
+        )]
+        coordinator_endpoint: String,
+
+        #[arg(
+            long,
+            help = "Run non-interactively: emit the next unanswered question as JSON instead of prompting",
+            default_value_t = false
+        )]
+        headless: bool,
+
+        #[arg(
+            long,
+            value_name = "ANSWERS_FILE",
+            help = "With --headless, a JSON file of extra answers (id -> {type,value}), such \
+                    as the omitted secrets; reads stdin when omitted and piped"
+        )]
+        answers: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum StateCommands {
+    #[command(about = "Rewrite the state file in another format")]
+    Migrate {
+        #[arg(
+            long,
+            value_name = "FORMAT",
+            help = "Target format: yaml, json or toml"
+        )]
+        to: StateFormat,
+
+        #[arg(default_value = ".")]
+        path: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -879,6 +1325,24 @@ mod tests {
         assert_eq!(cli.cache_dir, Some(PathBuf::from("/tmp/mycache")));
     }
 
+    #[test]
+    fn test_binary_merge_flag_parses() {
+        let cli = Cli::try_parse_from(["cyanprint", "update", "."]).unwrap();
+        assert_eq!(cli.binary_merge, BinaryMergePolicy::KeepLocal);
+
+        let cli = Cli::try_parse_from([
+            "cyanprint",
+            "update",
+            ".",
+            "--binary-merge",
+            "take-incoming",
+        ])
+        .unwrap();
+        assert_eq!(cli.binary_merge, BinaryMergePolicy::TakeIncoming);
+
+        assert!(Cli::try_parse_from(["cyanprint", "update", "--binary-merge", "newest"]).is_err());
+    }
+
     // FR6 / regression: the global execution-cache flag must NOT collide with the
     // Docker buildx `--no-cache` that `build` and `push` define. Both surfaces must
     // parse, and their `--no-cache` must remain the buildx switch.
@@ -966,6 +1430,312 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_update_targets_and_versions() {
+        let cli = Cli::try_parse_from([
+            "cyanprint",
+            "update",
+            "app",
+            "--to",
+            "bob/api:12",
+            "--to",
+            "carol/db:3",
+            "--",
+            "alice/web",
+        ])
+        .unwrap();
+        if let Commands::Update {
+            path, targets, to, ..
+        } = cli.command
+        {
+            assert_eq!(path, "app");
+            assert_eq!(targets, vec!["alice/web".to_string()]);
+            assert_eq!(
+                to,
+                vec![("bob/api".to_string(), 12), ("carol/db".to_string(), 3)]
+            );
+        } else {
+            panic!("Expected Update");
+        }
+
+        assert!(Cli::try_parse_from(["cyanprint", "update", "--to", "bob/api"]).is_err());
+    }
+
+    #[test]
+    fn test_update_targets_need_separator() {
+        let cli = Cli::try_parse_from(["cyanprint", "update", "--", "alice/web"]).unwrap();
+        if let Commands::Update { path, targets, .. } = cli.command {
+            assert_eq!(path, ".");
+            assert_eq!(targets, vec!["alice/web".to_string()]);
+        } else {
+            panic!("Expected Update");
+        }
+
+        // Without `--` a `<user>/<template>`-shaped argument is always the path
+        let cli = Cli::try_parse_from(["cyanprint", "update", "alice/web"]).unwrap();
+        if let Commands::Update { path, targets, .. } = cli.command {
+            assert_eq!(path, "alice/web");
+            assert!(targets.is_empty());
+        } else {
+            panic!("Expected Update");
+        }
+
+        assert!(Cli::try_parse_from(["cyanprint", "update", "--", "web"]).is_err());
+    }
+
+    #[test]
+    fn test_run_output_format() {
+        let cli = Cli::try_parse_from([
+            "cyanprint",
+            "create",
+            "alice/web",
+            "app",
+            "--headless",
+            "--output",
+            "json",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Create {
+                output: OutputFormat::Json,
+                ..
+            }
+        ));
+
+        let cli = Cli::try_parse_from(["cyanprint", "add", "alice/web"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Add {
+                output: OutputFormat::Text,
+                ..
+            }
+        ));
+
+        let cli = Cli::try_parse_from(["cyanprint", "update", "--output", "json"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Update {
+                output: OutputFormat::Json,
+                recursive: false,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_update_recursive_flags() {
+        let cli = Cli::try_parse_from([
+            "cyanprint",
+            "update",
+            "monorepo",
+            "--recursive",
+            "-j",
+            "8",
+            "--output",
+            "json",
+        ])
+        .unwrap();
+        if let Commands::Update {
+            path,
+            recursive,
+            jobs,
+            output,
+            ..
+        } = cli.command
+        {
+            assert_eq!(path, "monorepo");
+            assert!(recursive);
+            assert_eq!(jobs, 8);
+            assert_eq!(output, OutputFormat::Json);
+        } else {
+            panic!("Expected Update");
+        }
+
+        assert!(Cli::try_parse_from(["cyanprint", "update", "--jobs", "2"]).is_err());
+        assert!(Cli::try_parse_from(["cyanprint", "update", "-r", "--headless"]).is_err());
+    }
+
+    #[test]
+    fn test_update_branch_flags() {
+        let cli = Cli::try_parse_from([
+            "cyanprint",
+            "update",
+            "--branch",
+            "cyan/upgrade",
+            "--pr-description",
+            "pr.md",
+        ])
+        .unwrap();
+        if let Commands::Update {
+            branch,
+            pr_description,
+            ..
+        } = cli.command
+        {
+            assert_eq!(branch.as_deref(), Some("cyan/upgrade"));
+            assert_eq!(pr_description, Some(PathBuf::from("pr.md")));
+        } else {
+            panic!("Expected Update");
+        }
+
+        assert!(Cli::try_parse_from(["cyanprint", "update", "--pr-description", "pr.md"]).is_err());
+        assert!(Cli::try_parse_from(["cyanprint", "update", "-r", "--branch", "x"]).is_err());
+    }
+
+    #[test]
+    fn test_update_reanswer_flags() {
+        let cli = Cli::try_parse_from([
+            "cyanprint",
+            "update",
+            "--reanswer",
+            "db",
+            "--reanswer",
+            "port",
+        ])
+        .unwrap();
+        if let Commands::Update {
+            reanswer, reask, ..
+        } = cli.command
+        {
+            assert_eq!(reanswer, vec!["db".to_string(), "port".to_string()]);
+            assert!(!reask);
+        } else {
+            panic!("Expected Update");
+        }
+
+        assert!(
+            Cli::try_parse_from(["cyanprint", "update", "--reask", "--reanswer", "x"]).is_err()
+        );
+        assert!(Cli::try_parse_from(["cyanprint", "update", "-r", "--reask"]).is_err());
+    }
+
+    #[test]
+    fn test_answers_commands() {
+        let cli =
+            Cli::try_parse_from(["cyanprint", "answers", "export", "--file", "a.json"]).unwrap();
+        if let Commands::Answers {
+            command:
+                AnswersCommands::Export {
+                    path,
+                    file,
+                    allow_unknown_secrets,
+                },
+        } = cli.command
+        {
+            assert_eq!(path, ".");
+            assert_eq!(file, Some(PathBuf::from("a.json")));
+            assert!(!allow_unknown_secrets);
+        } else {
+            panic!("Expected Answers Export");
+        }
+
+        let cli = Cli::try_parse_from([
+            "cyanprint",
+            "answers",
+            "import",
+            "a.json",
+            "new-app",
+            "--headless",
+        ])
+        .unwrap();
+        if let Commands::Answers {
+            command:
+                AnswersCommands::Import {
+                    file,
+                    path,
+                    headless,
+                    ..
+                },
+        } = cli.command
+        {
+            assert_eq!(file, PathBuf::from("a.json"));
+            assert_eq!(path, "new-app");
+            assert!(headless);
+        } else {
+            panic!("Expected Answers Import");
+        }
+    }
+
+    #[test]
+    fn test_pin_command_args() {
+        let cli = Cli::try_parse_from(["cyanprint", "pin", "alice/web", "<=14"]).unwrap();
+        if let Commands::Pin {
+            template,
+            constraint,
+            clear,
+            ..
+        } = cli.command
+        {
+            assert_eq!(template, "alice/web");
+            assert_eq!(constraint, Some("<=14".parse().unwrap()));
+            assert!(!clear);
+        } else {
+            panic!("Expected Pin");
+        }
+
+        assert!(Cli::try_parse_from(["cyanprint", "pin", "alice/web", "<=x"]).is_err());
+        assert!(Cli::try_parse_from(["cyanprint", "pin", "alice/web", "<=1", "--clear"]).is_err());
+    }
+
+    #[test]
+    fn test_add_command_args() {
+        let cli = Cli::try_parse_from(["cyanprint", "add", "alice/web:3", "--headless"]).unwrap();
+        if let Commands::Add {
+            template_ref,
+            path,
+            headless,
+            ..
+        } = cli.command
+        {
+            assert_eq!(template_ref, "alice/web:3");
+            assert_eq!(path, ".");
+            assert!(headless);
+        } else {
+            panic!("Expected Add");
+        }
+    }
+
+    #[test]
+    fn test_remove_command_args() {
+        let cli = Cli::try_parse_from(["cyanprint", "rm", "alice/web", "--force"]).unwrap();
+        if let Commands::Remove {
+            template,
+            path,
+            force,
+            ..
+        } = cli.command
+        {
+            assert_eq!(template, "alice/web");
+            assert_eq!(path, ".");
+            assert!(force);
+        } else {
+            panic!("Expected Remove");
+        }
+        assert!(Cli::try_parse_from(["cyanprint", "remove"]).is_err());
+    }
+
+    #[test]
+    fn test_undo_command_args() {
+        let cli = Cli::try_parse_from(["cyanprint", "undo", "alice/web", "--path", "app"]).unwrap();
+        if let Commands::Undo {
+            template,
+            path,
+            force,
+            ..
+        } = cli.command
+        {
+            assert_eq!(template.as_deref(), Some("alice/web"));
+            assert_eq!(path, "app");
+            assert!(!force);
+        } else {
+            panic!("Expected Undo");
+        }
+
+        let cli = Cli::try_parse_from(["cyanprint", "undo"]).unwrap();
+        assert!(matches!(cli.command, Commands::Undo { template: None, .. }));
+    }
+
     // `--headless` and `--interactive` are mutually exclusive on update, so the
     // interactive version-select prompt (FR1/NFC2 violation) can never be reached headless.
     #[test]
@@ -982,6 +1752,148 @@ mod tests {
         assert!(Cli::try_parse_from(["cyanprint", "update", ".", "--interactive"]).is_ok());
     }
 
+    #[test]
+    fn test_diff_command_defaults() {
+        let cli = Cli::try_parse_from(["cyanprint", "diff"]).unwrap();
+        if let Commands::Diff {
+            path, interactive, ..
+        } = cli.command
+        {
+            assert_eq!(path, ".");
+            assert!(!interactive);
+        } else {
+            panic!("Expected Diff");
+        }
+
+        let cli = Cli::try_parse_from(["cyanprint", "diff", "svc", "-i"]).unwrap();
+        if let Commands::Diff {
+            path, interactive, ..
+        } = cli.command
+        {
+            assert_eq!(path, "svc");
+            assert!(interactive);
+        } else {
+            panic!("Expected Diff");
+        }
+    }
+
+    #[test]
+    fn test_state_migrate_and_location_flags() {
+        let cli = Cli::try_parse_from([
+            "cyanprint",
+            "state",
+            "migrate",
+            "--to",
+            "toml",
+            "svc",
+            "--state-file",
+            ".config/cyan/state.yaml",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.state_file,
+            Some(PathBuf::from(".config/cyan/state.yaml"))
+        );
+        assert_eq!(cli.state_format, None);
+        match cli.command {
+            Commands::State {
+                command: StateCommands::Migrate { to, path },
+            } => {
+                assert_eq!(to, StateFormat::Toml);
+                assert_eq!(path, "svc");
+            }
+            _ => panic!("Expected State Migrate"),
+        }
+
+        let cli = Cli::try_parse_from(["cyanprint", "status", "--state-format", "json"]).unwrap();
+        assert_eq!(cli.state_format, Some(StateFormat::Json));
+        assert!(Cli::try_parse_from(["cyanprint", "state", "migrate", "--to", "xml"]).is_err());
+    }
+
+    #[test]
+    fn test_secrets_policy_flag() {
+        let cli = Cli::try_parse_from(["cyanprint", "status"]).unwrap();
+        assert_eq!(cli.secrets, SecretPolicy::Omit);
+        let cli = Cli::try_parse_from(["cyanprint", "update", "--secrets", "encrypt"]).unwrap();
+        assert_eq!(cli.secrets, SecretPolicy::Encrypt);
+        assert!(Cli::try_parse_from(["cyanprint", "status", "--secrets", "plain"]).is_err());
+    }
+
+    #[test]
+    fn test_status_command_flags() {
+        let cli = Cli::try_parse_from(["cyanprint", "status"]).unwrap();
+        if let Commands::Status {
+            path,
+            output,
+            exit_code,
+            ..
+        } = cli.command
+        {
+            assert_eq!(path, ".");
+            assert_eq!(output, OutputFormat::Text);
+            assert!(!exit_code);
+        } else {
+            panic!("Expected Status");
+        }
+
+        let cli = Cli::try_parse_from([
+            "cyanprint",
+            "status",
+            "svc",
+            "--output",
+            "json",
+            "--exit-code",
+        ])
+        .unwrap();
+        if let Commands::Status {
+            path,
+            output,
+            exit_code,
+            ..
+        } = cli.command
+        {
+            assert_eq!(path, "svc");
+            assert_eq!(output, OutputFormat::Json);
+            assert!(exit_code);
+        } else {
+            panic!("Expected Status");
+        }
+        assert!(Cli::try_parse_from(["cyanprint", "status", "--output", "yaml"]).is_err());
+    }
+
+    #[test]
+    fn test_resolve_command_flags() {
+        let cli = Cli::try_parse_from(["cyanprint", "resolve", "--continue"]).unwrap();
+        if let Commands::Resolve {
+            path,
+            continue_merge,
+            abort,
+        } = cli.command
+        {
+            assert_eq!(path, ".");
+            assert!(continue_merge);
+            assert!(!abort);
+        } else {
+            panic!("Expected Resolve");
+        }
+
+        let cli = Cli::try_parse_from(["cyanprint", "resolve", "svc", "--abort"]).unwrap();
+        if let Commands::Resolve {
+            path,
+            continue_merge,
+            abort,
+        } = cli.command
+        {
+            assert_eq!(path, "svc");
+            assert!(!continue_merge);
+            assert!(abort);
+        } else {
+            panic!("Expected Resolve");
+        }
+
+        assert!(Cli::try_parse_from(["cyanprint", "resolve", "--continue", "--abort"]).is_err());
+    }
+
     #[test]
     fn test_try_headless_flags() {
         let cli = Cli::try_parse_from([
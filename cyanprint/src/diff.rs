@@ -0,0 +1,164 @@
+//! Rendering for `cyanprint diff`: a preview of what an update would change.
+//!
+//! The preview runs the same MAP → LAYER → MERGE pipeline as `update`
+//! ([`batch_plan`](crate::run::batch_plan)) and then, instead of writing, renders the
+//! merged VFS against the files on disk as per-file unified diffs plus the list of
+//! paths the cleanup step would remove.
+
+use std::io::Write;
+
+use cyancoordinator::fs::{FileChangeKind, VfsDiff, unified_diff};
+
+use crate::run::BatchPlan;
+
+/// Render the preview of `plan` to `writer`. Returns the computed [`VfsDiff`] so callers
+/// can act on it (e.g. pick an exit code) without recomputing.
+pub fn write_plan_diff<W: Write>(plan: &BatchPlan, writer: &mut W) -> std::io::Result<VfsDiff> {
+    let diff = VfsDiff::compute(&plan.local_vfs, &plan.merged_vfs);
+
+    if diff.is_empty() {
+        writeln!(
+            writer,
+            "No changes: the working tree already matches the update"
+        )?;
+        return Ok(diff);
+    }
+
+    for change in diff
+        .changes
+        .iter()
+        .filter(|c| c.kind != FileChangeKind::Deleted)
+    {
+        let path = change.path.to_string_lossy();
+        let rendered = unified_diff(
+            &path,
+            plan.local_vfs.get_file(&change.path).map(Vec::as_slice),
+            plan.merged_vfs.get_file(&change.path).map(Vec::as_slice),
+        );
+        write!(writer, "{rendered}")?;
+    }
+
+    let deleted = diff.paths_of(FileChangeKind::Deleted);
+    if !deleted.is_empty() {
+        writeln!(writer, "\nFiles that would be removed:")?;
+        for path in &deleted {
+            writeln!(writer, "  {}", path.display())?;
+        }
+    }
+
+    // Marker-free conflicts are local edits the merge keeps, not something to resolve
+    let conflicted: Vec<_> = plan
+        .merged_vfs
+        .conflicted_paths()
+        .into_iter()
+        .filter(|p| {
+            plan.merged_vfs
+                .get_file(p)
+                .is_some_and(|c| crate::resolve::has_conflict_markers(c))
+        })
+        .collect();
+    if !conflicted.is_empty() {
+        writeln!(writer, "\nFiles that would be left with conflict markers:")?;
+        for path in &conflicted {
+            writeln!(writer, "  {}", path.display())?;
+        }
+    }
+
+    writeln!(
+        writer,
+        "\n{} added, {} modified, {} removed",
+        diff.paths_of(FileChangeKind::Added).len(),
+        diff.paths_of(FileChangeKind::Modified).len(),
+        deleted.len()
+    )?;
+    Ok(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use cyancoordinator::fs::VirtualFileSystem;
+
+    fn plan(local: &[(&str, &str)], merged: &[(&str, &str)]) -> BatchPlan {
+        let to_vfs = |files: &[(&str, &str)]| {
+            let mut vfs = VirtualFileSystem::new();
+            for (p, c) in files {
+                vfs.add_file(PathBuf::from(p), c.as_bytes().to_vec());
+            }
+            vfs
+        };
+        BatchPlan {
+            session_ids: Vec::new(),
+            local_vfs: to_vfs(local),
+            incoming_vfs: VirtualFileSystem::new(),
+            merged_vfs: to_vfs(merged),
+            file_conflicts: Vec::new(),
+            commands: Vec::new(),
+            managed_by_template: HashMap::new(),
+            final_answers: HashMap::new(),
+            final_secrets: HashMap::new(),
+            need_input: None,
+        }
+    }
+
+    #[test]
+    fn renders_diffs_removals_and_summary() {
+        let plan = plan(
+            &[
+                ("keep.txt", "a\n"),
+                ("edit.txt", "old\n"),
+                ("gone.txt", "x\n"),
+            ],
+            &[
+                ("keep.txt", "a\n"),
+                ("edit.txt", "new\n"),
+                ("add.txt", "hi\n"),
+            ],
+        );
+        let mut out = Vec::new();
+        let diff = write_plan_diff(&plan, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert_eq!(diff.changes.len(), 3);
+        assert!(out.contains("+++ b/add.txt"), "got:\n{out}");
+        assert!(out.contains("-old\n+new"), "got:\n{out}");
+        assert!(!out.contains("keep.txt"), "unchanged files are omitted");
+        assert!(out.contains("Files that would be removed:\n  gone.txt"));
+        assert!(out.contains("1 added, 1 modified, 1 removed"));
+    }
+
+    #[test]
+    fn lists_conflicted_files() {
+        let mut plan = plan(
+            &[("a.txt", "local\n"), ("b.txt", "edited\n")],
+            &[
+                (
+                    "a.txt",
+                    "<<<<<<< local\nx\n=======\ny\n>>>>>>> alice/web:2\n",
+                ),
+                ("b.txt", "edited\n"),
+            ],
+        );
+        plan.merged_vfs.mark_conflicted(PathBuf::from("a.txt"));
+        // A kept local edit (modify/delete) has no markers and is not listed
+        plan.merged_vfs.mark_conflicted(PathBuf::from("b.txt"));
+        let mut out = Vec::new();
+        write_plan_diff(&plan, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(
+            out.contains("Files that would be left with conflict markers:\n  a.txt\n\n"),
+            "got:\n{out}"
+        );
+    }
+
+    #[test]
+    fn reports_no_changes() {
+        let plan = plan(&[("a.txt", "a")], &[("a.txt", "a")]);
+        let mut out = Vec::new();
+        assert!(write_plan_diff(&plan, &mut out).unwrap().is_empty());
+        assert!(String::from_utf8(out).unwrap().starts_with("No changes"));
+    }
+}
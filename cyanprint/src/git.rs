@@ -1,13 +1,24 @@
-use std::path::Path;
-use std::process::Command;
+use std::path::{Path, PathBuf};
+
+use git2::{BranchType, IndexAddOption, Repository, Signature, StatusOptions};
 
 /// Error types for git operations
 #[derive(Debug)]
 pub enum GitError {
     NotAGitRepository,
-    GitNotInstalled,
     CommandFailed(String),
     IoError(std::io::Error),
+    Git(git2::Error),
+}
+
+impl From<git2::Error> for GitError {
+    fn from(err: git2::Error) -> Self {
+        if err.code() == git2::ErrorCode::NotFound && err.class() == git2::ErrorClass::Repository {
+            GitError::NotAGitRepository
+        } else {
+            GitError::Git(err)
+        }
+    }
 }
 
 impl From<std::io::Error> for GitError {
@@ -20,71 +31,205 @@ impl std::fmt::Display for GitError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             GitError::NotAGitRepository => write!(f, "Not a git repository"),
-            GitError::GitNotInstalled => write!(f, "Git is not installed"),
             GitError::CommandFailed(msg) => write!(f, "Git command failed: {msg}"),
             GitError::IoError(err) => write!(f, "IO error: {err}"),
+            GitError::Git(err) => write!(f, "Git error: {}", err.message()),
         }
     }
 }
 
 impl std::error::Error for GitError {}
 
-/// Check if the git working directory at `path` has uncommitted changes.
-///
-/// Uses `git status --porcelain` to detect:
-/// - Modified files (staged and unstaged)
-/// - New files
-/// - Deleted files
+/// Check if the git working directory at `path` has uncommitted changes: staged or
+/// unstaged modifications, deletions and untracked files, as [`ProjectRepo::is_dirty`]
+/// counts them.
 ///
-/// Returns `Ok(true)` if there are uncommitted changes.
-/// Returns `Ok(false)` if the working directory is clean.
-/// Returns `Err(GitError)` if git check fails.
+/// Returns `Err(GitError::NotAGitRepository)` when `path` is in no repository.
 pub fn is_git_dirty(path: &Path) -> Result<bool, GitError> {
-    let output = Command::new("git")
-        .args(["status", "--porcelain"])
-        .current_dir(path)
-        .output()
-        .map_err(|e| {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                GitError::GitNotInstalled
-            } else {
-                GitError::CommandFailed(e.to_string())
-            }
-        })?;
+    ProjectRepo::open(path)?.is_dirty()
+}
 
-    if !output.status.success() {
-        // Not a git repository or git error - return specific error
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        if stderr.contains("not a git repository") {
-            return Err(GitError::NotAGitRepository);
-        }
-        return Err(GitError::CommandFailed(stderr.to_string()));
+/// The uncommitted changes at `path` for display, one `git status --porcelain`-style
+/// line each (`XY path`).
+pub fn get_modified_files(path: &Path) -> Result<Vec<String>, GitError> {
+    Ok(ProjectRepo::open(path)?
+        .uncommitted_changes()?
+        .into_iter()
+        .map(|(path, status)| format!("{} {path}", porcelain_code(status)))
+        .collect())
+}
+
+/// The two-letter index/worktree code `git status --porcelain` shows for `status`.
+fn porcelain_code(status: git2::Status) -> String {
+    use git2::Status;
+
+    if status.contains(Status::WT_NEW) {
+        return "??".to_string();
     }
+    let index = if status.contains(Status::INDEX_NEW) {
+        'A'
+    } else if status.contains(Status::INDEX_DELETED) {
+        'D'
+    } else if status.contains(Status::INDEX_RENAMED) {
+        'R'
+    } else if status.intersects(Status::INDEX_MODIFIED | Status::INDEX_TYPECHANGE) {
+        'M'
+    } else {
+        ' '
+    };
+    let worktree = if status.contains(Status::WT_DELETED) {
+        'D'
+    } else if status.contains(Status::WT_RENAMED) {
+        'R'
+    } else if status.intersects(Status::WT_MODIFIED | Status::WT_TYPECHANGE) {
+        'M'
+    } else if status.contains(Status::CONFLICTED) {
+        'U'
+    } else {
+        ' '
+    };
+    format!("{index}{worktree}")
+}
+
+/// Author of commits made when git has no `user.name` / `user.email` configured, as on
+/// a fresh CI runner.
+const FALLBACK_AUTHOR: (&str, &str) = ("cyanprint", "cyanprint@users.noreply.cyanprint.dev");
+
+/// Directories cyanprint keeps in a project that never belong in a commit.
+const UNCOMMITTED_DIRS: &[&str] = &[crate::resolve::MERGE_DIR, cyancoordinator::fs::TXN_DIR];
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    Ok(!stdout.trim().is_empty())
+/// The git repository containing a project, for the branch-and-commit flow of
+/// `update --branch`.
+pub struct ProjectRepo {
+    repo: Repository,
+    /// The project directory, relative to the repository's working tree.
+    project: PathBuf,
 }
 
-/// Get the list of modified files for display purposes.
-pub fn get_modified_files(path: &Path) -> Result<Vec<String>, GitError> {
-    let output = Command::new("git")
-        .args(["status", "--porcelain"])
-        .current_dir(path)
-        .output()
-        .map_err(|e| GitError::CommandFailed(e.to_string()))?;
-
-    if !output.status.success() {
-        return Ok(Vec::new());
+impl ProjectRepo {
+    /// Open the repository `project_dir` belongs to.
+    pub fn open(project_dir: &Path) -> Result<Self, GitError> {
+        let repo = Repository::discover(project_dir)?;
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| {
+                GitError::CommandFailed("bare repositories have no working tree".into())
+            })?
+            .canonicalize()?;
+        let project = project_dir
+            .canonicalize()?
+            .strip_prefix(&workdir)
+            .map(Path::to_path_buf)
+            .map_err(|_| GitError::NotAGitRepository)?;
+        Ok(Self { repo, project })
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let files: Vec<String> = stdout
-        .lines()
-        .filter(|line| !line.is_empty())
-        .map(|line| line.to_string())
-        .collect();
+    /// Whether the working tree has uncommitted changes, untracked files included.
+    pub fn is_dirty(&self) -> Result<bool, GitError> {
+        Ok(!self.uncommitted_changes()?.is_empty())
+    }
 
-    Ok(files)
+    /// Every path in the working tree that differs from HEAD, with its status: the one
+    /// definition of a dirty tree. Untracked files count; ignored ones and the project's
+    /// [`UNCOMMITTED_DIRS`] do not.
+    fn uncommitted_changes(&self) -> Result<Vec<(String, git2::Status)>, GitError> {
+        let scratch: Vec<PathBuf> = UNCOMMITTED_DIRS
+            .iter()
+            .map(|dir| self.project.join(dir))
+            .collect();
+        let mut opts = StatusOptions::new();
+        opts.include_untracked(true).include_ignored(false);
+        Ok(self
+            .repo
+            .statuses(Some(&mut opts))?
+            .iter()
+            .map(|entry| {
+                let path = String::from_utf8_lossy(entry.path_bytes()).into_owned();
+                (path, entry.status())
+            })
+            .filter(|(path, _)| !scratch.iter().any(|dir| Path::new(path).starts_with(dir)))
+            .collect())
+    }
+
+    /// Short name of the checked-out branch.
+    pub fn current_branch(&self) -> Result<String, GitError> {
+        let head = self.repo.head()?;
+        match head.shorthand() {
+            Some(name) if head.is_branch() => Ok(name.to_string()),
+            _ => Err(GitError::CommandFailed("HEAD is not on a branch".into())),
+        }
+    }
+
+    /// Create `name` at the current commit and check it out. The working tree is left
+    /// as it is; both branches point at the same commit.
+    pub fn create_branch(&self, name: &str) -> Result<(), GitError> {
+        if self.repo.find_branch(name, BranchType::Local).is_ok() {
+            return Err(GitError::CommandFailed(format!(
+                "branch '{name}' already exists"
+            )));
+        }
+        let head = self.repo.head()?.peel_to_commit()?;
+        let branch = self.repo.branch(name, &head, false)?;
+        let refname = branch
+            .get()
+            .name()
+            .ok_or_else(|| GitError::CommandFailed(format!("invalid branch name '{name}'")))?;
+        self.repo.set_head(refname)?;
+        Ok(())
+    }
+
+    /// Check out `to` again and delete `name`, which must still point at the same commit
+    /// as `to` (nothing was committed on it).
+    pub fn abandon_branch(&self, name: &str, to: &str) -> Result<(), GitError> {
+        self.repo.set_head(&format!("refs/heads/{to}"))?;
+        self.repo.find_branch(name, BranchType::Local)?.delete()?;
+        Ok(())
+    }
+
+    /// Stage every change below the project directory, cyanprint's scratch directories
+    /// aside, and commit it on the current branch. Returns `None` when there was nothing
+    /// to commit.
+    pub fn commit_project(&self, message: &str) -> Result<Option<git2::Oid>, GitError> {
+        let mut index = self.repo.index()?;
+        let pathspec = if self.project.as_os_str().is_empty() {
+            PathBuf::from(".")
+        } else {
+            self.project.clone()
+        };
+        let scratch: Vec<PathBuf> = UNCOMMITTED_DIRS
+            .iter()
+            .map(|dir| self.project.join(dir))
+            .collect();
+        let mut skip_scratch = |path: &Path, _: &[u8]| -> i32 {
+            i32::from(scratch.iter().any(|dir| path.starts_with(dir)))
+        };
+        index.add_all(
+            [&pathspec],
+            IndexAddOption::DEFAULT,
+            Some(&mut skip_scratch),
+        )?;
+        index.update_all([&pathspec], Some(&mut skip_scratch))?;
+        index.write()?;
+
+        let tree = self.repo.find_tree(index.write_tree()?)?;
+        let parent = self.repo.head()?.peel_to_commit()?;
+        if parent.tree_id() == tree.id() {
+            return Ok(None);
+        }
+        let signature = self
+            .repo
+            .signature()
+            .or_else(|_| Signature::now(FALLBACK_AUTHOR.0, FALLBACK_AUTHOR.1))?;
+        let oid = self.repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &[&parent],
+        )?;
+        Ok(Some(oid))
+    }
 }
 
 #[cfg(test)]
@@ -105,13 +250,53 @@ mod tests {
             Err(GitError::NotAGitRepository) => {
                 // Also valid if we're not in a git repo
             }
-            Err(GitError::GitNotInstalled) => {
-                // Valid in environments where git is not on PATH
-            }
-            Err(GitError::CommandFailed(_)) | Err(GitError::IoError(_)) => {
+            Err(GitError::CommandFailed(_)) | Err(GitError::IoError(_)) | Err(GitError::Git(_)) => {
                 // Valid in sandboxed build environments (e.g. Nix) where
                 // git may fail due to restricted filesystem or permissions
             }
         }
     }
+
+    #[test]
+    fn dirty_check_and_modified_files_share_one_definition() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "a\n").unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "ignored\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("a.txt")).unwrap();
+        index.add_path(Path::new(".gitignore")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("t", "t@example.com").unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+            .unwrap();
+
+        // Ignored files and cyanprint's own scratch directories never make the tree dirty
+        std::fs::write(dir.path().join("ignored"), "x").unwrap();
+        for scratch in UNCOMMITTED_DIRS {
+            std::fs::create_dir_all(dir.path().join(scratch)).unwrap();
+            std::fs::write(dir.path().join(scratch).join("f"), "x").unwrap();
+        }
+        assert!(!is_git_dirty(dir.path()).unwrap());
+        assert!(get_modified_files(dir.path()).unwrap().is_empty());
+
+        std::fs::write(dir.path().join("a.txt"), "changed\n").unwrap();
+        std::fs::write(dir.path().join("new.txt"), "new\n").unwrap();
+        assert!(is_git_dirty(dir.path()).unwrap());
+        assert!(ProjectRepo::open(dir.path()).unwrap().is_dirty().unwrap());
+        assert_eq!(
+            get_modified_files(dir.path()).unwrap(),
+            vec![" M a.txt".to_string(), "?? new.txt".to_string()]
+        );
+    }
+
+    #[test]
+    fn outside_a_repository_is_reported() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert!(matches!(
+            is_git_dirty(dir.path()),
+            Err(GitError::NotAGitRepository)
+        ));
+    }
 }
@@ -21,7 +21,10 @@ use std::io::{IsTerminal, Read};
 use cyanprompt::domain::models::answer::Answer;
 use cyanprompt::domain::models::headless::HeadlessEnvelope;
 use cyanprompt::domain::models::question::Question;
+use cyanregistry::cli::env_subst::substitute_env_vars;
+use serde::Deserialize;
 
+use crate::report::RunReport;
 use crate::try_cmd::TryHeadlessOutcome;
 
 /// Outcome of a (possibly headless) `cyan_run` / `cyan_update` invocation.
@@ -38,6 +41,8 @@ use crate::try_cmd::TryHeadlessOutcome;
 pub struct CyanRunResult {
     pub session_ids: Vec<String>,
     pub need_input: Option<Question>,
+    /// What a completed run changed, for `--output json`.
+    pub report: Option<RunReport>,
 }
 
 impl CyanRunResult {
@@ -46,6 +51,7 @@ impl CyanRunResult {
         Self {
             session_ids,
             need_input: None,
+            report: None,
         }
     }
 }
@@ -68,36 +74,162 @@ impl Error for HeadlessExit {}
 ///
 /// The value shape reuses [`Answer`]'s serde representation
 /// (`{"type":"String","value":"…"}`, `{"type":"Bool","value":true}`,
-/// `{"type":"StringArray","value":["…"]}`) — the same `{type,value}` shape used
+/// `{"type":"StringArray","value":["…"]}`, `{"type":"Number","value":8080}`,
+/// `{"type":"Object","value":{…}}`) — the same `{type,value}` shape used
 /// elsewhere for persisted answers. An empty / whitespace-only input is a valid
 /// "no answers yet" first call and yields an empty map.
+///
+/// An entry may instead reference its value with `from_env` or `from_file` (see
+/// [`resolve_answer_ref`]), so CI secrets never have to be written into the file.
 pub fn parse_answers(raw: &str) -> Result<HashMap<String, Answer>, Box<dyn Error + Send>> {
     let trimmed = raw.trim();
     if trimmed.is_empty() {
         return Ok(HashMap::new());
     }
-    serde_json::from_str::<HashMap<String, Answer>>(trimmed).map_err(|e| {
-        // serde's Display can EMBED the offending value (e.g. a mistyped password supplied
-        // as `{"token":"sup3r-s3cr3t"}` instead of the `{type,value}` shape yields
-        // `invalid type: string "sup3r-s3cr3t", expected …`). This error becomes the
-        // headless `error` envelope, so building it from serde's value-bearing text would
-        // leak the submitted value — and an error message must never echo a supplied answer
-        // value. Construct a value-free message from only the error CATEGORY plus its
-        // location instead.
-        let kind = match e.classify() {
-            serde_json::error::Category::Io => "I/O error reading answers JSON",
-            serde_json::error::Category::Eof => "answers JSON ended unexpectedly",
-            serde_json::error::Category::Syntax => "answers JSON is not valid JSON",
-            serde_json::error::Category::Data => {
-                "answers JSON does not match the expected {id: {type, value}} shape"
+    let entries =
+        serde_json::from_str::<HashMap<String, serde_json::Value>>(trimmed).map_err(|e| {
+            // serde's Display can EMBED the offending value (e.g. a mistyped password supplied
+            // as `{"token":"sup3r-s3cr3t"}` instead of the `{type,value}` shape yields
+            // `invalid type: string "sup3r-s3cr3t", expected …`). This error becomes the
+            // headless `error` envelope, so building it from serde's value-bearing text would
+            // leak the submitted value — and an error message must never echo a supplied answer
+            // value. Construct a value-free message from only the error CATEGORY plus its
+            // location instead.
+            let kind = match e.classify() {
+                serde_json::error::Category::Io => "I/O error reading answers JSON",
+                serde_json::error::Category::Eof => "answers JSON ended unexpectedly",
+                serde_json::error::Category::Syntax => "answers JSON is not valid JSON",
+                serde_json::error::Category::Data => {
+                    "answers JSON does not match the expected {id: {type, value}} shape"
+                }
+            };
+            answers_error(format!(
+                "failed to parse answers JSON: {kind} (line {}, column {})",
+                e.line(),
+                e.column()
+            ))
+        })?;
+
+    entries
+        .into_iter()
+        .map(|(id, value)| {
+            let answer = if is_answer_ref(&value) {
+                resolve_answer_ref(&id, value)?
+            } else {
+                // Same value-free rule as above: name the id, never the value.
+                serde_json::from_value::<Answer>(value).map_err(|_| {
+                    answers_error(format!(
+                        "failed to parse answers JSON: answer '{id}' does not match the \
+                         expected {{type, value}} shape"
+                    ))
+                })?
+            };
+            Ok((id, answer))
+        })
+        .collect()
+}
+
+fn answers_error(msg: String) -> Box<dyn Error + Send> {
+    Box::new(std::io::Error::other(msg))
+}
+
+/// An answers-file entry whose value is read at load time instead of written inline.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AnswerRef {
+    #[serde(rename = "type", default = "default_ref_type")]
+    kind: String,
+    from_env: Option<String>,
+    from_file: Option<String>,
+}
+
+fn default_ref_type() -> String {
+    "String".to_string()
+}
+
+fn is_answer_ref(value: &serde_json::Value) -> bool {
+    value
+        .as_object()
+        .is_some_and(|o| o.contains_key("from_env") || o.contains_key("from_file"))
+}
+
+/// Resolve a `from_env` / `from_file` answer reference into an [`Answer`].
+///
+/// - `{"type":"String","from_env":"DB_NAME"}` reads `DB_NAME`, with the same
+///   `${VAR:-default}` semantics as `cyan.yaml` substitution: `"DB_NAME:-app"` falls
+///   back to `app`, and an unset or empty variable without a default is an error.
+/// - `{"from_file":"./secrets/token"}` reads the file (relative to the working
+///   directory; `${VAR}` references in the path are substituted), dropping one
+///   trailing newline.
+///
+/// `type` defaults to `String`. A `Bool` source must read `true` or `false`, a `Number`
+/// source a number; a `StringArray` source must hold a JSON array of strings and an
+/// `Object` source a JSON object. Errors name the answer
+/// id and the source, never the value read.
+fn resolve_answer_ref(id: &str, value: serde_json::Value) -> Result<Answer, Box<dyn Error + Send>> {
+    let r: AnswerRef = serde_json::from_value(value).map_err(|_| {
+        answers_error(format!(
+            "answer '{id}': expected {{type, from_env}} or {{type, from_file}}"
+        ))
+    })?;
+
+    let (source, raw) = match (r.from_env, r.from_file) {
+        (Some(var), None) => {
+            let raw = substitute_env_vars(&format!("${{{var}}}"))
+                .map_err(|e| answers_error(format!("answer '{id}': {e}")))?;
+            (format!("environment variable '{var}'"), raw)
+        }
+        (None, Some(path)) => {
+            let path = substitute_env_vars(&path)
+                .map_err(|e| answers_error(format!("answer '{id}': {e}")))?;
+            let mut raw = fs::read_to_string(&path).map_err(|e| {
+                answers_error(format!("answer '{id}': failed to read '{path}': {e}"))
+            })?;
+            if raw.ends_with('\n') {
+                raw.pop();
+                if raw.ends_with('\r') {
+                    raw.pop();
+                }
             }
-        };
-        Box::new(std::io::Error::other(format!(
-            "failed to parse answers JSON: {kind} (line {}, column {})",
-            e.line(),
-            e.column()
-        ))) as Box<dyn Error + Send>
-    })
+            (format!("file '{path}'"), raw)
+        }
+        _ => {
+            return Err(answers_error(format!(
+                "answer '{id}': set exactly one of from_env and from_file"
+            )));
+        }
+    };
+
+    match r.kind.as_str() {
+        "String" => Ok(Answer::String(raw)),
+        "Bool" => match raw.trim() {
+            "true" => Ok(Answer::Bool(true)),
+            "false" => Ok(Answer::Bool(false)),
+            _ => Err(answers_error(format!(
+                "answer '{id}': {source} must be 'true' or 'false'"
+            ))),
+        },
+        "StringArray" => serde_json::from_str::<Vec<String>>(&raw)
+            .map(Answer::StringArray)
+            .map_err(|_| {
+                answers_error(format!(
+                    "answer '{id}': {source} must hold a JSON array of strings"
+                ))
+            }),
+        "Number" => raw
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite())
+            .map(Answer::Number)
+            .ok_or_else(|| answers_error(format!("answer '{id}': {source} must be a number"))),
+        "Object" => serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&raw)
+            .map(Answer::Object)
+            .map_err(|_| answers_error(format!("answer '{id}': {source} must hold a JSON object"))),
+        other => Err(answers_error(format!(
+            "answer '{id}': unknown answer type '{other}'"
+        ))),
+    }
 }
 
 /// Read answers from an arbitrary reader (the stdin path), parsing the content as
@@ -221,11 +353,15 @@ where
     let env = match r {
         Ok(result) => {
             clean_sessions(&result.session_ids);
-            match result.need_input {
-                Some(question) => HeadlessEnvelope::NeedInput {
+            match (result.need_input, result.report) {
+                (Some(question), _) => HeadlessEnvelope::NeedInput {
                     question: QuestionWire::from(&question),
                 },
-                None => HeadlessEnvelope::Done,
+                (None, Some(report)) => {
+                    return emit_done_with_report(writer, &report)
+                        .map_err(|e| Box::new(e) as Box<dyn Error + Send>);
+                }
+                (None, None) => HeadlessEnvelope::Done,
             }
         }
         Err(e) => HeadlessEnvelope::error(e.to_string()),
@@ -233,6 +369,18 @@ where
     emit_to(writer, &env).map_err(|e| Box::new(e) as Box<dyn Error + Send>)
 }
 
+/// Emit the `done` envelope with the run's [`RunReport`] under `report` (`--output
+/// json`). Same single-line contract and exit code as [`emit_to`].
+fn emit_done_with_report<W: std::io::Write>(
+    writer: &mut W,
+    report: &RunReport,
+) -> Result<(), HeadlessExit> {
+    let mut json = serde_json::to_value(HeadlessEnvelope::Done)
+        .expect("HeadlessEnvelope serialization is infallible");
+    json["report"] = serde_json::to_value(report).map_err(|_| HeadlessExit(1))?;
+    writeln!(writer, "{json}").map_err(|_| HeadlessExit(1))
+}
+
 /// Finish a headless `try` run: convert the [`TryHeadlessOutcome`] (or error) into the
 /// single JSON envelope, emit it on `writer`, and map it to the exit code. `Done` → `done`
 /// (exit 0); `NeedInput(question)` → `need_input` (exit 2) carrying the question; any error
@@ -304,9 +452,13 @@ mod tests {
         let raw = r#"{
             "name": {"type":"String","value":"demo"},
             "features": {"type":"StringArray","value":["a","b"]},
-            "useDb": {"type":"Bool","value":true}
+            "useDb": {"type":"Bool","value":true},
+            "port": {"type":"Number","value":8080},
+            "db": {"type":"Object","value":{"host":"localhost"}}
         }"#;
         let map = parse_answers(raw).unwrap();
+        assert!(matches!(map.get("port"), Some(Answer::Number(n)) if *n == 8080.0));
+        assert!(matches!(map.get("db"), Some(Answer::Object(o)) if o["host"] == "localhost"));
         assert!(matches!(map.get("name"), Some(Answer::String(s)) if s == "demo"));
         assert!(matches!(map.get("features"), Some(Answer::StringArray(v)) if v.len() == 2));
         assert!(matches!(map.get("useDb"), Some(Answer::Bool(true))));
@@ -347,6 +499,58 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_answers_resolves_from_env() {
+        std::env::set_var("CYAN_TEST_ANSWER_DB", "orders");
+        std::env::remove_var("CYAN_TEST_ANSWER_MISSING");
+        let raw = r#"{
+            "db": {"type":"String","from_env":"CYAN_TEST_ANSWER_DB"},
+            "port": {"type":"Number","from_env":"CYAN_TEST_ANSWER_MISSING:-5432"}
+        }"#;
+        let map = parse_answers(raw).unwrap();
+        assert!(matches!(map.get("db"), Some(Answer::String(s)) if s == "orders"));
+        assert!(matches!(map.get("port"), Some(Answer::Number(n)) if *n == 5432.0));
+
+        let err = parse_answers(r#"{"db":{"from_env":"CYAN_TEST_ANSWER_MISSING"}}"#)
+            .expect_err("an unset variable without a default must be an error");
+        assert!(err.to_string().contains("CYAN_TEST_ANSWER_MISSING"));
+        std::env::remove_var("CYAN_TEST_ANSWER_DB");
+    }
+
+    #[test]
+    fn parse_answers_resolves_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let token = dir.path().join("token");
+        std::fs::write(&token, "t0ken\n").unwrap();
+        let flags = dir.path().join("flags");
+        std::fs::write(&flags, r#"["a","b"]"#).unwrap();
+        let raw = serde_json::json!({
+            "token": {"from_file": token},
+            "flags": {"type": "StringArray", "from_file": flags},
+        })
+        .to_string();
+        let map = parse_answers(&raw).unwrap();
+        assert!(matches!(map.get("token"), Some(Answer::String(s)) if s == "t0ken"));
+        assert!(matches!(map.get("flags"), Some(Answer::StringArray(v)) if v.len() == 2));
+    }
+
+    #[test]
+    fn parse_answers_ref_errors_are_value_free() {
+        let secret = "sup3r-s3cr3t";
+        std::env::set_var("CYAN_TEST_ANSWER_NOT_BOOL", secret);
+        let err = parse_answers(r#"{"ok":{"type":"Bool","from_env":"CYAN_TEST_ANSWER_NOT_BOOL"}}"#)
+            .expect_err("a non-boolean value must be an error");
+        let msg = err.to_string();
+        assert!(
+            !msg.contains(secret),
+            "error must not echo the value: {msg}"
+        );
+        assert!(msg.contains("'ok'"));
+        std::env::remove_var("CYAN_TEST_ANSWER_NOT_BOOL");
+
+        assert!(parse_answers(r#"{"x":{"from_env":"A","from_file":"b"}}"#).is_err());
+    }
+
     // AC5: answers accepted from a file.
     #[test]
     fn read_answers_from_file() {
@@ -473,6 +677,7 @@ mod tests {
         let result = CyanRunResult {
             session_ids: vec!["session-A".to_string(), "session-B".to_string()],
             need_input: Some(text("project_name")),
+            report: None,
         };
         let mut buf = Vec::new();
         let res = finish_headless(Ok(result), &mut buf, |ids| {
@@ -500,6 +705,7 @@ mod tests {
         let result = CyanRunResult {
             session_ids: vec!["s1".to_string()],
             need_input: None,
+            report: None,
         };
         let mut buf = Vec::new();
         let res = finish_headless(Ok(result), &mut buf, |ids| {
@@ -510,6 +716,24 @@ mod tests {
         assert_command_stdout_single_json(&buf, "done", &res, None);
     }
 
+    // `--output json`: the report rides on the same single `done` object.
+    #[test]
+    fn finish_headless_done_carries_the_report() {
+        let report = RunReport {
+            unresolved: vec!["a.txt".to_string()],
+            ..RunReport::default()
+        };
+        let result = CyanRunResult {
+            report: Some(report),
+            ..CyanRunResult::completed(Vec::new())
+        };
+        let mut buf = Vec::new();
+        let res = finish_headless(Ok(result), &mut buf, |_| {});
+        assert_command_stdout_single_json(&buf, "done", &res, None);
+        let v: serde_json::Value = serde_json::from_slice(&buf).unwrap();
+        assert_eq!(v["report"]["unresolved"][0], "a.txt");
+    }
+
     // `create`/`update` boundary: a run error emits a single `error` JSON object and
     // exits 1. On error the sessions are unknown to the caller (never returned), so no
     // cleanup closure runs.
@@ -626,6 +850,7 @@ mod tests {
             default: None,
             desc: None,
             initial: None,
+            constraints: Default::default(),
             id: id.to_string(),
         })
     }
@@ -653,7 +878,7 @@ mod tests {
             TemplateState::NeedInput(question, _) => HeadlessEnvelope::NeedInput {
                 question: QuestionWire::from(question),
             },
-            TemplateState::Complete(_, _) => HeadlessEnvelope::Done,
+            TemplateState::Complete(..) => HeadlessEnvelope::Done,
             TemplateState::Err(message) => HeadlessEnvelope::error(message),
             // The headless driver never returns QnA() as a terminal state.
             TemplateState::QnA() => {
@@ -813,10 +1038,12 @@ mod tests {
             TemplateState::NeedInput(question, _) => Ok(CyanRunResult {
                 session_ids: session_ids.clone(),
                 need_input: Some(question),
+                report: None,
             }),
-            TemplateState::Complete(_, _) => Ok(CyanRunResult {
+            TemplateState::Complete(..) => Ok(CyanRunResult {
                 session_ids: session_ids.clone(),
                 need_input: None,
+                report: None,
             }),
             TemplateState::Err(message) => {
                 Err(Box::new(std::io::Error::other(message)) as Box<dyn Error + Send>)
@@ -904,7 +1131,7 @@ mod tests {
         // its Ok(()) outcome to a single `done` envelope, exit 0.
         assert!(matches!(
             engine.start_headless(Some(answers)),
-            TemplateState::Complete(_, _)
+            TemplateState::Complete(..)
         ));
         let mut buf = Vec::new();
         let res = finish_headless_try(Ok(TryHeadlessOutcome::Done), &mut buf);
@@ -935,7 +1162,7 @@ mod tests {
         );
         assert!(matches!(
             engine.start_headless(Some(answers)),
-            TemplateState::Complete(_, _)
+            TemplateState::Complete(..)
         ));
         let mut buf = Vec::new();
         let res = finish_headless_try(Ok(TryHeadlessOutcome::Done), &mut buf);
@@ -1039,6 +1266,7 @@ mod tests {
         let result = CyanRunResult {
             session_ids: Vec::new(),
             need_input: Some(question),
+            report: None,
         };
         let mut buf = Vec::new();
         let res = finish_headless(Ok(result), &mut buf, |_| {});
@@ -1056,7 +1284,7 @@ mod tests {
     fn state_variant_name(state: &TemplateState) -> &'static str {
         match state {
             TemplateState::QnA() => "QnA",
-            TemplateState::Complete(_, _) => "Complete",
+            TemplateState::Complete(..) => "Complete",
             TemplateState::NeedInput(_, _) => "NeedInput",
             TemplateState::Err(_) => "Err",
         }
@@ -1,5 +1,6 @@
 use std::error::Error;
-use std::path::Path;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 use std::rc::Rc;
 
@@ -9,14 +10,19 @@ use clap::Parser;
 use cyancoordinator::cache::{CacheConfig, CacheStore, resolve_cache_dir};
 use cyancoordinator::client::{CyanCoordinatorClient, new_client};
 use cyancoordinator::session::DefaultSessionIdGenerator;
+use cyancoordinator::state::StateLocation;
 use cyanregistry::cli::mapper::read_build_config;
 use cyanregistry::http::client::CyanRegistryClient;
 
+use crate::answers_cmd::{AnswersExport, ImportOptions, cyan_import};
 use crate::commands::{
-    CacheCommands, Cli, Commands, DaemonCommands, PushArgs, PushCommands, TestCommands, TryCommands,
+    AnswersCommands, CacheCommands, Cli, Commands, DaemonCommands, OutputFormat, PushArgs,
+    PushCommands, StateCommands, TestCommands, TryCommands,
 };
 use crate::coord::{start_coordinator, stop_coordinator};
 use crate::docker::{BuildOptions, BuildOutput, BuildxBuilder};
+use crate::headless::CyanRunResult;
+use crate::report::{JsonStdout, RunReport};
 use crate::run::cyan_run;
 use crate::test_cmd::init::run_init;
 use crate::test_cmd::report::write_human_report;
@@ -25,18 +31,27 @@ use crate::test_cmd::{
 };
 use crate::try_cmd::{execute_try_command, execute_try_group_command};
 use crate::update::UserAborted;
-use crate::update::cyan_update;
+use crate::update::{
+    Reanswer, RecursiveOptions, cyan_pin, cyan_remove, cyan_undo, cyan_update,
+    cyan_update_recursive, update_on_branch, write_recursive_report,
+};
 use crate::util::parse_ref;
 
+pub mod answers_cmd;
 pub mod command_executor;
 pub mod commands;
 pub mod coord;
+pub mod diff;
 pub mod docker;
 pub mod errors;
 pub mod git;
 pub mod headless;
 pub mod port;
+pub mod report;
+pub mod resolve;
 pub mod run;
+pub mod state_cmd;
+pub mod status;
 pub mod test_cmd;
 pub mod try_cmd;
 pub mod update;
@@ -58,6 +73,172 @@ fn main() -> ExitCode {
     }
 }
 
+/// Keep the run report only when `--output json` asked for it.
+fn select_report(
+    r: Result<CyanRunResult, Box<dyn Error + Send>>,
+    output: OutputFormat,
+) -> Result<CyanRunResult, Box<dyn Error + Send>> {
+    r.map(|result| match output {
+        OutputFormat::Json => result,
+        OutputFormat::Text => CyanRunResult {
+            report: None,
+            ..result
+        },
+    })
+}
+
+/// Outside headless mode, `--output json` holds stdout for the report: progress goes to
+/// stderr meanwhile. `None` for text output and under headless, which has its envelope.
+fn json_stdout(
+    output: OutputFormat,
+    headless: bool,
+) -> Result<Option<JsonStdout>, Box<dyn Error + Send>> {
+    if headless || output == OutputFormat::Text {
+        return Ok(None);
+    }
+    JsonStdout::capture()
+        .map(Some)
+        .map_err(|e| Box::new(e) as Box<dyn Error + Send>)
+}
+
+/// Print the `--output json` report as the one document on stdout (an empty report when
+/// the run had nothing to do).
+fn print_report(
+    json: Option<JsonStdout>,
+    report: Option<RunReport>,
+) -> Result<(), Box<dyn Error + Send>> {
+    let Some(json) = json else {
+        return Ok(());
+    };
+    emit_json(json, &report.unwrap_or_default())
+}
+
+/// Under `--output json`, report a failed run as a `{"error": ...}` document and exit 1.
+fn print_failure(json: Option<JsonStdout>, error: &dyn Error) -> Result<(), Box<dyn Error + Send>> {
+    let Some(json) = json else {
+        return Ok(());
+    };
+    emit_json(json, &serde_json::json!({ "error": error.to_string() }))?;
+    Err(Box::new(crate::headless::HeadlessExit(1)))
+}
+
+fn emit_json(
+    json: JsonStdout,
+    document: &impl serde::Serialize,
+) -> Result<(), Box<dyn Error + Send>> {
+    let document =
+        serde_json::to_value(document).map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
+    json.emit(&document)
+        .map_err(|e| Box::new(e) as Box<dyn Error + Send>)
+}
+
+/// The arguments `create` and `add` share.
+struct CreateArgs {
+    template_ref: String,
+    path: Option<String>,
+    coordinator_endpoint: String,
+    headless: bool,
+    answers: Option<String>,
+    output: OutputFormat,
+}
+
+/// Run a template into `args.path`. `check` vets the `<user>/<template>` key before the
+/// template is fetched.
+fn run_create(
+    args: CreateArgs,
+    registry: CyanRegistryClient,
+    debug: bool,
+    cache_config: cyancoordinator::cache::CacheConfig,
+    binary_policy: cyancoordinator::fs::BinaryMergePolicy,
+    check: impl FnOnce(&str) -> Result<(), Box<dyn Error + Send>>,
+) -> Result<(), Box<dyn Error + Send>> {
+    let CreateArgs {
+        template_ref,
+        path,
+        coordinator_endpoint,
+        headless,
+        answers,
+        output,
+    } = args;
+    let json = json_stdout(output, headless)?;
+    let session_id_generator = Box::new(DefaultSessionIdGenerator);
+
+    // Headless: ingest the supplied answers up front via the shared helper;
+    // a bad source is an `error` envelope (exit 1), never a panic.
+    let headless_answers = load_headless_answers(headless, answers.as_deref())?;
+
+    let username = parse_ref(template_ref.clone())
+        .map(|(u, _, _)| u)
+        .unwrap_or_else(|_| "unknown".to_string());
+
+    let r = parse_ref(template_ref)
+        .and_then(|(u, n, v)| {
+            check(&format!("{u}/{n}"))?;
+            crate::hprogress!(
+                headless,
+                "Retrieving template '{}/{}:{}' from registry...",
+                u,
+                n,
+                v.unwrap_or(-1)
+            );
+            let r = registry.get_template(u.clone(), n.clone(), v);
+            crate::hprogress!(
+                headless,
+                "Retrieved template '{}/{}:{}' from registry.",
+                u,
+                n,
+                v.unwrap_or(-1)
+            );
+            r
+        })
+        .and_then(|tv| {
+            let coord_client = CyanCoordinatorClient::new(coordinator_endpoint.clone());
+            let registry_ref = Rc::new(registry);
+
+            cyan_run(
+                session_id_generator,
+                path,
+                tv,
+                coord_client,
+                username.clone(),
+                Rc::clone(&registry_ref),
+                debug,
+                cache_config,
+                binary_policy,
+                headless,
+                headless_answers,
+                std::collections::HashMap::new(),
+            )
+        });
+    let r = select_report(r, output);
+
+    if headless {
+        let clean = crate::headless::headless_session_cleaner(coordinator_endpoint.clone());
+        let stdout = std::io::stdout();
+        return crate::headless::finish_headless(r, &mut stdout.lock(), clean);
+    }
+
+    match r {
+        Ok(result) => {
+            println!("Completed successfully");
+            let coord_client = CyanCoordinatorClient::new(coordinator_endpoint.clone());
+            println!("Cleaning up all sessions...");
+            for sid in result.session_ids {
+                println!("Cleaning up session: {sid}");
+                let _ = coord_client.clean(sid);
+            }
+            println!("Cleaned up all sessions");
+            print_report(json, result.report)?;
+        }
+        Err(e) => {
+            eprintln!("Error: {e:#?}");
+            println!("No sessions to clean up");
+            print_failure(json, e.as_ref())?;
+        }
+    }
+    Ok(())
+}
+
 fn run() -> Result<(), Box<dyn Error + Send>> {
     let http_client = new_client()?;
     let http = Rc::new(http_client);
@@ -71,6 +252,9 @@ fn run() -> Result<(), Box<dyn Error + Send>> {
     // Resolved once here (cheap) so the create / update / try-group arms can read
     // it by reference instead of recomputing it inside the move-destructured match.
     let cache_config = cli_cache_config(&cli);
+    let binary_policy = cli.binary_merge;
+    StateLocation::new(cli.state_file.clone(), cli.state_format).install();
+    cli.secrets.install();
     match cli.command {
         Commands::Build {
             tag,
@@ -389,54 +573,144 @@ fn run() -> Result<(), Box<dyn Error + Send>> {
             coordinator_endpoint,
             headless,
             answers,
+            output,
+        } => run_create(
+            CreateArgs {
+                template_ref,
+                path,
+                coordinator_endpoint,
+                headless,
+                answers,
+                output,
+            },
+            registry,
+            cli.debug,
+            cache_config,
+            binary_policy,
+            |_| Ok(()),
+        ),
+        // `add` is `create` into an existing project, after checking the project and
+        // template up front
+        Commands::Add {
+            template_ref,
+            path,
+            coordinator_endpoint,
+            headless,
+            answers,
+            output,
+        } => {
+            let target_dir = PathBuf::from(&path);
+            run_create(
+                CreateArgs {
+                    template_ref,
+                    path: Some(path),
+                    coordinator_endpoint,
+                    headless,
+                    answers,
+                    output,
+                },
+                registry,
+                cli.debug,
+                cache_config,
+                binary_policy,
+                |key| crate::run::ensure_addable(&target_dir, key),
+            )
+        }
+        Commands::Update {
+            path,
+            targets,
+            to,
+            coordinator_endpoint,
+            interactive,
+            force,
+            headless,
+            answers,
+            reanswer,
+            reask,
+            recursive,
+            jobs,
+            branch,
+            pr_description,
+            output,
         } => {
+            let registry_ref = Rc::new(registry);
+            if recursive {
+                let opts = RecursiveOptions {
+                    registry_endpoint: cli.registry.clone(),
+                    coordinator_endpoint,
+                    debug: cli.debug,
+                    force,
+                    cache_config,
+                    binary_policy,
+                    jobs,
+                    targets,
+                    versions: to,
+                };
+                let json = json_stdout(output, false)?;
+                let report = match cyan_update_recursive(Path::new(&path), registry_ref, &opts) {
+                    Ok(report) => report,
+                    Err(e) => {
+                        print_failure(json, e.as_ref())?;
+                        return Err(e);
+                    }
+                };
+
+                match json {
+                    None => write_recursive_report(&report, &mut std::io::stdout().lock())
+                        .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?,
+                    Some(json) => emit_json(json, &report)?,
+                }
+
+                if report.has_failures() {
+                    return Err(Box::new(crate::headless::HeadlessExit(1)));
+                }
+                return Ok(());
+            }
+
             let session_id_generator = Box::new(DefaultSessionIdGenerator);
+            let coord_client = CyanCoordinatorClient::new(coordinator_endpoint.clone());
 
-            // Headless: ingest the supplied answers up front via the shared helper;
-            // a bad source is an `error` envelope (exit 1), never a panic.
+            // Headless: ingest answers up front via the shared helper; a bad source
+            // → `error` envelope.
             let headless_answers = load_headless_answers(headless, answers.as_deref())?;
+            let reanswer = Reanswer::new(&reanswer, reask);
+            let json = json_stdout(output, headless)?;
 
-            let username = parse_ref(template_ref.clone())
-                .map(|(u, _, _)| u)
-                .unwrap_or_else(|_| "unknown".to_string());
-
-            let r = parse_ref(template_ref)
-                .and_then(|(u, n, v)| {
-                    crate::hprogress!(
-                        headless,
-                        "Retrieving template '{}/{}:{}' from registry...",
-                        u,
-                        n,
-                        v.unwrap_or(-1)
-                    );
-                    let r = registry.get_template(u.clone(), n.clone(), v);
-                    crate::hprogress!(
-                        headless,
-                        "Retrieved template '{}/{}:{}' from registry.",
-                        u,
-                        n,
-                        v.unwrap_or(-1)
-                    );
-                    r
-                })
-                .and_then(|tv| {
-                    let coord_client = CyanCoordinatorClient::new(coordinator_endpoint.clone());
-                    let registry_ref = Rc::new(registry);
-                    let cache_config = cache_config.clone();
-
-                    cyan_run(
-                        session_id_generator,
-                        path,
-                        tv,
-                        coord_client,
-                        username.clone(),
-                        Rc::clone(&registry_ref),
-                        cli.debug,
-                        cache_config,
-                        headless,
-                        headless_answers,
-                    )
-                });
+            if !headless {
+                println!("Updating templates to latest versions");
+            }
+
+            let target_dir = PathBuf::from(&path);
+            let update = || {
+                cyan_update(
+                    session_id_generator,
+                    path,
+                    coord_client.clone(),
+                    Rc::clone(&registry_ref),
+                    cli.debug,
+                    interactive,
+                    force,
+                    cache_config,
+                    binary_policy,
+                    headless,
+                    headless_answers,
+                    false,
+                    &targets,
+                    &to,
+                    &reanswer,
+                )
+            };
+            let r = match &branch {
+                Some(branch) => update_on_branch(
+                    &target_dir,
+                    branch,
+                    pr_description.as_deref(),
+                    headless,
+                    update,
+                ),
+                None => update(),
+            };
+            let r = select_report(r, output);
 
             if headless {
                 let clean = crate::headless::headless_session_cleaner(coordinator_endpoint.clone());
@@ -446,41 +720,34 @@ fn run() -> Result<(), Box<dyn Error + Send>> {
 
             match r {
                 Ok(result) => {
-                    println!("Completed successfully");
-                    let coord_client = CyanCoordinatorClient::new(coordinator_endpoint.clone());
+                    println!("Update completed successfully");
                     println!("Cleaning up all sessions...");
                     for sid in result.session_ids {
                         println!("Cleaning up session: {sid}");
                         let _ = coord_client.clean(sid);
                     }
                     println!("Cleaned up all sessions");
+                    print_report(json, result.report)?;
                 }
                 Err(e) => {
-                    eprintln!("Error: {e:#?}");
-                    println!("No sessions to clean up");
+                    if !e.is::<UserAborted>() {
+                        eprintln!("Error during update: {e:#?}");
+                    }
+                    print_failure(json, e.as_ref())?;
                 }
             }
             Ok(())
         }
-        Commands::Update {
+        Commands::Diff {
             path,
             coordinator_endpoint,
             interactive,
-            force,
-            headless,
-            answers,
         } => {
             let session_id_generator = Box::new(DefaultSessionIdGenerator);
             let coord_client = CyanCoordinatorClient::new(coordinator_endpoint.clone());
             let registry_ref = Rc::new(registry);
 
-            // Headless: ingest answers up front via the shared helper; a bad source
-            // → `error` envelope.
-            let headless_answers = load_headless_answers(headless, answers.as_deref())?;
-
-            if !headless {
-                println!("Updating templates to latest versions");
-            }
+            println!("Previewing template updates (nothing will be written)");
 
             let r = cyan_update(
                 session_id_generator,
@@ -489,36 +756,152 @@ fn run() -> Result<(), Box<dyn Error + Send>> {
                 Rc::clone(&registry_ref),
                 cli.debug,
                 interactive,
-                force,
+                false,
                 cache_config,
-                headless,
-                headless_answers,
+                binary_policy,
+                false,
+                std::collections::HashMap::new(),
+                true,
+                &[],
+                &[],
+                &Reanswer::Keep,
             );
 
-            if headless {
-                let clean = crate::headless::headless_session_cleaner(coordinator_endpoint.clone());
-                let stdout = std::io::stdout();
-                return crate::headless::finish_headless(r, &mut stdout.lock(), clean);
+            match r {
+                Ok(result) => {
+                    for sid in result.session_ids {
+                        let _ = coord_client.clean(sid);
+                    }
+                    Ok(())
+                }
+                Err(e) => {
+                    eprintln!("Error during diff: {e:#?}");
+                    Err(e)
+                }
             }
+        }
+        Commands::Status {
+            path,
+            coordinator_endpoint,
+            output,
+            exit_code,
+        } => {
+            let coord_client = CyanCoordinatorClient::new(coordinator_endpoint);
+            let report = status::cyan_status(
+                Box::new(DefaultSessionIdGenerator),
+                &path,
+                &coord_client,
+                Rc::new(registry),
+                cache_config,
+                binary_policy,
+            )?;
+
+            let stdout = std::io::stdout();
+            let mut stdout = stdout.lock();
+            match output {
+                OutputFormat::Text => status::write_status(&report, &mut stdout),
+                OutputFormat::Json => serde_json::to_writer(&mut stdout, &report)
+                    .map_err(std::io::Error::from)
+                    .and_then(|()| writeln!(stdout)),
+            }
+            .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
 
+            if exit_code && !report.clean {
+                return Err(Box::new(crate::headless::HeadlessExit(1)));
+            }
+            Ok(())
+        }
+        Commands::Resolve {
+            path,
+            continue_merge,
+            abort,
+        } => {
+            let target_dir = Path::new(&path);
+            let r = if continue_merge {
+                resolve::resolve_continue(target_dir)
+            } else if abort {
+                resolve::resolve_abort(target_dir)
+            } else {
+                resolve::resolve_status(target_dir)
+            };
+            if let Err(e) = &r {
+                eprintln!("Error during resolve: {e}");
+            }
+            r
+        }
+        Commands::Pin {
+            template,
+            constraint,
+            path,
+            clear,
+        } => {
+            let r = cyan_pin(&path, &template, constraint, clear);
+            if let Err(e) = &r {
+                eprintln!("Error during pin: {e}");
+            }
+            r
+        }
+        Commands::Remove {
+            template,
+            path,
+            coordinator_endpoint,
+            force,
+        } => {
+            let coord_client = CyanCoordinatorClient::new(coordinator_endpoint);
+            let r = cyan_remove(
+                Box::new(DefaultSessionIdGenerator),
+                path,
+                template,
+                coord_client.clone(),
+                Rc::new(registry),
+                cli.debug,
+                force,
+                cache_config,
+                binary_policy,
+            );
             match r {
                 Ok(result) => {
-                    println!("Update completed successfully");
-                    println!("Cleaning up all sessions...");
                     for sid in result.session_ids {
-                        println!("Cleaning up session: {sid}");
                         let _ = coord_client.clean(sid);
                     }
-                    println!("Cleaned up all sessions");
+                    Ok(())
                 }
                 Err(e) => {
-                    if e.is::<UserAborted>() {
-                        return Ok(());
+                    eprintln!("Error during remove: {e}");
+                    Err(e)
+                }
+            }
+        }
+        Commands::Undo {
+            template,
+            path,
+            coordinator_endpoint,
+            force,
+        } => {
+            let coord_client = CyanCoordinatorClient::new(coordinator_endpoint);
+            let r = cyan_undo(
+                Box::new(DefaultSessionIdGenerator),
+                path,
+                template,
+                coord_client.clone(),
+                Rc::new(registry),
+                cli.debug,
+                force,
+                cache_config,
+                binary_policy,
+            );
+            match r {
+                Ok(result) => {
+                    for sid in result.session_ids {
+                        let _ = coord_client.clean(sid);
                     }
-                    eprintln!("Error during update: {e:#?}");
+                    Ok(())
+                }
+                Err(e) => {
+                    eprintln!("Error during undo: {e}");
+                    Err(e)
                 }
             }
-            Ok(())
         }
         Commands::Daemon { command } => {
             let docker = Docker::connect_with_local_defaults()
@@ -578,6 +961,77 @@ fn run() -> Result<(), Box<dyn Error + Send>> {
                 }
             }
         }
+        Commands::State { command } => match command {
+            StateCommands::Migrate { to, path } => {
+                let dest = state_cmd::migrate_state(Path::new(&path), to)?;
+                println!("Migrated state to {}", dest.display());
+                Ok(())
+            }
+        },
+        Commands::Answers { command } => match command {
+            AnswersCommands::Export {
+                path,
+                file,
+                allow_unknown_secrets,
+            } => {
+                let export = AnswersExport::of_project(Path::new(&path), allow_unknown_secrets)?;
+                let json = serde_json::to_string_pretty(&export)
+                    .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
+                match &file {
+                    Some(file) => {
+                        std::fs::write(file, format!("{json}\n"))
+                            .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
+                        eprintln!(
+                            "Exported the answers of {} template(s) to {}",
+                            export.templates.len(),
+                            file.display()
+                        );
+                    }
+                    None => println!("{json}"),
+                }
+                let omitted: usize = export.templates.iter().map(|t| t.omitted.len()).sum();
+                if omitted > 0 {
+                    eprintln!(
+                        "ℹ️  Left out {omitted} secret answer(s); `cyanprint answers import` asks for them"
+                    );
+                }
+                Ok(())
+            }
+            AnswersCommands::Import {
+                file,
+                path,
+                coordinator_endpoint,
+                headless,
+                answers,
+            } => {
+                let extra_answers = load_headless_answers(headless, answers.as_deref())?;
+                let opts = ImportOptions {
+                    coordinator_endpoint: coordinator_endpoint.clone(),
+                    debug: cli.debug,
+                    cache_config,
+                    binary_policy,
+                    headless,
+                    answers: extra_answers,
+                };
+                let r = AnswersExport::read(&file).and_then(|export| {
+                    cyan_import(&export, Path::new(&path), Rc::new(registry), &opts)
+                });
+
+                if headless {
+                    let clean = crate::headless::headless_session_cleaner(coordinator_endpoint);
+                    let stdout = std::io::stdout();
+                    return crate::headless::finish_headless(r, &mut stdout.lock(), clean);
+                }
+
+                let result = r?;
+                let coord_client = CyanCoordinatorClient::new(coordinator_endpoint);
+                for sid in result.session_ids {
+                    let _ = coord_client.clean(sid);
+                }
+                println!("Imported answers into {path}");
+                Ok(())
+            }
+        },
         Commands::Try { command } => match command {
             TryCommands::Template {
                 template_path,
@@ -0,0 +1,248 @@
+//! Structured result of a `create` / `update` run, printed with `--output json`.
+//!
+//! The report describes what the run did to the project — never the answers given — so
+//! it is safe to attach to a pull request.
+
+use cyancoordinator::conflict_file_resolver::FileConflictEntry;
+use cyancoordinator::fs::{FileChangeKind, VfsDiff};
+use cyancoordinator::state::CyanState;
+use serde::Serialize;
+use std::io::Write;
+
+use crate::command_executor::CommandRun;
+use crate::run::normalize_path;
+use crate::update::spec::TemplateSpec;
+
+/// What a completed run changed.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RunReport {
+    /// Templates the run installed, upgraded or re-ran.
+    pub templates: Vec<TemplateChange>,
+    pub files: FileChanges,
+    /// How overlapping template outputs were resolved.
+    pub file_conflicts: Vec<FileConflictEntry>,
+    /// Paths written with conflict markers, left for `cyanprint resolve`.
+    pub unresolved: Vec<String>,
+    /// `None` when the output cache is disabled.
+    pub cache: Option<CacheReport>,
+    /// Post-template commands that ran; empty while a merge is unresolved.
+    pub commands: Vec<CommandRun>,
+}
+
+impl RunReport {
+    /// Report a written batch: `upgraded` are the specs whose metadata it saved, `state`
+    /// the project state saved after it, and `cache` the operator's hit/total summary.
+    /// Commands are added once they ran.
+    pub fn for_run(
+        prev: &[TemplateSpec],
+        upgraded: &[&TemplateSpec],
+        diff: &VfsDiff,
+        state: &CyanState,
+        cache: Option<(usize, usize)>,
+    ) -> Self {
+        Self {
+            templates: TemplateChange::between(prev, upgraded),
+            files: FileChanges::from(diff),
+            file_conflicts: state.file_conflicts.clone(),
+            unresolved: state
+                .pending_merge
+                .as_ref()
+                .map(|m| m.unresolved.clone())
+                .unwrap_or_default(),
+            cache: CacheReport::from_summary(cache),
+            commands: Vec::new(),
+        }
+    }
+}
+
+/// A template's move between versions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TemplateChange {
+    pub template: String,
+    /// `None` for a template new to the project.
+    pub from: Option<i64>,
+    pub to: i64,
+}
+
+impl TemplateChange {
+    /// The change for each of `changed`, relative to its version in `prev`.
+    pub fn between(prev: &[TemplateSpec], changed: &[&TemplateSpec]) -> Vec<Self> {
+        changed
+            .iter()
+            .map(|spec| Self {
+                template: spec.key(),
+                from: prev
+                    .iter()
+                    .find(|p| p.key() == spec.key())
+                    .map(|p| p.version),
+                to: spec.version,
+            })
+            .collect()
+    }
+}
+
+/// Files the write created, overwrote and removed, by normalized path.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FileChanges {
+    pub added: Vec<String>,
+    pub modified: Vec<String>,
+    pub deleted: Vec<String>,
+}
+
+impl From<&VfsDiff> for FileChanges {
+    fn from(diff: &VfsDiff) -> Self {
+        let paths = |kind| {
+            diff.paths_of(kind)
+                .iter()
+                .map(|p| normalize_path(p))
+                .collect()
+        };
+        Self {
+            added: paths(FileChangeKind::Added),
+            modified: paths(FileChangeKind::Modified),
+            deleted: paths(FileChangeKind::Deleted),
+        }
+    }
+}
+
+/// Template executions served from the output cache, out of those looked up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CacheReport {
+    pub hits: usize,
+    pub total: usize,
+}
+
+impl CacheReport {
+    pub fn from_summary(summary: Option<(usize, usize)>) -> Option<Self> {
+        summary.map(|(hits, total)| Self { hits, total })
+    }
+}
+
+/// Holds stdout for the one JSON document `--output json` prints outside headless mode.
+///
+/// While held, everything else written to stdout — progress, merger debug output, the
+/// post-template commands' own output — goes to stderr instead, so stdout carries only
+/// what [`JsonStdout::emit`] writes. Elsewhere than on Unix stdout is left as it is.
+pub struct JsonStdout {
+    /// The real stdout, set aside while fd 1 points at stderr.
+    #[cfg(unix)]
+    saved: Option<std::fs::File>,
+}
+
+impl JsonStdout {
+    #[cfg(unix)]
+    pub fn capture() -> std::io::Result<Self> {
+        use std::os::fd::FromRawFd;
+
+        std::io::stdout().flush()?;
+        // SAFETY: plain descriptor calls; `saved` is a fresh descriptor owned by the File
+        let saved = unsafe { libc::dup(libc::STDOUT_FILENO) };
+        if saved < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let saved = unsafe { std::fs::File::from_raw_fd(saved) };
+        if unsafe { libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) } < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(Self { saved: Some(saved) })
+    }
+
+    #[cfg(not(unix))]
+    pub fn capture() -> std::io::Result<Self> {
+        Ok(Self {})
+    }
+
+    /// Give stdout back and write `document` to it as one line.
+    pub fn emit(mut self, document: &serde_json::Value) -> std::io::Result<()> {
+        self.release();
+        let mut stdout = std::io::stdout().lock();
+        writeln!(stdout, "{document}")?;
+        stdout.flush()
+    }
+
+    #[cfg(unix)]
+    fn release(&mut self) {
+        use std::os::fd::AsRawFd;
+
+        if let Some(saved) = self.saved.take() {
+            let _ = std::io::stdout().flush();
+            // SAFETY: `saved` is open until it drops at the end of this block
+            unsafe { libc::dup2(saved.as_raw_fd(), libc::STDOUT_FILENO) };
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn release(&mut self) {}
+}
+
+impl Drop for JsonStdout {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cyancoordinator::fs::VirtualFileSystem;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    fn spec(name: &str, version: i64) -> TemplateSpec {
+        TemplateSpec::new(
+            "alice".to_string(),
+            name.to_string(),
+            version,
+            HashMap::new(),
+            HashMap::new(),
+            chrono::Utc::now(),
+        )
+    }
+
+    #[test]
+    fn template_changes_record_the_previous_version() {
+        let prev = vec![spec("web", 3)];
+        let web = spec("web", 5);
+        let api = spec("api", 1);
+        assert_eq!(
+            TemplateChange::between(&prev, &[&web, &api]),
+            vec![
+                TemplateChange {
+                    template: "alice/web".to_string(),
+                    from: Some(3),
+                    to: 5,
+                },
+                TemplateChange {
+                    template: "alice/api".to_string(),
+                    from: None,
+                    to: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn file_changes_split_the_diff() {
+        let mut local = VirtualFileSystem::new();
+        local.add_file(PathBuf::from("same.txt"), b"a".to_vec());
+        local.add_file(PathBuf::from("edit.txt"), b"a".to_vec());
+        local.add_file(PathBuf::from("gone.txt"), b"a".to_vec());
+        let mut merged = VirtualFileSystem::new();
+        merged.add_file(PathBuf::from("same.txt"), b"a".to_vec());
+        merged.add_file(PathBuf::from("edit.txt"), b"b".to_vec());
+        merged.add_file(PathBuf::from("src/new.txt"), b"a".to_vec());
+
+        let changes = FileChanges::from(&VfsDiff::compute(&local, &merged));
+        assert_eq!(changes.added, vec!["src/new.txt"]);
+        assert_eq!(changes.modified, vec!["edit.txt"]);
+        assert_eq!(changes.deleted, vec!["gone.txt"]);
+
+        let json = serde_json::to_value(RunReport {
+            files: changes,
+            ..RunReport::default()
+        })
+        .unwrap();
+        assert_eq!(json["files"]["added"][0], "src/new.txt");
+        assert!(json["cache"].is_null());
+    }
+}
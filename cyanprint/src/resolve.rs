@@ -0,0 +1,527 @@
+//! `cyanprint resolve`: finishing or rolling back an update whose 3-way merge left
+//! conflict markers in the working tree.
+//!
+//! When the merge conflicts, [`batch_process`](crate::run::batch_process) backs up every
+//! file the write is about to touch, plus the state file, under [`MERGE_DIR`] before
+//! writing. The merged files (markers included) and the new state are then written as
+//! usual, with a [`PendingMerge`] record in the state file listing the unresolved
+//! paths. `--continue` checks the markers are gone, clears the record and runs the
+//! post-template commands that were held back; `--abort` puts the backups back.
+
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use cyancoordinator::fs::{FileChangeKind, VfsDiff, VirtualFileSystem, copy_entry, remove_any};
+use cyancoordinator::state::{
+    CyanState, DefaultStateManager, PendingMerge, StateReader, StateWriter, state_file,
+};
+
+use crate::command_executor::CommandExecutor;
+use crate::run::normalize_path;
+
+/// Directory (relative to the project root) holding a conflicted update's backups.
+pub const MERGE_DIR: &str = ".cyan_merge";
+
+const BACKUP_FILES: &str = "files";
+const BACKUP_STATE: &str = "state.yaml";
+
+fn other(msg: String) -> Box<dyn Error + Send> {
+    Box::new(io::Error::other(msg))
+}
+
+/// True when `content` still contains a conflict hunk: a line starting with
+/// `<<<<<<<` followed later by one starting with `>>>>>>>`.
+pub fn has_conflict_markers(content: &[u8]) -> bool {
+    let text = String::from_utf8_lossy(content);
+    let mut open = false;
+    for line in text.lines() {
+        if line.starts_with("<<<<<<<") {
+            open = true;
+        } else if open && line.starts_with(">>>>>>>") {
+            return true;
+        }
+    }
+    false
+}
+
+/// Back up the files a write of `merged` over `local` would modify or delete, and the
+/// current state file, into [`MERGE_DIR`]. Files are copied from disk as they are, so
+/// modes and symlinks survive an abort. Returns the normalized paths the write will
+/// create, which an abort must remove again.
+pub fn back_up_pre_merge(
+    target_dir: &Path,
+    local: &VirtualFileSystem,
+    merged: &VirtualFileSystem,
+) -> io::Result<Vec<String>> {
+    let merge_dir = target_dir.join(MERGE_DIR);
+    if merge_dir.exists() {
+        fs::remove_dir_all(&merge_dir)?;
+    }
+    let files_dir = merge_dir.join(BACKUP_FILES);
+    fs::create_dir_all(&files_dir)?;
+
+    let diff = VfsDiff::compute(local, merged);
+    let mut added = Vec::new();
+    for change in &diff.changes {
+        if change.kind == FileChangeKind::Added {
+            added.push(normalize_path(&change.path));
+            continue;
+        }
+        if local.get_file(&change.path).is_some() {
+            let backup = files_dir.join(&change.path);
+            if let Some(parent) = backup.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            copy_entry(&target_dir.join(&change.path), &backup)?;
+        }
+    }
+
+    let state_file = state_file(target_dir);
+    if state_file.exists() {
+        fs::copy(&state_file, merge_dir.join(BACKUP_STATE))?;
+    }
+    Ok(added)
+}
+
+/// Refuse to start another create/update on top of an unresolved merge.
+pub fn ensure_no_pending_merge(state: &CyanState) -> Result<(), Box<dyn Error + Send>> {
+    match &state.pending_merge {
+        Some(pending) => Err(other(format!(
+            "A previous update left {} conflicted file(s); run `cyanprint resolve --continue` \
+             once they are resolved, or `cyanprint resolve --abort` to roll it back",
+            pending.unresolved.len()
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Tell the user the update stopped on conflicts and how to finish it.
+pub fn report_pending_merge(pending: &PendingMerge, headless: bool) {
+    crate::hprogress!(
+        headless,
+        "\n⚠️ Merge left conflicts in {} file(s):",
+        pending.unresolved.len()
+    );
+    for path in &pending.unresolved {
+        crate::hprogress!(headless, "  {path}");
+    }
+    if !pending.commands.is_empty() {
+        crate::hprogress!(
+            headless,
+            "⏸️ Holding back {} post-template command(s) until the conflicts are resolved",
+            pending.commands.len()
+        );
+    }
+    crate::hprogress!(
+        headless,
+        "Fix the conflict markers, then run `cyanprint resolve --continue` \
+         (or `cyanprint resolve --abort` to roll the update back)"
+    );
+}
+
+/// Where an unresolved path stands on disk.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConflictStatus {
+    /// Still carries conflict markers, or could not be read to show otherwise.
+    Conflicted,
+    Resolved,
+    /// The file was deleted while resolving.
+    Deleted,
+}
+
+pub fn conflict_status(target_dir: &Path, path: &str) -> ConflictStatus {
+    match fs::read(target_dir.join(path)) {
+        Ok(content) if has_conflict_markers(&content) => ConflictStatus::Conflicted,
+        Ok(_) => ConflictStatus::Resolved,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => ConflictStatus::Deleted,
+        Err(_) => ConflictStatus::Conflicted,
+    }
+}
+
+fn paths_with_status(
+    target_dir: &Path,
+    pending: &PendingMerge,
+    status: ConflictStatus,
+) -> Vec<String> {
+    pending
+        .unresolved
+        .iter()
+        .filter(|path| conflict_status(target_dir, path) == status)
+        .cloned()
+        .collect()
+}
+
+/// Unresolved paths of `pending` whose file on disk still carries conflict markers or
+/// cannot be read.
+pub fn remaining_conflicts(target_dir: &Path, pending: &PendingMerge) -> Vec<String> {
+    paths_with_status(target_dir, pending, ConflictStatus::Conflicted)
+}
+
+/// Unresolved paths of `pending` that were deleted rather than edited.
+pub fn deleted_conflicts(target_dir: &Path, pending: &PendingMerge) -> Vec<String> {
+    paths_with_status(target_dir, pending, ConflictStatus::Deleted)
+}
+
+fn load_pending(target_dir: &Path) -> Result<(CyanState, PendingMerge), Box<dyn Error + Send>> {
+    crate::run::recover_interrupted_write(target_dir, false)?;
+    let state_file = state_file(target_dir);
+    let state = DefaultStateManager::new().load_state_file(&state_file)?;
+    let pending = state
+        .pending_merge
+        .clone()
+        .ok_or_else(|| other("No update is waiting for conflict resolution".to_string()))?;
+    Ok((state, pending))
+}
+
+/// `resolve` without a flag: list the unresolved paths and whether each still has markers.
+pub fn resolve_status(target_dir: &Path) -> Result<(), Box<dyn Error + Send>> {
+    let (_, pending) = load_pending(target_dir)?;
+    let remaining = remaining_conflicts(target_dir, &pending);
+    for path in &pending.unresolved {
+        let mark = match conflict_status(target_dir, path) {
+            ConflictStatus::Conflicted => "conflicted",
+            ConflictStatus::Resolved => "resolved",
+            ConflictStatus::Deleted => "deleted",
+        };
+        println!("  {mark:<10} {path}");
+    }
+    println!(
+        "{} of {} file(s) still conflicted",
+        remaining.len(),
+        pending.unresolved.len()
+    );
+    Ok(())
+}
+
+/// `resolve --continue`: finalise the update once no unresolved path has markers left.
+pub fn resolve_continue(target_dir: &Path) -> Result<(), Box<dyn Error + Send>> {
+    let (mut state, pending) = load_pending(target_dir)?;
+    let remaining = remaining_conflicts(target_dir, &pending);
+    if !remaining.is_empty() {
+        return Err(other(format!(
+            "Conflict markers remain in: {}",
+            remaining.join(", ")
+        )));
+    }
+    let deleted = deleted_conflicts(target_dir, &pending);
+    if !deleted.is_empty() {
+        println!("🗑️  Resolved by deletion: {}", deleted.join(", "));
+    }
+
+    state.pending_merge = None;
+    DefaultStateManager::new().save_state_file(&state, &state_file(target_dir))?;
+    remove_merge_dir(target_dir)?;
+    println!(
+        "✅ Resolved {} file(s); update complete",
+        pending.unresolved.len()
+    );
+
+    if !pending.commands.is_empty() {
+        println!(
+            "\n⚡ Executing {} post-template command(s)...",
+            pending.commands.len()
+        );
+        let exec_result =
+            CommandExecutor::execute_commands_for_mode(&pending.commands, target_dir, false)?;
+        if exec_result.aborted {
+            return Err(other(format!(
+                "Command execution aborted: {}/{} succeeded, {}/{} failed before abort",
+                exec_result.succeeded, exec_result.total, exec_result.failed, exec_result.total
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// `resolve --abort`: restore the files and state as they were before the update.
+pub fn resolve_abort(target_dir: &Path) -> Result<(), Box<dyn Error + Send>> {
+    let (_, pending) = load_pending(target_dir)?;
+    let merge_dir = target_dir.join(MERGE_DIR);
+    let io_err = |e: io::Error| Box::new(e) as Box<dyn Error + Send>;
+
+    // Whatever an added path has become (a dangling symlink, a directory) goes
+    for path in &pending.added {
+        remove_any(&target_dir.join(path)).map_err(io_err)?;
+    }
+
+    let files_dir = merge_dir.join(BACKUP_FILES);
+    if files_dir.is_dir() {
+        restore_tree(&files_dir, &files_dir, target_dir).map_err(io_err)?;
+    }
+
+    let state_backup = merge_dir.join(BACKUP_STATE);
+    let state_file = state_file(target_dir);
+    if state_backup.exists() {
+        fs::copy(&state_backup, &state_file).map_err(io_err)?;
+    } else {
+        fs::remove_file(&state_file).map_err(io_err)?;
+    }
+
+    remove_merge_dir(target_dir)?;
+    println!("↩️ Update aborted; working tree and state restored");
+    Ok(())
+}
+
+/// Copy every file and symlink under `dir` back to the same relative path under
+/// `target_dir`.
+fn restore_tree(root: &Path, dir: &Path, target_dir: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            restore_tree(root, &path, target_dir)?;
+            continue;
+        }
+        let rel = path.strip_prefix(root).map_err(io::Error::other)?;
+        let dest = target_dir.join(rel);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        copy_entry(&path, &dest)?;
+    }
+    Ok(())
+}
+
+fn remove_merge_dir(target_dir: &Path) -> Result<(), Box<dyn Error + Send>> {
+    let merge_dir = target_dir.join(MERGE_DIR);
+    if merge_dir.exists() {
+        fs::remove_dir_all(merge_dir).map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    const STATE_FILE: &str = ".cyan_state.yaml";
+
+    fn vfs(files: &[(&str, &str)]) -> VirtualFileSystem {
+        let mut vfs = VirtualFileSystem::new();
+        for (path, content) in files {
+            vfs.add_file(PathBuf::from(path), content.as_bytes().to_vec());
+        }
+        vfs
+    }
+
+    fn write(dir: &Path, path: &str, content: &str) {
+        let full = dir.join(path);
+        fs::create_dir_all(full.parent().unwrap()).unwrap();
+        fs::write(full, content).unwrap();
+    }
+
+    fn read(dir: &Path, path: &str) -> String {
+        fs::read_to_string(dir.join(path)).unwrap()
+    }
+
+    /// Simulates a conflicted update: backs up the local tree, then writes the merged
+    /// tree and a state carrying the pending record, as `batch_process` + caller do.
+    fn conflicted_update(dir: &Path) {
+        write(dir, "a.txt", "local\n");
+        write(dir, "gone.txt", "bye\n");
+        write(
+            dir,
+            STATE_FILE,
+            "alice/web:\n  active: true\n  history: []\n",
+        );
+        let local = vfs(&[("a.txt", "local\n"), ("gone.txt", "bye\n")]);
+        let merged = vfs(&[
+            (
+                "a.txt",
+                "<<<<<<< local\nlocal\n=======\nnew\n>>>>>>> alice/web:2\n",
+            ),
+            ("new.txt", "hi\n"),
+        ]);
+
+        let added = back_up_pre_merge(dir, &local, &merged).unwrap();
+        assert_eq!(added, vec!["new.txt".to_string()]);
+
+        fs::remove_file(dir.join("gone.txt")).unwrap();
+        for path in merged.get_paths() {
+            fs::write(dir.join(&path), merged.get_file(&path).unwrap()).unwrap();
+        }
+        let state = CyanState {
+            pending_merge: Some(PendingMerge {
+                unresolved: vec!["a.txt".to_string()],
+                added,
+                commands: Vec::new(),
+            }),
+            ..Default::default()
+        };
+        DefaultStateManager::new()
+            .save_state_file(&state, &dir.join(STATE_FILE))
+            .unwrap();
+    }
+
+    #[test]
+    fn detects_conflict_markers() {
+        assert!(has_conflict_markers(
+            b"a\n<<<<<<< local\nx\n=======\ny\n>>>>>>> alice/web:2\nb\n"
+        ));
+        assert!(!has_conflict_markers(b"plain\n=======\ntext\n"));
+        assert!(!has_conflict_markers(b">>>>>>> before\n<<<<<<< after\n"));
+    }
+
+    #[test]
+    fn continue_refuses_while_markers_remain() {
+        let dir = TempDir::new().unwrap();
+        conflicted_update(dir.path());
+
+        let err = resolve_continue(dir.path()).unwrap_err().to_string();
+        assert!(err.contains("a.txt"), "got: {err}");
+        assert!(dir.path().join(MERGE_DIR).exists());
+    }
+
+    #[test]
+    fn continue_finalises_once_resolved() {
+        let dir = TempDir::new().unwrap();
+        conflicted_update(dir.path());
+        write(dir.path(), "a.txt", "merged by hand\n");
+
+        resolve_continue(dir.path()).unwrap();
+
+        let state = DefaultStateManager::new()
+            .load_state_file(&dir.path().join(STATE_FILE))
+            .unwrap();
+        assert!(state.pending_merge.is_none());
+        assert!(!dir.path().join(MERGE_DIR).exists());
+        assert_eq!(read(dir.path(), "a.txt"), "merged by hand\n");
+    }
+
+    #[test]
+    fn unreadable_file_stays_conflicted_and_deleted_file_is_reported() {
+        let dir = TempDir::new().unwrap();
+        conflicted_update(dir.path());
+        let (_, pending) = load_pending(dir.path()).unwrap();
+
+        // A directory where the file was cannot be read, so nothing shows it resolved.
+        fs::remove_file(dir.path().join("a.txt")).unwrap();
+        fs::create_dir(dir.path().join("a.txt")).unwrap();
+        assert_eq!(
+            conflict_status(dir.path(), "a.txt"),
+            ConflictStatus::Conflicted
+        );
+        assert!(resolve_continue(dir.path()).is_err());
+
+        fs::remove_dir(dir.path().join("a.txt")).unwrap();
+        assert_eq!(
+            deleted_conflicts(dir.path(), &pending),
+            vec!["a.txt".to_string()]
+        );
+        assert!(remaining_conflicts(dir.path(), &pending).is_empty());
+    }
+
+    #[test]
+    fn abort_restores_files_and_state() {
+        let dir = TempDir::new().unwrap();
+        conflicted_update(dir.path());
+
+        resolve_abort(dir.path()).unwrap();
+
+        assert_eq!(read(dir.path(), "a.txt"), "local\n");
+        assert_eq!(read(dir.path(), "gone.txt"), "bye\n");
+        assert!(!dir.path().join("new.txt").exists());
+        assert!(!dir.path().join(MERGE_DIR).exists());
+        let state = DefaultStateManager::new()
+            .load_state_file(&dir.path().join(STATE_FILE))
+            .unwrap();
+        assert!(state.pending_merge.is_none());
+        assert!(state.templates.contains_key("alice/web"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn abort_restores_modes_and_symlinks() {
+        use std::os::unix::fs::{PermissionsExt, symlink};
+
+        let dir = TempDir::new().unwrap();
+        let dir = dir.path();
+        write(dir, "run.sh", "#!/bin/sh\n");
+        fs::set_permissions(dir.join("run.sh"), fs::Permissions::from_mode(0o755)).unwrap();
+        write(dir, "target.txt", "t\n");
+        symlink("target.txt", dir.join("link")).unwrap();
+        write(
+            dir,
+            STATE_FILE,
+            "alice/web:\n  active: true\n  history: []\n",
+        );
+        let local = vfs(&[("run.sh", "#!/bin/sh\n"), ("link", "t\n")]);
+        let merged = vfs(&[
+            (
+                "run.sh",
+                "<<<<<<< local\n=======\nexit 1\n>>>>>>> alice/web:2\n",
+            ),
+            ("link", "replaced\n"),
+            ("added_link", "x"),
+            ("added_dir/f", "x"),
+        ]);
+
+        let added = back_up_pre_merge(dir, &local, &merged).unwrap();
+        // The write replaces both entries with plain files; the added paths end up as a
+        // dangling symlink and a directory
+        fs::remove_file(dir.join("run.sh")).unwrap();
+        write(
+            dir,
+            "run.sh",
+            "<<<<<<< local\n=======\nexit 1\n>>>>>>> alice/web:2\n",
+        );
+        fs::remove_file(dir.join("link")).unwrap();
+        write(dir, "link", "replaced\n");
+        symlink("nowhere", dir.join("added_link")).unwrap();
+        write(dir, "added_dir/f", "x");
+        let state = CyanState {
+            pending_merge: Some(PendingMerge {
+                unresolved: vec!["run.sh".to_string()],
+                added: vec!["added_link".to_string(), "added_dir".to_string()],
+                commands: Vec::new(),
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            added,
+            vec!["added_dir/f".to_string(), "added_link".to_string()]
+        );
+        DefaultStateManager::new()
+            .save_state_file(&state, &dir.join(STATE_FILE))
+            .unwrap();
+
+        resolve_abort(dir).unwrap();
+
+        let mode = fs::metadata(dir.join("run.sh"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o755);
+        assert_eq!(read(dir, "run.sh"), "#!/bin/sh\n");
+        assert_eq!(
+            fs::read_link(dir.join("link")).unwrap(),
+            PathBuf::from("target.txt")
+        );
+        assert!(fs::symlink_metadata(dir.join("added_link")).is_err());
+        assert!(!dir.join("added_dir").exists());
+    }
+
+    #[test]
+    fn resolve_without_pending_merge_errors() {
+        let dir = TempDir::new().unwrap();
+        write(
+            dir.path(),
+            STATE_FILE,
+            "alice/web:\n  active: true\n  history: []\n",
+        );
+        assert!(resolve_continue(dir.path()).is_err());
+        assert!(resolve_abort(dir.path()).is_err());
+    }
+
+    #[test]
+    fn pending_merge_blocks_new_runs() {
+        let mut state = CyanState::default();
+        assert!(ensure_no_pending_merge(&state).is_ok());
+        state.pending_merge = Some(PendingMerge::default());
+        assert!(ensure_no_pending_merge(&state).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::fs;
 use std::iter;
@@ -7,10 +7,16 @@ use std::rc::Rc;
 
 use cyancoordinator::client::CyanCoordinatorClient;
 use cyancoordinator::conflict_file_resolver::FileConflictEntry;
-use cyancoordinator::fs::{DiskFileLoader, DiskFileWriter, GitLikeMerger, TarGzUnpacker};
+use cyancoordinator::fs::{
+    BinaryMergePolicy, DiskFileLoader, DiskFileWriter, GitLikeMerger, MergeLabels, TarGzUnpacker,
+    VfsDiff, WriteTransaction, deleted_paths,
+};
 use cyancoordinator::operations::TemplateOperator;
 use cyancoordinator::operations::composition::{CompositionOperator, DefaultDependencyResolver};
-use cyancoordinator::state::{DefaultStateManager, StateReader, StateWriter};
+use cyancoordinator::state::{
+    CyanState, DefaultStateManager, ManagedFile, PendingMerge, StateLocation, StateReader,
+    StateWriter, state_file,
+};
 use cyancoordinator::template::TemplateHistory;
 use cyanprompt::domain::models::answer::Answer;
 use cyanprompt::domain::models::cyan::Cyan;
@@ -28,19 +34,28 @@ use cyancoordinator::fs::VirtualFileSystem;
 
 use crate::command_executor::CommandExecutor;
 use crate::headless::CyanRunResult;
-use crate::update::spec::{TemplateSpec, TemplateSpecManager, sort_specs};
+use crate::report::RunReport;
+use crate::update::spec::{TemplateSpec, TemplateSpecManager, fill_missing_secrets, sort_specs};
 
 /// cyanprint's own bookkeeping artifacts, excluded from the managed-files manifest.
 /// `.cyan_state.yaml` is the state file the loader already special-cases
 /// (`fs/loader.rs:25,79`); `.cyan_output` is the default output artifact
-/// (`commands.rs`). Matched by exact path (after normalization) or top-level entry.
-const CYANPRINT_INTERNAL_FILES: &[&str] = &[".cyan_state.yaml", ".cyan_output"];
+/// (`commands.rs`); `.cyan_merge` holds a conflicted update's pre-merge backups
+/// (`resolve.rs`); `.cyan_txn` holds a running write transaction. Matched by exact
+/// path (after normalization) or top-level entry.
+/// A state file moved with `--state-file`/`--state-format` is matched via [`StateLocation`].
+const CYANPRINT_INTERNAL_FILES: &[&str] = &[
+    ".cyan_state.yaml",
+    ".cyan_output",
+    ".cyan_merge",
+    cyancoordinator::fs::TXN_DIR,
+];
 
 /// Normalize a VFS path to the manifest's canonical form: forward-slash separators,
 /// no leading `./` or `/`, no trailing `/`. VFS paths are already stored relative
 /// (the loader/unpacker strip the target-dir prefix), so this is normalization, not
 /// relativization.
-fn normalize_path(path: &Path) -> String {
+pub(crate) fn normalize_path(path: &Path) -> String {
     // Render with '/' regardless of OS separator, using lossy UTF-8 for each component.
     let joined = path
         .components()
@@ -67,23 +82,63 @@ fn is_cyanprint_internal(path: &str) -> bool {
     CYANPRINT_INTERNAL_FILES
         .iter()
         .any(|internal| path == *internal || top_level == *internal)
+        || StateLocation::current().is_state_file(Path::new(path))
 }
 
-/// Collect a template's output paths from its VFS as a normalized, filtered, sorted,
-/// de-duplicated list of relative paths suitable for the managed-files manifest.
-fn normalize_managed_paths(vfs: &VirtualFileSystem) -> Vec<String> {
-    let mut v: Vec<String> = vfs
+/// Collect a template's output files from its VFS as a normalized, filtered, sorted,
+/// de-duplicated list of relative paths, each with the hash of its generated content,
+/// suitable for the managed-files manifest.
+fn managed_files(vfs: &VirtualFileSystem) -> Vec<ManagedFile> {
+    let mut v: Vec<ManagedFile> = vfs
         .get_paths()
         .iter()
-        .map(|p| normalize_path(p))
-        .filter(|p| !p.is_empty())
-        .filter(|p| !is_cyanprint_internal(p))
+        .map(|p| (normalize_path(p), p))
+        .filter(|(n, _)| !n.is_empty())
+        .filter(|(n, _)| !is_cyanprint_internal(n))
+        .map(|(n, p)| ManagedFile::new(n, vfs.get_file(p).map_or(&[][..], Vec::as_slice)))
         .collect();
-    v.sort();
-    v.dedup();
+    v.sort_by(|a, b| a.path.cmp(&b.path));
+    v.dedup_by(|a, b| a.path == b.path);
     v
 }
 
+/// 3-way merge `local` with `incoming` over `base`, except for the local files still
+/// exactly as the templates last generated them (per the hashes in `state`): those
+/// cannot conflict, so they take the incoming side whole without being merged.
+fn merge_edited(
+    operator: &CompositionOperator,
+    state: &CyanState,
+    base: VirtualFileSystem,
+    local: &VirtualFileSystem,
+    incoming: &VirtualFileSystem,
+    labels: &MergeLabels,
+) -> Result<VirtualFileSystem, Box<dyn Error + Send>> {
+    let untouched: Vec<PathBuf> = local
+        .get_paths()
+        .into_iter()
+        .filter(|p| {
+            local
+                .get_file(p)
+                .is_some_and(|c| state.is_untouched(&normalize_path(p), c))
+        })
+        .collect();
+    if untouched.is_empty() {
+        return operator.merge(&base, local, incoming, labels);
+    }
+
+    let (mut base, mut edited, mut rest) = (base, local.clone(), incoming.clone());
+    for path in &untouched {
+        base.remove_file(path);
+        edited.remove_file(path);
+        rest.remove_file(path);
+    }
+    let mut merged = operator.merge(&base, &edited, &rest, labels)?;
+    for path in &untouched {
+        merged.copy_entry(incoming, path);
+    }
+    Ok(merged)
+}
+
 /// Check if a template has execution artifacts (Docker properties)
 fn has_execution_artifacts(template: &TemplateVersionRes) -> bool {
     template.principal.properties.is_some()
@@ -94,31 +149,168 @@ fn has_dependencies(template: &TemplateVersionRes) -> bool {
     !template.templates.is_empty()
 }
 
-/// Unified batch processing for both create and update commands.
-/// Handles MAP, LAYER, and MERGE+WRITE phases.
-/// Returns session IDs for cleanup, file conflicts for state persistence, and commands for execution.
-// `headless` controls only whether progress goes to stderr; the per-phase
-// inputs are intrinsic to batch processing, so this stays parameter-heavy.
-#[allow(clippy::type_complexity, clippy::too_many_arguments)]
-pub fn batch_process(
+/// Output of the MAP → LAYER → MERGE phases of a batch, computed before anything is
+/// written to disk. [`batch_process`] applies it; `cyanprint diff` only renders it.
+pub struct BatchPlan {
+    /// Coordinator sessions acquired while executing the specs. The caller owns their
+    /// release from here on.
+    pub session_ids: Vec<String>,
+    /// The files currently on disk, as loaded for the 3-way merge.
+    pub local_vfs: VirtualFileSystem,
+    /// The layered output of the current specs: the MERGE's incoming side, i.e. what
+    /// the templates produce before the user's edits are merged in.
+    pub incoming_vfs: VirtualFileSystem,
+    /// The 3-way merge result that WRITE would put on disk.
+    pub merged_vfs: VirtualFileSystem,
+    pub file_conflicts: Vec<FileConflictEntry>,
+    pub commands: Vec<String>,
+    pub managed_by_template: HashMap<String, Vec<ManagedFile>>,
+    /// Final answers (including Q&A) per template key, for metadata persistence.
+    pub final_answers: HashMap<String, HashMap<String, Answer>>,
+    /// Per template key, the ids in its final answers given to `Password` questions.
+    pub final_secrets: HashMap<String, HashSet<String>>,
+    /// Headless: the question a template stopped on. When set, every other field except
+    /// `session_ids` is empty and nothing may be written.
+    pub need_input: Option<Question>,
+}
+
+/// Output of the MAP → LAYER phases of a batch: what the templates produce, before any
+/// local file is looked at.
+pub struct LayerPlan {
+    /// Coordinator sessions acquired while executing the specs. The caller owns their
+    /// release from here on.
+    pub session_ids: Vec<String>,
+    /// The layered output of the prev specs: the MERGE's base side.
+    pub prev_vfs: VirtualFileSystem,
+    /// The layered output of the curr specs: the MERGE's incoming side.
+    pub incoming_vfs: VirtualFileSystem,
+    pub commands: Vec<String>,
+    pub managed_by_template: HashMap<String, Vec<ManagedFile>>,
+    pub final_answers: HashMap<String, HashMap<String, Answer>>,
+    pub final_secrets: HashMap<String, HashSet<String>>,
+    /// Headless: the question a template stopped on. When set, every other field except
+    /// `session_ids` is empty.
+    pub need_input: Option<Question>,
+}
+
+impl LayerPlan {
+    fn need_input(session_ids: Vec<String>, question: Question) -> Self {
+        Self {
+            session_ids,
+            prev_vfs: VirtualFileSystem::new(),
+            incoming_vfs: VirtualFileSystem::new(),
+            commands: Vec::new(),
+            managed_by_template: HashMap::new(),
+            final_answers: HashMap::new(),
+            final_secrets: HashMap::new(),
+            need_input: Some(question),
+        }
+    }
+}
+
+/// What [`batch_process`] hands back to `create`/`update` once the plan is written.
+pub struct BatchOutcome {
+    /// Coordinator sessions to clean at the command boundary.
+    pub session_ids: Vec<String>,
+    pub file_conflicts: Vec<FileConflictEntry>,
+    pub commands: Vec<String>,
+    pub managed_by_template: HashMap<String, Vec<ManagedFile>>,
+    /// Set when the merge left conflict markers on disk: the caller records it in state
+    /// and holds `commands` back until `cyanprint resolve --continue`.
+    pub pending_merge: Option<PendingMerge>,
+    /// Files the user edited that the templates no longer produce; they were kept.
+    pub kept_local: Vec<String>,
+    /// What the write changes on disk.
+    pub diff: VfsDiff,
+    /// The still-open write: the caller commits it after its final state save, and
+    /// dropping it instead rolls the whole write back. `None` when nothing was written.
+    pub transaction: Option<WriteTransaction>,
+    /// Headless: the question a template stopped on; nothing was written.
+    pub need_input: Option<Question>,
+}
+
+impl BatchOutcome {
+    fn need_input(session_ids: Vec<String>, question: Question) -> Self {
+        Self {
+            session_ids,
+            file_conflicts: Vec::new(),
+            commands: Vec::new(),
+            managed_by_template: HashMap::new(),
+            pending_merge: None,
+            kept_local: Vec::new(),
+            diff: VfsDiff::default(),
+            transaction: None,
+            need_input: Some(question),
+        }
+    }
+}
+
+/// Conflict-marker label for one side of the merge: the `<user>/<template>:<version>` of
+/// each spec in `specs`, or `fallback` when there are none.
+fn merge_label<'a>(specs: impl IntoIterator<Item = &'a TemplateSpec>, fallback: &str) -> String {
+    let ids: Vec<String> = specs
+        .into_iter()
+        .map(|s| format!("{}:{}", s.key(), s.version))
+        .collect();
+    if ids.is_empty() {
+        fallback.to_string()
+    } else {
+        ids.join(", ")
+    }
+}
+
+/// Labels for the 3-way merge of `prev_specs` (base) → `curr_specs` (incoming) over the
+/// local files. The incoming side names only the templates that changed version, so a
+/// conflict points at the upgrade that caused it.
+fn merge_labels(prev_specs: &[TemplateSpec], curr_specs: &[TemplateSpec]) -> MergeLabels {
+    let changed: Vec<&TemplateSpec> = curr_specs
+        .iter()
+        .filter(|c| {
+            !prev_specs
+                .iter()
+                .any(|p| p.key() == c.key() && p.version == c.version)
+        })
+        .collect();
+    let incoming = if changed.is_empty() {
+        merge_label(curr_specs, "incoming")
+    } else {
+        merge_label(changed, "incoming")
+    };
+    MergeLabels {
+        base: merge_label(prev_specs, "base"),
+        local: "local".to_string(),
+        incoming,
+    }
+}
+
+impl BatchPlan {
+    fn need_input(session_ids: Vec<String>, question: Question) -> Self {
+        Self {
+            session_ids,
+            local_vfs: VirtualFileSystem::new(),
+            incoming_vfs: VirtualFileSystem::new(),
+            merged_vfs: VirtualFileSystem::new(),
+            file_conflicts: Vec::new(),
+            commands: Vec::new(),
+            managed_by_template: HashMap::new(),
+            final_answers: HashMap::new(),
+            final_secrets: HashMap::new(),
+            need_input: Some(question),
+        }
+    }
+}
+
+/// MAP and LAYER phases of the unified batch: every spec is executed and the prev/curr
+/// outputs are layered. Nothing is read from or written to the target directory, so
+/// `cyanprint status` uses this alone; [`batch_plan`] adds the MERGE on top.
+pub fn batch_layer(
     prev_specs: &[TemplateSpec],
     curr_specs: &[TemplateSpec],
-    upgraded_specs: &[&TemplateSpec], // Templates that need metadata saved
-    target_dir: &Path,
     registry: &CyanRegistryClient,
     coord_client: &CyanCoordinatorClient,
     operator: &mut CompositionOperator,
     headless: bool,
-) -> Result<
-    (
-        Vec<String>,
-        Vec<FileConflictEntry>,
-        Vec<String>,
-        HashMap<String, Vec<String>>,
-        Option<Question>,
-    ),
-    Box<dyn Error + Send>,
-> {
+) -> Result<LayerPlan, Box<dyn Error + Send>> {
     // PHASE 2: MAP (execute each template spec → VFS)
     crate::hprogress!(
         headless,
@@ -131,11 +323,12 @@ pub fn batch_process(
     // Every coordinator session acquired below is registered with `session_guard`, which
     // releases them on ANY early `?` return between acquisition and the point the ids are
     // handed back to the caller — closing the window where a post-acquisition failure
-    // (layering, merge, write, metadata save) would drop the ids and leak the sessions.
+    // (layering, merge) would drop the ids and leak the sessions.
     let mut prev_vfs_list = Vec::new();
     let mut session_guard =
         SessionCleanupGuard::new(|sid: &str| release_session(coord_client, sid), Vec::new());
     let mut prev_template_res_list = Vec::new();
+    let mut asked_secrets: HashMap<String, HashMap<String, Answer>> = HashMap::new();
 
     for spec in prev_specs {
         crate::hprogress!(
@@ -160,13 +353,17 @@ pub fn batch_process(
         // Surface it immediately; no files are written. Hand the sessions to the caller
         // (which cleans them at the headless boundary) by disarming via `take`.
         if let Some(question) = final_state.need_input {
-            return Ok((
-                session_guard.take(),
-                Vec::new(),
-                Vec::new(),
-                HashMap::new(),
-                Some(question),
-            ));
+            return Ok(LayerPlan::need_input(session_guard.take(), question));
+        }
+        // Secrets state did not keep were just asked for; the curr run reuses them
+        let asked: HashMap<String, Answer> = final_state
+            .secret_answers
+            .iter()
+            .filter(|id| !spec.answers.contains_key(*id))
+            .filter_map(|id| Some((id.clone(), final_state.shared_answers.get(id)?.clone())))
+            .collect();
+        if !asked.is_empty() {
+            asked_secrets.insert(spec.key(), asked);
         }
         prev_vfs_list.push(vfs);
         prev_template_res_list.push(template_res);
@@ -177,12 +374,13 @@ pub fn batch_process(
     let mut curr_vfs_list = Vec::new();
     // Map template_key -> final answers for metadata persistence
     let mut final_answers_map: HashMap<String, HashMap<String, Answer>> = HashMap::new();
+    let mut final_secrets_map: HashMap<String, HashSet<String>> = HashMap::new();
     let mut curr_template_res_list = Vec::new();
     let mut curr_resolved_commands = Vec::new();
     // Per-template managed-files manifest, keyed by "<user>/<template>". Sourced from
     // each ACTIVE template's own output VFS BEFORE the LAYER/MERGE phases consume it,
     // so it reflects template output — never the merged result or the user's local files.
-    let mut managed_by_template: HashMap<String, Vec<String>> = HashMap::new();
+    let mut managed_by_template: HashMap<String, Vec<ManagedFile>> = HashMap::new();
 
     for spec in curr_specs {
         crate::hprogress!(
@@ -196,9 +394,13 @@ pub fn batch_process(
             spec.template_name.clone(),
             Some(spec.version),
         )?;
+        let mut answers = spec.answers.clone();
+        for (id, answer) in asked_secrets.remove(&spec.key()).unwrap_or_default() {
+            answers.entry(id).or_insert(answer);
+        }
         let (vfs, final_state, session_ids, commands) = operator.execute_template(
             &template_res,
-            &spec.answers,
+            &answers,
             &spec.deterministic_states,
             headless,
         )?;
@@ -207,23 +409,15 @@ pub fn batch_process(
         // stop the batch before any layering / merge / write happens. Hand the accumulated
         // (prev + curr) sessions to the caller by disarming via `take`.
         if let Some(question) = final_state.need_input {
-            return Ok((
-                session_guard.take(),
-                Vec::new(),
-                Vec::new(),
-                HashMap::new(),
-                Some(question),
-            ));
+            return Ok(LayerPlan::need_input(session_guard.take(), question));
         }
         curr_vfs_list.push(vfs);
-        // Collect this template's normalized output paths from its own VFS (the active
+        // Collect this template's normalized output files from its own VFS (the active
         // set), before layering merges them into one.
-        managed_by_template.insert(
-            spec.key(),
-            normalize_managed_paths(curr_vfs_list.last().unwrap()),
-        );
+        managed_by_template.insert(spec.key(), managed_files(curr_vfs_list.last().unwrap()));
         // Store the final answers for this template (includes Q&A answers)
         final_answers_map.insert(spec.key(), final_state.shared_answers);
+        final_secrets_map.insert(spec.key(), final_state.secret_answers);
         curr_template_res_list.push(template_res);
         // Collect commands from the full dependency tree (not just root templates)
         curr_resolved_commands.extend(commands);
@@ -266,17 +460,176 @@ pub fn batch_process(
         )?
     };
 
-    // PHASE 4: MERGE + WRITE
+    Ok(LayerPlan {
+        // All fallible work is past — disarm the guard and hand the sessions to the caller.
+        session_ids: session_guard.take(),
+        prev_vfs,
+        incoming_vfs: curr_vfs,
+        // Use resolved commands from execute_template which includes the full dependency
+        // tree (prev is just the 3-way-merge baseline; its commands would be duplicates or
+        // stale)
+        commands: curr_resolved_commands,
+        managed_by_template,
+        final_answers: final_answers_map,
+        final_secrets: final_secrets_map,
+        need_input: None,
+    })
+}
+
+/// MAP, LAYER and MERGE phases of the unified batch, with no side effects on
+/// `target_dir`: [`batch_layer`] runs and layers the specs, then the curr layer is 3-way
+/// merged against the local files. Shared by [`batch_process`] (which then writes the
+/// plan) and the `diff` preview (which only renders it).
+pub fn batch_plan(
+    prev_specs: &[TemplateSpec],
+    curr_specs: &[TemplateSpec],
+    target_dir: &Path,
+    registry: &CyanRegistryClient,
+    coord_client: &CyanCoordinatorClient,
+    operator: &mut CompositionOperator,
+    headless: bool,
+) -> Result<BatchPlan, Box<dyn Error + Send>> {
+    let layered = batch_layer(
+        prev_specs,
+        curr_specs,
+        registry,
+        coord_client,
+        operator,
+        headless,
+    )?;
+    if let Some(question) = layered.need_input {
+        return Ok(BatchPlan::need_input(layered.session_ids, question));
+    }
+    // Re-arm the cleanup over the MERGE, which can still fail
+    let mut session_guard = SessionCleanupGuard::new(
+        |sid: &str| release_session(coord_client, sid),
+        layered.session_ids,
+    );
+
+    // PHASE 4a: MERGE (in memory only)
     crate::hprogress!(
         headless,
         "\n📝 PHASE 4: MERGE+WRITE - 3-way merge with local files"
     );
 
     let local_vfs = operator.load_local_files(target_dir)?;
-    let merged_vfs = operator.merge(&prev_vfs, &local_vfs, &curr_vfs)?;
+    let labels = merge_labels(prev_specs, curr_specs);
+    let state = DefaultStateManager::new().load_state_file(&state_file(target_dir))?;
+    let merged_vfs = merge_edited(
+        operator,
+        &state,
+        layered.prev_vfs,
+        &local_vfs,
+        &layered.incoming_vfs,
+        &labels,
+    )?;
+
+    // Collect file conflicts from operator for state persistence
+    let file_conflicts = operator.get_file_conflicts().to_vec();
+
+    Ok(BatchPlan {
+        session_ids: session_guard.take(),
+        local_vfs,
+        incoming_vfs: layered.incoming_vfs,
+        merged_vfs,
+        file_conflicts,
+        commands: layered.commands,
+        managed_by_template: layered.managed_by_template,
+        final_answers: layered.final_answers,
+        final_secrets: layered.final_secrets,
+        need_input: None,
+    })
+}
+
+/// Unified batch processing for both create and update commands.
+/// Handles MAP, LAYER, and MERGE+WRITE phases.
+/// Returns session IDs for cleanup, file conflicts for state persistence, and commands for execution.
+// `headless` controls only whether progress goes to stderr; the per-phase
+// inputs are intrinsic to batch processing, so this stays parameter-heavy.
+#[allow(clippy::too_many_arguments)]
+pub fn batch_process(
+    prev_specs: &[TemplateSpec],
+    curr_specs: &[TemplateSpec],
+    upgraded_specs: &[&TemplateSpec], // Templates that need metadata saved
+    target_dir: &Path,
+    registry: &CyanRegistryClient,
+    coord_client: &CyanCoordinatorClient,
+    operator: &mut CompositionOperator,
+    headless: bool,
+) -> Result<BatchOutcome, Box<dyn Error + Send>> {
+    let plan = batch_plan(
+        prev_specs,
+        curr_specs,
+        target_dir,
+        registry,
+        coord_client,
+        operator,
+        headless,
+    )?;
+    if let Some(question) = plan.need_input {
+        return Ok(BatchOutcome::need_input(plan.session_ids, question));
+    }
+
+    // The sessions from the plan are re-armed for the WRITE phase: a `?` from any
+    // operation below drops the still-armed guard and releases them.
+    let mut session_guard = SessionCleanupGuard::new(
+        |sid: &str| release_session(coord_client, sid),
+        plan.session_ids,
+    );
+    let final_answers_map = plan.final_answers;
+    let mut final_secrets_map = plan.final_secrets;
+
+    // PHASE 4b: WRITE
+    // Every write below (files, deletions, the state file) goes through one transaction:
+    // a `?` drops it uncommitted and rolls all of them back. The caller commits it once
+    // its own state save has succeeded.
+    let io_err = |e: std::io::Error| Box::new(e) as Box<dyn Error + Send>;
+    let mut transaction = WriteTransaction::begin(target_dir).map_err(io_err)?;
+    transaction
+        .protect(&StateLocation::current().relative(target_dir))
+        .map_err(io_err)?;
+
+    // A conflict without markers is a local edit to a file the templates no longer
+    // produce: the edit is kept and reported, there is nothing to resolve.
+    let mut unresolved = Vec::new();
+    let mut kept_local = Vec::new();
+    for path in plan.merged_vfs.conflicted_paths() {
+        let marked = plan
+            .merged_vfs
+            .get_file(&path)
+            .is_some_and(|c| crate::resolve::has_conflict_markers(c));
+        if marked {
+            unresolved.push(normalize_path(&path));
+        } else {
+            kept_local.push(normalize_path(&path));
+        }
+    }
+
+    // A conflicted merge is written with markers and left for `cyanprint resolve`; back
+    // up everything the write touches (and the state file) first so it can be aborted.
+    let pending_merge = if !unresolved.is_empty() {
+        transaction
+            .track(Path::new(crate::resolve::MERGE_DIR))
+            .map_err(io_err)?;
+        let added =
+            crate::resolve::back_up_pre_merge(target_dir, &plan.local_vfs, &plan.merged_vfs)
+                .map_err(io_err)?;
+        Some(PendingMerge {
+            unresolved,
+            added,
+            commands: plan.commands.clone(),
+        })
+    } else {
+        None
+    };
+
+    let diff = VfsDiff::compute(&plan.local_vfs, &plan.merged_vfs);
 
     // Clean up files that were deleted during merge
-    let deleted = operator.cleanup_deleted_files(target_dir, &local_vfs, &merged_vfs)?;
+    transaction
+        .prepare_remove(&deleted_paths(&plan.local_vfs, &plan.merged_vfs))
+        .map_err(io_err)?;
+    let deleted = operator.cleanup_deleted_files(target_dir, &plan.local_vfs, &plan.merged_vfs)?;
     if !deleted.is_empty() {
         crate::hprogress!(
             headless,
@@ -285,7 +638,10 @@ pub fn batch_process(
         );
     }
 
-    operator.write_to_disk(target_dir, &merged_vfs)?;
+    transaction
+        .prepare_write(&plan.merged_vfs)
+        .map_err(io_err)?;
+    operator.write_to_disk(target_dir, &plan.merged_vfs)?;
 
     // Save metadata for upgraded templates only
     if !upgraded_specs.is_empty() {
@@ -315,6 +671,7 @@ pub fn batch_process(
                     plugins: Vec::new(),
                 },
                 final_answers,
+                final_secrets_map.remove(&spec.key()).unwrap_or_default(),
             );
 
             operator.get_template_history().save_template_metadata(
@@ -331,21 +688,68 @@ pub fn batch_process(
     // drops the still-armed guard and releases them).
     let all_session_ids = session_guard.take();
 
-    // Collect file conflicts from operator for state persistence
-    let file_conflicts = operator.get_file_conflicts().to_vec();
+    crate::hprogress!(headless, "✅ Batch process complete");
+    Ok(BatchOutcome {
+        session_ids: all_session_ids,
+        file_conflicts: plan.file_conflicts,
+        commands: plan.commands,
+        managed_by_template: plan.managed_by_template,
+        pending_merge,
+        kept_local,
+        diff,
+        transaction: Some(transaction),
+        need_input: None,
+    })
+}
 
-    // Use resolved commands from execute_template which includes the full dependency tree
-    // (prev is just the 3-way-merge baseline; its commands would be duplicates or stale)
-    let all_commands = curr_resolved_commands;
+/// `cyanprint add` needs a project to add to, and a template not already in it (those
+/// are moved with `update`). A template removed earlier may be added back.
+pub fn ensure_addable(target_dir: &Path, key: &str) -> Result<(), Box<dyn Error + Send>> {
+    let state_file = state_file(target_dir);
+    let state = if state_file.exists() {
+        DefaultStateManager::new().load_state_file(&state_file)?
+    } else {
+        CyanState::default()
+    };
+    if !state.templates.values().any(|ts| ts.active) {
+        return Err(Box::new(std::io::Error::other(format!(
+            "{} is not a cyanprint project; use `cyanprint create` to start one",
+            target_dir.display()
+        ))));
+    }
+    if state.templates.get(key).is_some_and(|ts| ts.active) {
+        return Err(Box::new(std::io::Error::other(format!(
+            "{key} is already in this project; use `cyanprint update {key}` to change its version"
+        ))));
+    }
+    Ok(())
+}
 
-    crate::hprogress!(headless, "✅ Batch process complete");
-    Ok((
-        all_session_ids,
-        file_conflicts,
-        all_commands,
-        managed_by_template,
-        None,
-    ))
+/// Commit the write [`batch_process`] left open, once the caller's state save is done.
+pub(crate) fn commit_write(
+    transaction: Option<WriteTransaction>,
+) -> Result<(), Box<dyn Error + Send>> {
+    match transaction {
+        Some(transaction) => transaction
+            .commit()
+            .map_err(|e| Box::new(e) as Box<dyn Error + Send>),
+        None => Ok(()),
+    }
+}
+
+/// Roll back a write transaction that an earlier, interrupted run left in `target_dir`,
+/// so this run starts from the project as it was before that run.
+pub(crate) fn recover_interrupted_write(
+    target_dir: &Path,
+    headless: bool,
+) -> Result<(), Box<dyn Error + Send>> {
+    if WriteTransaction::recover(target_dir).map_err(|e| Box::new(e) as Box<dyn Error + Send>)? {
+        crate::hprogress!(
+            headless,
+            "♻️ Rolled back an interrupted write left by a previous run"
+        );
+    }
+    Ok(())
 }
 
 /// The shallowest path component of `target_dir` that does not yet exist.
@@ -495,6 +899,8 @@ impl<F: FnMut(&str)> Drop for SessionCleanupGuard<F> {
 
 /// Run the cyan template generation process with automatic composition detection
 /// Returns all session IDs that were created and need to be cleaned up
+/// `headless_answers` and `seed_states` are given to the template being run, so only
+/// the questions they leave open are asked.
 #[allow(clippy::too_many_arguments)]
 pub fn cyan_run(
     session_id_generator: Box<dyn SessionIdGenerator>,
@@ -505,8 +911,10 @@ pub fn cyan_run(
     registry_client: Rc<CyanRegistryClient>,
     debug: bool,
     cache_config: cyancoordinator::cache::CacheConfig,
+    binary_policy: BinaryMergePolicy,
     headless: bool,
     headless_answers: HashMap<String, Answer>,
+    seed_states: HashMap<String, String>,
 ) -> Result<CyanRunResult, Box<dyn Error + Send>> {
     // Handle the target directory
     let path = path.unwrap_or(".".to_string());
@@ -538,7 +946,8 @@ pub fn cyan_run(
     // pollute the single-JSON-on-stdout contract. Disable merger debug under
     // headless so stdout stays reserved for the envelope; interactive `--debug` is
     // unchanged.
-    let merger = Box::new(GitLikeMerger::new(debug && !headless, 50));
+    let merger =
+        Box::new(GitLikeMerger::new(debug && !headless, 50).with_binary_policy(binary_policy));
     let writer = Box::new(DiskFileWriter);
 
     // Setup services with explicit dependencies
@@ -610,16 +1019,18 @@ pub fn cyan_run(
     let manager = TemplateSpecManager::new(Rc::clone(&registry_client));
 
     // Load the current state (may be empty for new projects)
-    let state_file_path = target_dir.join(".cyan_state.yaml");
+    recover_interrupted_write(target_dir, headless)?;
+    let state_file_path = state_file(target_dir);
     let state = DefaultStateManager::new()
         .load_state_file(&state_file_path)
         .unwrap_or_default();
+    crate::resolve::ensure_no_pending_merge(&state)?;
 
     // Build specs using composable primitives
     let mut prev_specs = manager.get(&state);
     sort_specs(&mut prev_specs);
 
-    let (prev_specs, curr_specs, upgraded_specs): (
+    let (mut prev_specs, mut curr_specs, upgraded_specs): (
         Vec<TemplateSpec>,
         Vec<TemplateSpec>,
         Vec<TemplateSpec>,
@@ -672,7 +1083,7 @@ pub fn cyan_run(
                             u.to_string(),
                             t.to_string(),
                             entry.version,
-                            entry.answers.clone(),
+                            entry.replay_answers(),
                             entry.deterministic_states.clone(),
                             entry.time,
                         ))
@@ -703,7 +1114,7 @@ pub fn cyan_run(
                             u.to_string(),
                             t.to_string(),
                             entry.version,
-                            entry.answers.clone(),
+                            entry.replay_answers(),
                             entry.deterministic_states.clone(),
                             entry.time,
                         ))
@@ -751,7 +1162,7 @@ pub fn cyan_run(
                             u.to_string(),
                             t.to_string(),
                             entry.version,
-                            entry.answers.clone(),
+                            entry.replay_answers(),
                             entry.deterministic_states.clone(),
                             entry.time,
                         ))
@@ -773,7 +1184,11 @@ pub fn cyan_run(
         }
     };
 
-    // Headless: seed the supplied answers ONLY into the template(s) this invocation
+    // Secrets state did not keep are supplied wherever their template re-runs
+    fill_missing_secrets(&mut prev_specs, &state, &headless_answers);
+    fill_missing_secrets(&mut curr_specs, &state, &headless_answers);
+
+    // Seed the supplied answers (and states) ONLY into the template(s) this invocation
     // is creating/upgrading/rerunning — never into pre-existing, already-installed
     // templates that happen to be re-executed as part of the batch. A flat answer map
     // has no per-template scoping, so seeding it into every curr_spec would let an
@@ -781,8 +1196,9 @@ pub fn cyan_run(
     // an unrelated installed template's same-named question, skipping its expected
     // `need_input` and generating with the wrong value. Scoping to the upgraded set
     // (NewTemplate → the new spec; Upgrade/Rerun → the target) keeps each template's
-    // Q&A independent.
-    let curr_specs: Vec<TemplateSpec> = if headless && !headless_answers.is_empty() {
+    // Q&A independent. Interactive `create` supplies nothing; `answers import` supplies
+    // the exported answers and states either way.
+    let curr_specs: Vec<TemplateSpec> = if !headless_answers.is_empty() || !seed_states.is_empty() {
         let upgraded_keys: std::collections::HashSet<String> =
             upgraded_specs.iter().map(|s| s.key()).collect();
         curr_specs
@@ -792,6 +1208,9 @@ pub fn cyan_run(
                     for (k, v) in &headless_answers {
                         s.answers.insert(k.clone(), v.clone());
                     }
+                    for (k, v) in &seed_states {
+                        s.deterministic_states.insert(k.clone(), v.clone());
+                    }
                 }
                 s
             })
@@ -804,37 +1223,37 @@ pub fn cyan_run(
     let upgraded_refs: Vec<&TemplateSpec> = upgraded_specs.iter().collect();
 
     // Execute unified batch processing
-    let (session_ids, file_conflicts, commands, managed_by_template, need_input) =
-        match batch_process(
-            &prev_specs,
-            &curr_specs,
-            &upgraded_refs,
-            target_dir,
-            &registry_client,
-            &coord_client,
-            &mut composition_operator,
-            headless,
-        ) {
-            Ok(v) => v,
-            Err(err) => {
-                // Headless: a supplied-answer validation failure or a transport error during
-                // the batch surfaces here (it never reaches a write). The still-armed
-                // `dir_guard` drops on this return and removes the directory tree THIS
-                // invocation created, leaving the filesystem as it was found. Interactive
-                // runs are untouched (the guard is inert when not headless).
-                return Err(err);
-            }
-        };
+    let outcome = match batch_process(
+        &prev_specs,
+        &curr_specs,
+        &upgraded_refs,
+        target_dir,
+        &registry_client,
+        &coord_client,
+        &mut composition_operator,
+        headless,
+    ) {
+        Ok(v) => v,
+        Err(err) => {
+            // Headless: a supplied-answer validation failure or a transport error during
+            // the batch surfaces here (it never reaches a write). The still-armed
+            // `dir_guard` drops on this return and removes the directory tree THIS
+            // invocation created, leaving the filesystem as it was found. Interactive
+            // runs are untouched (the guard is inert when not headless).
+            return Err(err);
+        }
+    };
 
     // Headless: a question is pending. Do NOT persist state, write files, or run
     // post-commands — surface the question and stop (stateless replay). The still-armed
     // `dir_guard` drops on this return and removes the directory tree THIS invocation
     // created (the walk stopped before PHASE 4, so the tree is empty and removal is safe),
     // leaving the filesystem exactly as found; a pre-existing target is untouched.
-    if let Some(question) = need_input {
+    if let Some(question) = outcome.need_input {
         return Ok(CyanRunResult {
-            session_ids,
+            session_ids: outcome.session_ids,
             need_input: Some(question),
+            report: None,
         });
     }
 
@@ -847,8 +1266,10 @@ pub fn cyan_run(
     // from any of them would drop the ids and leak the coordinator sessions. Hand them to a
     // cleanup guard so every error path below releases them; `take()` disarms it on the
     // happy `done` return (where `finish_headless` then cleans normally).
-    let mut session_guard =
-        SessionCleanupGuard::new(|sid: &str| release_session(&coord_client, sid), session_ids);
+    let mut session_guard = SessionCleanupGuard::new(
+        |sid: &str| release_session(&coord_client, sid),
+        outcome.session_ids,
+    );
 
     // One-line cache summary (always printed when caching is enabled). (FR15)
     composition_operator.print_cache_summary();
@@ -858,15 +1279,25 @@ pub fn cyan_run(
     let mut cyan_state = state_manager
         .load_state_file(&state_file_path)
         .unwrap_or_default();
-    let conflicts_count = file_conflicts.len();
-    cyan_state.file_conflicts = file_conflicts;
+    let conflicts_count = outcome.file_conflicts.len();
+    cyan_state.file_conflicts = outcome.file_conflicts;
     // Recompute the managed-files manifest wholesale from this run's active
     // templates: sets each template's `files` and the top-level `managed_files`
     // union, clearing stale entries (e.g. for now-deactivated templates).
-    cyan_state.set_managed_files(&managed_by_template);
+    cyan_state.set_managed_files(&outcome.managed_by_template);
+    cyan_state.pending_merge = outcome.pending_merge;
     let managed_count = cyan_state.managed_files.len();
-    // A save failure here drops the still-armed `session_guard`, releasing the sessions.
+    // A save failure here drops the still-armed `session_guard`, releasing the sessions,
+    // and the uncommitted transaction, rolling the files and state back.
     state_manager.save_state_file(&cyan_state, &state_file_path)?;
+    commit_write(outcome.transaction)?;
+    let mut report = RunReport::for_run(
+        &prev_specs,
+        &upgraded_refs,
+        &outcome.diff,
+        &cyan_state,
+        composition_operator.cache_summary(),
+    );
     if conflicts_count > 0 {
         crate::hprogress!(
             headless,
@@ -880,7 +1311,24 @@ pub fn cyan_run(
         );
     }
 
+    for path in &outcome.kept_local {
+        crate::hprogress!(
+            headless,
+            "✋ Kept {path}: you edited it and no template produces it"
+        );
+    }
+
+    // A conflicted merge holds the post-template commands back until it is resolved.
+    if let Some(pending) = &cyan_state.pending_merge {
+        crate::resolve::report_pending_merge(pending, headless);
+        return Ok(CyanRunResult {
+            report: Some(report),
+            ..CyanRunResult::completed(session_guard.take())
+        });
+    }
+
     // Execute commands if any were collected
+    let commands = outcome.commands;
     if !commands.is_empty() {
         crate::hprogress!(
             headless,
@@ -911,11 +1359,15 @@ pub fn cyan_run(
                 exec_result.succeeded, exec_result.total, exec_result.failed, exec_result.total
             ))));
         }
+        report.commands = exec_result.runs;
     }
 
     // `done`: disarm the guard and hand the sessions to `finish_headless`, which cleans
     // them at the command boundary.
-    Ok(CyanRunResult::completed(session_guard.take()))
+    Ok(CyanRunResult {
+        report: Some(report),
+        ..CyanRunResult::completed(session_guard.take())
+    })
 }
 
 /// Parse template key from the update module
@@ -927,6 +1379,7 @@ fn parse_template_key(template_key: &str) -> Option<(String, String)> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use cyancoordinator::fs::FileWriter;
 
     // A headless run that stops before `done` (need_input OR error) must remove the
     // directory tree IT created so the filesystem is left exactly as found. For a nested
@@ -1099,32 +1552,68 @@ mod tests {
         assert!(is_cyanprint_internal(".cyan_output"));
         // Top-level bookkeeping directory entries are excluded too.
         assert!(is_cyanprint_internal(".cyan_output/foo.txt"));
+        assert!(is_cyanprint_internal(".cyan_txn/journal.json"));
 
         assert!(!is_cyanprint_internal("a.txt"));
         assert!(!is_cyanprint_internal("src/.cyan_state.yaml"));
         assert!(!is_cyanprint_internal("dir/normal.txt"));
     }
 
-    // AC6 + AC7: normalize_managed_paths excludes bookkeeping, normalizes,
-    // sorts, and de-duplicates.
     #[test]
-    fn normalize_managed_paths_filters_and_sorts() {
+    fn ensure_addable_needs_a_project_and_a_new_template() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(ensure_addable(dir.path(), "alice/web").is_err());
+
+        std::fs::write(
+            dir.path().join(".cyan_state.yaml"),
+            "alice/web:\n  active: true\n  history: []\nbob/api:\n  active: false\n  history: []\n",
+        )
+        .unwrap();
+        assert!(ensure_addable(dir.path(), "alice/web").is_err());
+        assert!(ensure_addable(dir.path(), "bob/api").is_ok());
+        assert!(ensure_addable(dir.path(), "carol/db").is_ok());
+    }
+
+    // A write that dies before its caller commits is rolled back by the next run; a
+    // committed one is kept.
+    #[test]
+    fn interrupted_write_is_rolled_back_and_committed_write_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), b"old").unwrap();
+        let mut vfs = VirtualFileSystem::new();
+        vfs.add_file(PathBuf::from("a.txt"), b"new".to_vec());
+
+        let mut transaction = WriteTransaction::begin(dir.path()).unwrap();
+        transaction.prepare_write(&vfs).unwrap();
+        DiskFileWriter.write(dir.path(), &vfs).unwrap();
+        std::mem::forget(transaction);
+        recover_interrupted_write(dir.path(), true).unwrap();
+        assert_eq!(std::fs::read(dir.path().join("a.txt")).unwrap(), b"old");
+
+        let mut transaction = WriteTransaction::begin(dir.path()).unwrap();
+        transaction.prepare_write(&vfs).unwrap();
+        DiskFileWriter.write(dir.path(), &vfs).unwrap();
+        commit_write(Some(transaction)).unwrap();
+        recover_interrupted_write(dir.path(), true).unwrap();
+        assert_eq!(std::fs::read(dir.path().join("a.txt")).unwrap(), b"new");
+    }
+
+    // AC6 + AC7: managed_files excludes bookkeeping, normalizes, sorts, and
+    // de-duplicates, hashing each file's generated content.
+    #[test]
+    fn managed_files_filters_and_sorts() {
         let mut vfs = VirtualFileSystem::new();
         vfs.add_file(PathBuf::from("./b.txt"), vec![]);
-        vfs.add_file(PathBuf::from("a.txt"), vec![]);
+        vfs.add_file(PathBuf::from("a.txt"), b"a".to_vec());
         vfs.add_file(PathBuf::from(".cyan_state.yaml"), vec![]);
         vfs.add_file(PathBuf::from(".cyan_output"), vec![]);
         vfs.add_file(PathBuf::from("dir/c.txt"), vec![]);
 
-        let paths = normalize_managed_paths(&vfs);
-        assert_eq!(
-            paths,
-            vec![
-                "a.txt".to_string(),
-                "b.txt".to_string(),
-                "dir/c.txt".to_string()
-            ]
-        );
+        let files = managed_files(&vfs);
+        let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["a.txt", "b.txt", "dir/c.txt"]);
+        assert!(files[0].matches(b"a"));
+        assert_eq!(files[0].size, Some(1));
     }
 
     // Coordinator sessions acquired during a run must not leak when a fallible step
@@ -0,0 +1,80 @@
+//! `cyanprint state`: housekeeping on the project state file.
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use cyancoordinator::state::{
+    DefaultStateManager, StateFormat, StateLocation, StateReader, StateWriter,
+};
+
+fn other(msg: String) -> Box<dyn Error + Send> {
+    Box::new(std::io::Error::other(msg))
+}
+
+/// Rewrite the state file of the project at `target_dir` in `to`, next to the old one
+/// under the same name, and delete the old file. Returns the new path.
+pub fn migrate_state(target_dir: &Path, to: StateFormat) -> Result<PathBuf, Box<dyn Error + Send>> {
+    crate::run::recover_interrupted_write(target_dir, false)?;
+    let from = StateLocation::current().path(target_dir);
+    if !from.exists() {
+        return Err(other(format!("No state file at {}", from.display())));
+    }
+    let dest = from.with_extension(to.extension());
+    if dest == from {
+        return Err(other(format!("{} is already {to}", from.display())));
+    }
+    if dest.exists() {
+        return Err(other(format!(
+            "{} already exists; remove it or migrate to another format",
+            dest.display()
+        )));
+    }
+
+    let state = DefaultStateManager::new().load_state_file(&from)?;
+    // `resolve --abort` restores the state backup verbatim, in the old format
+    crate::resolve::ensure_no_pending_merge(&state)?;
+    DefaultStateManager::with_format(to).save_state_file(&state, &dest)?;
+    fs::remove_file(&from).map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
+    Ok(dest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const STATE: &str = "\
+schema_version: 2
+alice/web:
+  active: true
+  history: []
+";
+
+    #[test]
+    fn migrates_between_formats() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join(".cyan_state.yaml"), STATE).unwrap();
+
+        let json = migrate_state(dir.path(), StateFormat::Json).unwrap();
+        assert_eq!(json, dir.path().join(".cyan_state.json"));
+        assert!(!dir.path().join(".cyan_state.yaml").exists());
+        let text = fs::read_to_string(&json).unwrap();
+        assert!(text.contains("\"alice/web\""), "got:\n{text}");
+
+        let toml = migrate_state(dir.path(), StateFormat::Toml).unwrap();
+        assert_eq!(toml, dir.path().join(".cyan_state.toml"));
+        let yaml = migrate_state(dir.path(), StateFormat::Yaml).unwrap();
+        assert_eq!(fs::read_to_string(yaml).unwrap(), STATE);
+    }
+
+    #[test]
+    fn refuses_missing_or_same_format() {
+        let dir = TempDir::new().unwrap();
+        assert!(migrate_state(dir.path(), StateFormat::Json).is_err());
+
+        fs::write(dir.path().join(".cyan_state.yaml"), STATE).unwrap();
+        let err = migrate_state(dir.path(), StateFormat::Yaml).unwrap_err();
+        assert!(err.to_string().contains("already yaml"), "got: {err}");
+    }
+}
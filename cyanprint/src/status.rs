@@ -0,0 +1,410 @@
+//! `cyanprint status`: drift between the managed files on disk and what the installed
+//! templates produce.
+//!
+//! Every active template is re-run with its recorded answers through the same MAP →
+//! LAYER pipeline as `update` ([`batch_layer`](crate::run::batch_layer)); the layered
+//! output is then compared against the working tree. Nothing is written. A template
+//! whose omitted secrets no `CYANPRINT_ANSWER_<ID>` variable supplies is skipped and
+//! reported as such.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::error::Error;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use cyancoordinator::client::CyanCoordinatorClient;
+use cyancoordinator::fs::VirtualFileSystem;
+use cyancoordinator::session::SessionIdGenerator;
+use cyancoordinator::state::models::CyanState;
+use cyancoordinator::state::{DefaultStateManager, StateReader, env_var_name, state_file};
+use cyanprompt::domain::models::question::QuestionTrait;
+use cyanregistry::http::client::CyanRegistryClient;
+use serde::Serialize;
+
+use crate::run::{SessionCleanupGuard, batch_layer, recover_interrupted_write, release_session};
+use crate::update::spec::{TemplateSpec, fill_missing_secrets};
+use crate::update::{OperatorFactory, TemplateSpecManager, sort_specs};
+
+/// How a managed (or would-be managed) file differs from the template output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftKind {
+    /// Edited locally: the content no longer matches what the templates produce.
+    Modified,
+    /// Deleted locally although a template still produces it.
+    Deleted,
+    /// Produced by a template but not managed, and a local file of that name exists.
+    Colliding,
+}
+
+impl DriftKind {
+    fn label(self) -> &'static str {
+        match self {
+            DriftKind::Modified => "modified",
+            DriftKind::Deleted => "deleted",
+            DriftKind::Colliding => "colliding",
+        }
+    }
+}
+
+/// One drifted file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Drift {
+    pub path: String,
+    pub kind: DriftKind,
+    /// The `<user>/<template>`s recorded as producing the file; empty for collisions.
+    pub templates: Vec<String>,
+}
+
+/// A template that could not be re-run: state omitted some of its secret answers and
+/// nothing here supplies them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SkippedTemplate {
+    pub template: String,
+    /// The secret question ids without an answer.
+    pub secrets: Vec<String>,
+}
+
+/// Result of `cyanprint status`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct StatusReport {
+    /// No drift, and every template was checked.
+    pub clean: bool,
+    pub drift: Vec<Drift>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<SkippedTemplate>,
+}
+
+/// Compare the managed files in `state` against the template output `expected` and the
+/// files on disk `local`.
+pub fn compute_status(
+    state: &CyanState,
+    expected: &VirtualFileSystem,
+    local: &VirtualFileSystem,
+) -> StatusReport {
+    let mut owners: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for (key, ts) in state.templates.iter().filter(|(_, ts)| ts.active) {
+        for file in &ts.files {
+            owners.entry(&file.path).or_default().push(key.clone());
+        }
+    }
+    for keys in owners.values_mut() {
+        keys.sort();
+    }
+
+    let mut drift = Vec::new();
+    for path in &state.managed_files {
+        let file = PathBuf::from(path);
+        // A managed file the templates no longer produce is not drift: the next
+        // update removes it
+        let Some(want) = expected.get_file(&file) else {
+            continue;
+        };
+        let kind = match local.get_file(&file) {
+            None => DriftKind::Deleted,
+            Some(have) if have != want => DriftKind::Modified,
+            Some(_) => continue,
+        };
+        drift.push(Drift {
+            path: path.clone(),
+            kind,
+            templates: owners.get(path.as_str()).cloned().unwrap_or_default(),
+        });
+    }
+
+    let managed: BTreeSet<&str> = state.managed_files.iter().map(String::as_str).collect();
+    for file in expected.get_paths() {
+        let path = crate::run::normalize_path(&file);
+        if !managed.contains(path.as_str()) && local.get_file(&file).is_some() {
+            drift.push(Drift {
+                path,
+                kind: DriftKind::Colliding,
+                templates: Vec::new(),
+            });
+        }
+    }
+
+    drift.sort_by(|a, b| (a.kind, &a.path).cmp(&(b.kind, &b.path)));
+    StatusReport {
+        clean: drift.is_empty(),
+        drift,
+        skipped: Vec::new(),
+    }
+}
+
+/// Supply the omitted secrets of `specs` from their `CYANPRINT_ANSWER_<ID>` variables and
+/// set aside the templates still missing some.
+fn replayable_specs(
+    mut specs: Vec<TemplateSpec>,
+    state: &CyanState,
+) -> (Vec<TemplateSpec>, Vec<SkippedTemplate>) {
+    fill_missing_secrets(&mut specs, state, &HashMap::new());
+    let mut skipped = Vec::new();
+    specs.retain(|spec| {
+        let missing: Vec<String> = state
+            .templates
+            .get(&spec.key())
+            .and_then(|ts| ts.history.last())
+            .map(|entry| entry.missing_secrets())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|id| !spec.answers.contains_key(id))
+            .collect();
+        if missing.is_empty() {
+            return true;
+        }
+        skipped.push(SkippedTemplate {
+            template: spec.key(),
+            secrets: missing,
+        });
+        false
+    });
+    (specs, skipped)
+}
+
+/// Render `report` for a terminal.
+pub fn write_status<W: Write>(report: &StatusReport, writer: &mut W) -> std::io::Result<()> {
+    for s in &report.skipped {
+        let vars: Vec<String> = s.secrets.iter().map(|id| env_var_name(id)).collect();
+        writeln!(
+            writer,
+            "Skipped {}: no answer for secret(s) {} (set {})",
+            s.template,
+            s.secrets.join(", "),
+            vars.join(", ")
+        )?;
+    }
+    if report.drift.is_empty() {
+        return if report.skipped.is_empty() {
+            writeln!(writer, "Clean: every managed file matches its templates")
+        } else {
+            writeln!(writer, "No drift in the other templates")
+        };
+    }
+    for d in &report.drift {
+        let owners = if d.templates.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", d.templates.join(", "))
+        };
+        writeln!(writer, "  {:<10} {}{owners}", d.kind.label(), d.path)?;
+    }
+    writeln!(writer, "\n{} file(s) drifted", report.drift.len())
+}
+
+/// Re-run every active template with its recorded answers and report drift against the
+/// working tree. Only the coordinator sessions are touched; the project is not written.
+pub fn cyan_status(
+    session_id_generator: Box<dyn SessionIdGenerator>,
+    path: &str,
+    coord_client: &CyanCoordinatorClient,
+    registry_client: Rc<CyanRegistryClient>,
+    cache_config: cyancoordinator::cache::CacheConfig,
+    binary_policy: cyancoordinator::fs::BinaryMergePolicy,
+) -> Result<StatusReport, Box<dyn Error + Send>> {
+    let target_dir = Path::new(path);
+    recover_interrupted_write(target_dir, true)?;
+    let state = DefaultStateManager::new().load_state_file(&state_file(target_dir))?;
+
+    let manager = TemplateSpecManager::new(Rc::clone(&registry_client));
+    let (mut specs, skipped) = replayable_specs(manager.get(&state), &state);
+    sort_specs(&mut specs);
+
+    let mut operator = OperatorFactory::create_composition_operator(
+        session_id_generator,
+        coord_client.clone(),
+        Rc::clone(&registry_client),
+        false,
+        cache_config,
+        binary_policy,
+        true,
+    );
+    // Headless: recorded answers must be enough, and progress stays off stdout
+    let plan = batch_layer(
+        &[],
+        &specs,
+        &registry_client,
+        coord_client,
+        &mut operator,
+        true,
+    )?;
+    let _session_guard = SessionCleanupGuard::new(
+        |sid: &str| release_session(coord_client, sid),
+        plan.session_ids,
+    );
+    if let Some(question) = plan.need_input {
+        return Err(Box::new(std::io::Error::other(format!(
+            "cannot regenerate the templates: question '{}' has no recorded answer",
+            question.id()
+        ))));
+    }
+
+    // Only the layered output is compared: no 3-way merge, so nothing here depends on
+    // how differing files would be merged
+    let local_vfs = operator.load_local_files(target_dir)?;
+    let mut report = compute_status(&state, &plan.incoming_vfs, &local_vfs);
+    report.clean &= skipped.is_empty();
+    report.skipped = skipped;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cyancoordinator::state::models::{ManagedFile, TemplateHistoryEntry, TemplateState};
+
+    fn vfs(files: &[(&str, &str)]) -> VirtualFileSystem {
+        let mut vfs = VirtualFileSystem::new();
+        for (path, content) in files {
+            vfs.add_file(PathBuf::from(path), content.as_bytes().to_vec());
+        }
+        vfs
+    }
+
+    fn state(templates: &[(&str, &[&str])]) -> CyanState {
+        let mut state = CyanState::default();
+        for (key, files) in templates {
+            state.templates.insert(
+                key.to_string(),
+                TemplateState {
+                    active: true,
+                    history: Vec::new(),
+                    files: files
+                        .iter()
+                        .map(|f| ManagedFile::new(f.to_string(), b""))
+                        .collect(),
+                    pinned: false,
+                    constraint: None,
+                },
+            );
+        }
+        let mut managed: Vec<String> = templates
+            .iter()
+            .flat_map(|(_, files)| files.iter().map(|f| f.to_string()))
+            .collect();
+        managed.sort();
+        managed.dedup();
+        state.managed_files = managed;
+        state
+    }
+
+    #[test]
+    fn reports_modified_deleted_and_colliding() {
+        let state = state(&[
+            ("alice/web", &["a.txt", "gone.txt", "shared.txt"]),
+            ("bob/api", &["shared.txt", "same.txt"]),
+        ]);
+        let expected = vfs(&[
+            ("a.txt", "a\n"),
+            ("gone.txt", "g\n"),
+            ("shared.txt", "s\n"),
+            ("same.txt", "x\n"),
+            ("new.txt", "n\n"),
+        ]);
+        let local = vfs(&[
+            ("a.txt", "a\n"),
+            ("shared.txt", "edited\n"),
+            ("same.txt", "x\n"),
+            ("new.txt", "user's own\n"),
+        ]);
+
+        let report = compute_status(&state, &expected, &local);
+        assert!(!report.clean);
+        assert_eq!(
+            report.drift,
+            vec![
+                Drift {
+                    path: "shared.txt".into(),
+                    kind: DriftKind::Modified,
+                    templates: vec!["alice/web".into(), "bob/api".into()],
+                },
+                Drift {
+                    path: "gone.txt".into(),
+                    kind: DriftKind::Deleted,
+                    templates: vec!["alice/web".into()],
+                },
+                Drift {
+                    path: "new.txt".into(),
+                    kind: DriftKind::Colliding,
+                    templates: vec![],
+                },
+            ]
+        );
+
+        let mut out = Vec::new();
+        write_status(&report, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("  modified   shared.txt (alice/web, bob/api)\n"));
+        assert!(out.contains("3 file(s) drifted"));
+    }
+
+    #[test]
+    fn matching_tree_is_clean() {
+        let state = state(&[("alice/web", &["a.txt"])]);
+        let files = vfs(&[("a.txt", "a\n")]);
+        let report = compute_status(&state, &files, &files);
+        assert!(report.clean);
+        assert_eq!(
+            serde_json::to_string(&report).unwrap(),
+            r#"{"clean":true,"drift":[]}"#
+        );
+    }
+
+    #[test]
+    fn templates_with_unavailable_secrets_are_skipped() {
+        let mut state = state(&[("alice/web", &["a.txt"]), ("bob/api", &["b.txt"])]);
+        for (key, secrets) in [("alice/web", Some(["status_token"])), ("bob/api", None)] {
+            state
+                .templates
+                .get_mut(key)
+                .unwrap()
+                .history
+                .push(TemplateHistoryEntry {
+                    version: 1,
+                    time: chrono::Utc::now(),
+                    answers: HashMap::new(),
+                    deterministic_states: HashMap::new(),
+                    secrets: secrets.map(|ids| ids.iter().map(|id| id.to_string()).collect()),
+                    sealed: Default::default(),
+                });
+        }
+        let spec = |user: &str, name: &str| {
+            TemplateSpec::new(
+                user.into(),
+                name.into(),
+                1,
+                HashMap::new(),
+                HashMap::new(),
+                chrono::Utc::now(),
+            )
+        };
+
+        let (specs, skipped) =
+            replayable_specs(vec![spec("alice", "web"), spec("bob", "api")], &state);
+        assert_eq!(
+            specs.iter().map(TemplateSpec::key).collect::<Vec<_>>(),
+            ["bob/api"]
+        );
+        assert_eq!(
+            skipped,
+            vec![SkippedTemplate {
+                template: "alice/web".into(),
+                secrets: vec!["status_token".into()],
+            }]
+        );
+
+        let report = StatusReport {
+            clean: false,
+            drift: Vec::new(),
+            skipped,
+        };
+        let mut out = Vec::new();
+        write_status(&report, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Skipped alice/web: no answer for secret(s) status_token \
+             (set CYANPRINT_ANSWER_STATUS_TOKEN)\nNo drift in the other templates\n"
+        );
+    }
+}
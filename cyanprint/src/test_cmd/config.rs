@@ -126,6 +126,14 @@ pub enum AnswerStateEntry {
     /// Boolean answer for confirm questions
     #[serde(rename = "Bool")]
     Bool(bool),
+
+    /// Numeric answer for number questions
+    #[serde(rename = "Number")]
+    Number(f64),
+
+    /// Structured answer (a JSON/YAML mapping)
+    #[serde(rename = "Object")]
+    Object(serde_json::Map<String, serde_json::Value>),
 }
 
 /// File glob pattern entry.
@@ -456,6 +464,31 @@ value: true
         }
     }
 
+    #[test]
+    fn test_parse_answer_state_entry_number_and_object() {
+        let entry: AnswerStateEntry =
+            serde_yaml::from_str("type: Number\nvalue: 8080\n").expect("Failed to parse YAML");
+        match entry {
+            AnswerStateEntry::Number(n) => assert_eq!(n, 8080.0),
+            _ => panic!("Expected Number variant"),
+        }
+
+        let yaml = r#"
+type: Object
+value:
+  host: localhost
+  port: 5432
+"#;
+        let entry: AnswerStateEntry = serde_yaml::from_str(yaml).expect("Failed to parse YAML");
+        match entry {
+            AnswerStateEntry::Object(map) => {
+                assert_eq!(map["host"], "localhost");
+                assert_eq!(map["port"], 5432);
+            }
+            _ => panic!("Expected Object variant"),
+        }
+    }
+
     #[test]
     fn test_parse_expected_output_snapshot() {
         let yaml = r#"
@@ -20,7 +20,7 @@ use bollard::Docker;
 use reqwest::blocking::Client;
 
 use cyanprompt::domain::models::answer::Answer;
-use cyanprompt::domain::models::question::{Question, QuestionTrait};
+use cyanprompt::domain::models::question::{Question, QuestionTrait, key_value_object};
 use cyanprompt::domain::models::template::{input::TemplateAnswerInput, output::TemplateOutput};
 use cyanprompt::domain::services::repo::{CyanHttpRepo, CyanRepo};
 use cyanprompt::http::client::CyanClient;
@@ -81,6 +81,10 @@ enum DiscoveredQuestionType {
     Select,
     Confirm,
     Checkbox,
+    Number,
+    Editor,
+    Path,
+    List,
 }
 
 /// A question discovered during pass-1 tree walking.
@@ -101,6 +105,10 @@ fn classify_question(question: &Question) -> (DiscoveredQuestionType, String) {
         Question::Select(q) => (DiscoveredQuestionType::Select, q.message.clone()),
         Question::Confirm(q) => (DiscoveredQuestionType::Confirm, q.message.clone()),
         Question::Checkbox(q) => (DiscoveredQuestionType::Checkbox, q.message.clone()),
+        Question::Number(q) => (DiscoveredQuestionType::Number, q.message.clone()),
+        Question::Editor(q) => (DiscoveredQuestionType::Editor, q.message.clone()),
+        Question::Path(q) => (DiscoveredQuestionType::Path, q.message.clone()),
+        Question::List(q) => (DiscoveredQuestionType::List, q.message.clone()),
     }
 }
 
@@ -459,6 +467,8 @@ fn answer_display(entry: &AnswerStateEntry) -> String {
             }
         }
         AnswerStateEntry::Bool(b) => if *b { "yes" } else { "no" }.to_string(),
+        AnswerStateEntry::Number(n) => n.to_string(),
+        AnswerStateEntry::Object(map) => serde_json::Value::Object(map.clone()).to_string(),
     }
 }
 
@@ -546,6 +556,15 @@ fn branch_already_exists(branches: &[(Answer, String)], label: &str) -> bool {
     branches.iter().any(|(_, l)| l == label)
 }
 
+/// The answer for a typed-in branch value: a number for `Number` questions (`None` when
+/// it does not parse), the raw string otherwise.
+fn typed_answer(question_type: &DiscoveredQuestionType, value: &str) -> Option<Answer> {
+    match question_type {
+        DiscoveredQuestionType::Number => value.parse::<f64>().ok().map(Answer::Number),
+        _ => Some(Answer::String(value.to_string())),
+    }
+}
+
 /// Convert an `inquire::InquireError` into our boxed error type.
 fn inquire_err(e: inquire::InquireError) -> Box<dyn Error + Send> {
     Box::new(std::io::Error::other(e.to_string()))
@@ -553,11 +572,12 @@ fn inquire_err(e: inquire::InquireError) -> Box<dyn Error + Send> {
 
 /// Interactive modification of discovered branches.
 ///
-/// - **Text/Password**: Reprompt loop — shows default seed, asks for values one at a time
-///   until user says no to "Add another?". Deduplicates by label.
+/// - **Text/Password/Number/Editor/Path**: Reprompt loop — shows default seed, asks for
+///   values one at a time until user says no to "Add another?". Deduplicates by label.
 /// - **Date**: `DateSelect` date-picker widget; reprompt loop with dedup on formatted date.
 /// - **Checkbox**: `MultiSelect` from individual options per combination; dedup on sorted label.
-/// - **Select/Confirm**: `MultiSelect` to de-select branches; enforces ≥1 branch via reprompt.
+/// - **Select/Confirm/List**: `MultiSelect` to de-select branches; enforces ≥1 branch via
+///   reprompt.
 fn interactive_modify(
     discovered: &[DiscoveredQuestion],
 ) -> Result<BranchConfig, Box<dyn Error + Send>> {
@@ -565,7 +585,11 @@ fn interactive_modify(
 
     for dq in discovered {
         match dq.question_type {
-            DiscoveredQuestionType::Text | DiscoveredQuestionType::Password => {
+            DiscoveredQuestionType::Text
+            | DiscoveredQuestionType::Password
+            | DiscoveredQuestionType::Number
+            | DiscoveredQuestionType::Editor
+            | DiscoveredQuestionType::Path => {
                 if dq.branches.is_empty() {
                     branch_config.insert(dq.id.clone(), dq.branches.clone());
                     continue;
@@ -587,13 +611,11 @@ fn interactive_modify(
                 let first_trimmed = first.trim();
                 if first_trimmed.is_empty() {
                     branches.push(dq.branches[0].clone());
-                } else if !branch_already_exists(&branches, first_trimmed) {
-                    branches.push((
-                        Answer::String(first_trimmed.to_string()),
-                        first_trimmed.to_string(),
-                    ));
+                } else if let Some(answer) = typed_answer(&dq.question_type, first_trimmed) {
+                    branches.push((answer, first_trimmed.to_string()));
                 } else {
-                    println!("  (skipped duplicate '{first_trimmed}')");
+                    println!("  Not a number, keeping '{default_seed}'");
+                    branches.push(dq.branches[0].clone());
                 }
 
                 // Reprompt loop
@@ -613,11 +635,10 @@ fn interactive_modify(
                     if !next_trimmed.is_empty() {
                         if branch_already_exists(&branches, next_trimmed) {
                             println!("  (skipped duplicate '{next_trimmed}')");
+                        } else if let Some(answer) = typed_answer(&dq.question_type, next_trimmed) {
+                            branches.push((answer, next_trimmed.to_string()));
                         } else {
-                            branches.push((
-                                Answer::String(next_trimmed.to_string()),
-                                next_trimmed.to_string(),
-                            ));
+                            println!("  (skipped non-numeric '{next_trimmed}')");
                         }
                     }
                 }
@@ -760,7 +781,9 @@ fn interactive_modify(
                 println!("  {}: {} combination(s)", dq.id, branches.len());
                 branch_config.insert(dq.id.clone(), branches);
             }
-            DiscoveredQuestionType::Select | DiscoveredQuestionType::Confirm => {
+            DiscoveredQuestionType::Select
+            | DiscoveredQuestionType::Confirm
+            | DiscoveredQuestionType::List => {
                 if dq.branches.len() <= 1 {
                     if !dq.branches.is_empty() {
                         println!(
@@ -875,6 +898,8 @@ fn full_dfs(
                     Answer::String(s) => AnswerStateEntry::String(s.clone()),
                     Answer::StringArray(arr) => AnswerStateEntry::StringArray(arr.clone()),
                     Answer::Bool(b) => AnswerStateEntry::Bool(*b),
+                    Answer::Number(n) => AnswerStateEntry::Number(*n),
+                    Answer::Object(map) => AnswerStateEntry::Object(map.clone()),
                 };
                 answer_state.insert(question_id.clone(), entry);
             }
@@ -950,7 +975,8 @@ fn get_answer_branches(
         Question::Select(q) => q
             .options
             .iter()
-            .map(|opt| (Answer::String(opt.clone()), opt.clone()))
+            .filter(|opt| !opt.disabled)
+            .map(|opt| (Answer::String(opt.value.clone()), opt.value.clone()))
             .collect(),
         Question::Confirm(_q) => {
             vec![
@@ -959,7 +985,13 @@ fn get_answer_branches(
             ]
         }
         Question::Checkbox(q) => {
-            if q.options.is_empty() {
+            let values: Vec<String> = q
+                .options
+                .iter()
+                .filter(|opt| !opt.disabled)
+                .map(|opt| opt.value.clone())
+                .collect();
+            if values.is_empty() {
                 return Vec::new();
             }
 
@@ -969,17 +1001,56 @@ fn get_answer_branches(
             branches.push((Answer::StringArray(Vec::new()), "none".to_string()));
 
             // Each individual option
-            for opt in &q.options {
-                branches.push((Answer::StringArray(vec![opt.clone()]), opt.clone()));
+            for value in &values {
+                branches.push((Answer::StringArray(vec![value.clone()]), value.clone()));
             }
 
             // All options (only when there are at least 2, to avoid duplicating the singleton)
-            if q.options.len() > 1 {
-                branches.push((Answer::StringArray(q.options.clone()), "all".to_string()));
+            if values.len() > 1 {
+                branches.push((Answer::StringArray(values), "all".to_string()));
             }
 
             branches
         }
+        Question::Number(q) => {
+            // The default and both bounds, when they are valid answers.
+            let mut values: Vec<f64> = Vec::new();
+            for v in [q.default, q.min, q.max].into_iter().flatten() {
+                if q.accepts(v) && !values.contains(&v) {
+                    values.push(v);
+                }
+            }
+            if values.is_empty() {
+                values.push(q.min.unwrap_or(0.0));
+            }
+            values
+                .into_iter()
+                .map(|v| (Answer::Number(v), v.to_string()))
+                .collect()
+        }
+        Question::Editor(_q) => {
+            vec![(Answer::String(text_seed.to_string()), text_seed.to_string())]
+        }
+        Question::Path(q) => {
+            let path = q.default.clone().unwrap_or_else(|| ".".to_string());
+            vec![(Answer::String(path.clone()), path)]
+        }
+        Question::List(q) if q.is_key_value() => {
+            let items = q
+                .default
+                .clone()
+                .unwrap_or_else(|| vec![format!("key={text_seed}")]);
+            let label = items.join("+");
+            vec![(Answer::Object(key_value_object(&items)), label)]
+        }
+        Question::List(q) => {
+            let items = q
+                .default
+                .clone()
+                .unwrap_or_else(|| vec![text_seed.to_string()]);
+            let label = items.join("+");
+            vec![(Answer::StringArray(items), label)]
+        }
     }
 }
 
@@ -1451,6 +1522,7 @@ mod tests {
             default: None,
             desc: None,
             initial: None,
+            constraints: Default::default(),
             id: "q1".to_string(),
         };
 
@@ -1463,6 +1535,50 @@ mod tests {
         assert_eq!(branches[0].1, "seed");
     }
 
+    #[test]
+    fn test_get_answer_branches_number() {
+        use cyanprompt::domain::models::question::{NumberQuestion, Question};
+
+        let q = NumberQuestion {
+            message: "Port".to_string(),
+            desc: None,
+            default: Some(8080.0),
+            min: Some(1024.0),
+            max: Some(65535.0),
+            step: None,
+            id: "q1".to_string(),
+        };
+
+        let branches = get_answer_branches(&Question::Number(q), "seed", "pass", "2024-01-01");
+        let labels: Vec<&str> = branches.iter().map(|(_, l)| l.as_str()).collect();
+        assert_eq!(labels, vec!["8080", "1024", "65535"]);
+        assert!(matches!(&branches[0].0, Answer::Number(n) if *n == 8080.0));
+    }
+
+    #[test]
+    fn test_get_answer_branches_key_value_list() {
+        use cyanprompt::domain::models::question::{ListQuestion, Question};
+
+        let q = ListQuestion {
+            message: "Env".to_string(),
+            desc: None,
+            default: Some(vec!["PORT=80".to_string(), "HOST=a=b".to_string()]),
+            key_value: Some(true),
+            id: "q1".to_string(),
+        };
+
+        let branches = get_answer_branches(&Question::List(q), "seed", "pass", "2024-01-01");
+        assert_eq!(branches.len(), 1);
+        assert_eq!(branches[0].1, "PORT=80+HOST=a=b");
+        match &branches[0].0 {
+            Answer::Object(o) => {
+                assert_eq!(o["PORT"], "80");
+                assert_eq!(o["HOST"], "a=b");
+            }
+            _ => panic!("Expected Object answer"),
+        }
+    }
+
     #[test]
     fn test_get_answer_branches_select() {
         use cyanprompt::domain::models::question::{Question, SelectQuestion};
@@ -1470,7 +1586,8 @@ mod tests {
         let q = SelectQuestion {
             message: "Choose".to_string(),
             desc: None,
-            options: vec!["opt1".to_string(), "opt2".to_string()],
+            options: vec!["opt1".into(), "opt2".into()],
+            default: None,
             id: "q1".to_string(),
         };
 
@@ -1520,7 +1637,8 @@ mod tests {
 
         let q = CheckboxQuestion {
             message: "Select".to_string(),
-            options: vec!["opt1".to_string(), "opt2".to_string()],
+            options: vec!["opt1".into(), "opt2".into()],
+            default: None,
             desc: None,
             id: "q1".to_string(),
         };
@@ -1541,6 +1659,7 @@ mod tests {
         let q = CheckboxQuestion {
             message: "Select".to_string(),
             options: vec![],
+            default: None,
             desc: None,
             id: "q1".to_string(),
         };
@@ -1555,7 +1674,8 @@ mod tests {
 
         let q = CheckboxQuestion {
             message: "Select".to_string(),
-            options: vec!["only".to_string()],
+            options: vec!["only".into()],
+            default: None,
             desc: None,
             id: "q1".to_string(),
         };
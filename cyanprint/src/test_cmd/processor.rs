@@ -88,10 +88,9 @@ pub fn run_processor_tests(
     };
 
     if test_cases.is_empty() {
-        if test_filter.is_some() {
+        if let Some(name) = test_filter {
             return Err(Box::new(std::io::Error::other(format!(
-                "Test case '{}' not found",
-                test_filter.unwrap()
+                "Test case '{name}' not found"
             ))) as Box<dyn Error + Send>);
         } else {
             println!("No tests found");
@@ -203,10 +203,9 @@ pub fn run_template_tests(
     };
 
     if test_cases.is_empty() {
-        if test_filter.is_some() {
+        if let Some(name) = test_filter {
             return Err(Box::new(std::io::Error::other(format!(
-                "Test case '{}' not found",
-                test_filter.unwrap()
+                "Test case '{name}' not found"
             ))) as Box<dyn Error + Send>);
         } else {
             println!("No tests found");
@@ -697,6 +696,12 @@ fn run_single_composition_test_case(
             AnswerStateEntry::Bool(b) => {
                 answers.insert(question_id.clone(), Answer::Bool(*b));
             }
+            AnswerStateEntry::Number(n) => {
+                answers.insert(question_id.clone(), Answer::Number(*n));
+            }
+            AnswerStateEntry::Object(map) => {
+                answers.insert(question_id.clone(), Answer::Object(map.clone()));
+            }
         }
     }
 
@@ -1219,6 +1224,12 @@ fn run_single_test_case(
             AnswerStateEntry::Bool(b) => {
                 answers.insert(question_id.clone(), Answer::Bool(*b));
             }
+            AnswerStateEntry::Number(n) => {
+                answers.insert(question_id.clone(), Answer::Number(*n));
+            }
+            AnswerStateEntry::Object(map) => {
+                answers.insert(question_id.clone(), Answer::Object(map.clone()));
+            }
         }
     }
 
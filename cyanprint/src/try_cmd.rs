@@ -67,6 +67,9 @@ struct TryConfig {
 /// boundary emits it. This mirrors the create/update flow, where `cyan_run` returns a
 /// `CyanRunResult` and `finish_headless` emits at the boundary (no split emission, no hidden
 /// "already printed elsewhere" contract). Errors are the `Err` arm of the enclosing `Result`.
+// `NeedInput` carrying a whole question dwarfs `Done`; this is a single-shot return
+// value, so boxing it would only add an indirection.
+#[allow(clippy::large_enum_variant)]
 pub enum TryHeadlessOutcome {
     /// The run completed; the boundary emits `done` (exit 0).
     Done,
@@ -1409,7 +1412,7 @@ fn run_qa_loop(
     let state = prompter.start_with(None, None);
 
     match state {
-        TemplateState::Complete(cyan, answers) => {
+        TemplateState::Complete(cyan, answers, _) => {
             let states = extract_deterministic_states(&answers);
             Ok((cyan, answers, states))
         }
@@ -1457,7 +1460,7 @@ fn run_qa_loop_headless(
     let prompter = build_template_prompter(template_endpoint(dev_mode, cyan_yaml_path, port)?)?;
 
     match prompter.start_headless(Some(answers)) {
-        TemplateState::Complete(cyan, answers) => {
+        TemplateState::Complete(cyan, answers, _) => {
             let states = extract_deterministic_states(&answers);
             Ok(HeadlessQaOutcome::Complete(cyan, answers, states))
         }
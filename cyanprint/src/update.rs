@@ -8,22 +8,42 @@ use cyanregistry::http::client::CyanRegistryClient;
 use crate::headless::CyanRunResult;
 
 // Re-export the modular update system
+mod branch;
 mod operator_factory;
 mod orchestrator;
+mod pin;
+mod recursive;
+mod remove;
+mod rewrite;
 pub mod spec;
+mod undo;
 mod utils;
 mod version_manager;
 
 use orchestrator::UpdateOrchestrator;
 
 // Re-export public interface
+pub use branch::{commit_message, pull_request_description, update_on_branch};
+pub(crate) use operator_factory::OperatorFactory;
 pub use orchestrator::UserAborted;
-pub use spec::{TemplateSpec, TemplateSpecManager, sort_specs};
-pub use utils::{SelectionError, parse_template_key};
+pub use pin::cyan_pin;
+pub use recursive::{
+    ProjectOutcome, ProjectStatus, RecursiveOptions, RecursiveReport, Upgrade, UpgradeGroup,
+    cyan_update_recursive, discover_projects, write_recursive_report,
+};
+pub use remove::cyan_remove;
+pub use spec::{
+    Reanswer, TemplateSpec, TemplateSpecManager, UpdateSelection, VersionPlan, sort_specs,
+};
+pub use undo::cyan_undo;
+pub use utils::{SelectionError, parse_template_key, parse_template_target, parse_version_target};
 pub use version_manager::{TemplateVersionInfo, format_friendly_date, select_version_interactive};
 
 /// Update all templates in a project to their latest versions with automatic composition detection
 /// Returns all session IDs that were created and need to be cleaned up
+/// With `dry_run`, only prints the diff the update would apply.
+/// `targets` and `versions` (`--to`) restrict which templates move; empty means all.
+/// `reanswer` forgets recorded answers so those questions are asked again.
 #[allow(clippy::too_many_arguments)]
 pub fn cyan_update(
     session_id_generator: Box<dyn SessionIdGenerator>,
@@ -34,8 +54,13 @@ pub fn cyan_update(
     interactive: bool,
     force: bool,
     cache_config: cyancoordinator::cache::CacheConfig,
+    binary_policy: cyancoordinator::fs::BinaryMergePolicy,
     headless: bool,
     headless_answers: std::collections::HashMap<String, cyanprompt::domain::models::answer::Answer>,
+    dry_run: bool,
+    targets: &[String],
+    versions: &[(String, i64)],
+    reanswer: &Reanswer,
 ) -> Result<CyanRunResult, Box<dyn Error + Send>> {
     UpdateOrchestrator::update_templates(
         session_id_generator,
@@ -46,7 +71,12 @@ pub fn cyan_update(
         interactive,
         force,
         cache_config,
+        binary_policy,
         headless,
         headless_answers,
+        dry_run,
+        targets,
+        versions,
+        reanswer,
     )
 }
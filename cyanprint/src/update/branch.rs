@@ -0,0 +1,315 @@
+//! `cyanprint update --branch`: run an update on a new git branch and commit the result,
+//! for scheduled upgrade pull requests.
+
+use std::error::Error;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use cyancoordinator::conflict_file_resolver::{ConflictResolution, FileConflictEntry};
+
+use crate::git::{GitError, ProjectRepo};
+use crate::headless::CyanRunResult;
+use crate::report::{RunReport, TemplateChange};
+
+fn git_error(e: GitError) -> Box<dyn Error + Send> {
+    Box::new(std::io::Error::other(format!("--branch: {e}")))
+}
+
+/// Create `branch` at the current commit, run `update` on it, and commit what it wrote
+/// with a message generated from the run's report; write a pull request description to
+/// `pr_description` when given. A failed update, or one that upgraded nothing, leaves
+/// the original branch checked out and `branch` deleted again.
+///
+/// `update` must return its [`RunReport`], whatever `--output` asks for.
+pub fn update_on_branch<F>(
+    target_dir: &Path,
+    branch: &str,
+    pr_description: Option<&Path>,
+    headless: bool,
+    update: F,
+) -> Result<CyanRunResult, Box<dyn Error + Send>>
+where
+    F: FnOnce() -> Result<CyanRunResult, Box<dyn Error + Send>>,
+{
+    let repo = ProjectRepo::open(target_dir).map_err(git_error)?;
+    if repo.is_dirty().map_err(git_error)? {
+        return Err(Box::new(std::io::Error::other(
+            "--branch needs a clean working tree; commit or stash your changes first",
+        )));
+    }
+    let base = repo.current_branch().map_err(git_error)?;
+    repo.create_branch(branch).map_err(git_error)?;
+    crate::hprogress!(headless, "🌿 Switched to new branch '{branch}'");
+
+    let result = match update() {
+        Ok(result) => result,
+        Err(e) => {
+            // The write was rolled back; the update's own error is the one to report
+            let _ = repo.abandon_branch(branch, &base);
+            return Err(e);
+        }
+    };
+    let report = match (&result.need_input, &result.report) {
+        (None, Some(report)) if !report.templates.is_empty() => report,
+        _ => {
+            repo.abandon_branch(branch, &base).map_err(git_error)?;
+            crate::hprogress!(headless, "ℹ️  Nothing to commit; back on '{base}'");
+            return Ok(result);
+        }
+    };
+
+    match repo
+        .commit_project(&commit_message(report))
+        .map_err(git_error)?
+    {
+        Some(oid) => crate::hprogress!(headless, "📦 Committed {oid} on '{branch}'"),
+        None => {
+            repo.abandon_branch(branch, &base).map_err(git_error)?;
+            crate::hprogress!(headless, "ℹ️  Nothing to commit; back on '{base}'");
+            return Ok(result);
+        }
+    }
+    if let Some(path) = pr_description {
+        fs::write(path, pull_request_description(report, branch, &base))
+            .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
+        crate::hprogress!(headless, "📝 Wrote pull request description to {path:?}");
+    }
+    Ok(result)
+}
+
+fn version_bump(change: &TemplateChange) -> String {
+    match change.from {
+        Some(from) => format!("v{from} → v{}", change.to),
+        None => format!("new at v{}", change.to),
+    }
+}
+
+fn resolution(entry: &FileConflictEntry) -> &'static str {
+    match entry.resolution {
+        ConflictResolution::Resolver => "merged by a resolver",
+        _ => "last template wins",
+    }
+}
+
+/// Commit message for an update: a subject naming the upgrade, each template's version
+/// bump, and the files left with conflict markers.
+pub fn commit_message(report: &RunReport) -> String {
+    let mut msg = match report.templates.as_slice() {
+        [only] => format!("Update {} ({})\n", only.template, version_bump(only)),
+        all => format!("Update {} templates\n", all.len()),
+    };
+    msg.push('\n');
+    for change in &report.templates {
+        let _ = writeln!(msg, "- {}: {}", change.template, version_bump(change));
+    }
+    if !report.unresolved.is_empty() {
+        msg.push_str("\nConflicts left for `cyanprint resolve`:\n");
+        for path in &report.unresolved {
+            let _ = writeln!(msg, "- {path}");
+        }
+    }
+    msg
+}
+
+/// Markdown pull request description for an update committed on `branch`.
+pub fn pull_request_description(report: &RunReport, branch: &str, base: &str) -> String {
+    let mut md = format!("## Template upgrades\n\nMerges `{branch}` into `{base}`.\n\n");
+    md.push_str("| Template | Version |\n| --- | --- |\n");
+    for change in &report.templates {
+        let _ = writeln!(md, "| `{}` | {} |", change.template, version_bump(change));
+    }
+
+    let files = &report.files;
+    let _ = writeln!(
+        md,
+        "\n**Files:** {} added, {} modified, {} deleted",
+        files.added.len(),
+        files.modified.len(),
+        files.deleted.len()
+    );
+
+    if !report.unresolved.is_empty() {
+        md.push_str(
+            "\n### ⚠️ Conflicts\n\nThese files contain conflict markers; resolve them on \
+             this branch and run `cyanprint resolve --continue`:\n\n",
+        );
+        for path in &report.unresolved {
+            let _ = writeln!(md, "- `{path}`");
+        }
+    }
+    if !report.file_conflicts.is_empty() {
+        md.push_str("\n### Files written by several templates\n\n");
+        for entry in &report.file_conflicts {
+            let _ = writeln!(md, "- `{}`: {}", entry.path, resolution(entry));
+        }
+    }
+    if !report.commands.is_empty() {
+        md.push_str("\n### Commands\n\n");
+        for run in &report.commands {
+            let status = match run.exit_code {
+                Some(0) => "✅".to_string(),
+                Some(code) => format!("❌ exit {code}"),
+                None => "❌ killed".to_string(),
+            };
+            let _ = writeln!(md, "- `{}` {status}", run.command);
+        }
+    }
+    md
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_executor::CommandRun;
+    use crate::report::FileChanges;
+    use git2::Repository;
+    use tempfile::TempDir;
+
+    fn change(template: &str, from: Option<i64>, to: i64) -> TemplateChange {
+        TemplateChange {
+            template: template.to_string(),
+            from,
+            to,
+        }
+    }
+
+    fn report(templates: Vec<TemplateChange>) -> RunReport {
+        RunReport {
+            templates,
+            ..RunReport::default()
+        }
+    }
+
+    fn repo_with_commit() -> (TempDir, Repository) {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        fs::write(dir.path().join("README.md"), "hi\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("README.md")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = git2::Signature::now("t", "t@example.com").unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+            .unwrap();
+        drop(tree);
+        (dir, repo)
+    }
+
+    fn head_branch(repo: &Repository) -> String {
+        repo.head().unwrap().shorthand().unwrap().to_string()
+    }
+
+    #[test]
+    fn commit_message_lists_bumps_and_conflicts() {
+        let one = commit_message(&report(vec![change("alice/web", Some(3), 5)]));
+        assert_eq!(one, "Update alice/web (v3 → v5)\n\n- alice/web: v3 → v5\n");
+
+        let mut many = report(vec![
+            change("alice/web", Some(3), 5),
+            change("bob/api", None, 1),
+        ]);
+        many.unresolved = vec!["src/main.rs".to_string()];
+        assert_eq!(
+            commit_message(&many),
+            "Update 2 templates\n\n\
+             - alice/web: v3 → v5\n\
+             - bob/api: new at v1\n\
+             \nConflicts left for `cyanprint resolve`:\n\
+             - src/main.rs\n"
+        );
+    }
+
+    #[test]
+    fn pull_request_description_summarises_the_run() {
+        let md = pull_request_description(
+            &RunReport {
+                files: FileChanges {
+                    added: vec!["a".to_string()],
+                    modified: vec!["b".to_string(), "c".to_string()],
+                    deleted: Vec::new(),
+                },
+                unresolved: vec!["b".to_string()],
+                commands: vec![CommandRun {
+                    command: "npm i".to_string(),
+                    exit_code: Some(0),
+                }],
+                ..report(vec![change("alice/web", Some(3), 5)])
+            },
+            "cyan/upgrade",
+            "main",
+        );
+        assert!(md.contains("Merges `cyan/upgrade` into `main`."));
+        assert!(md.contains("| `alice/web` | v3 → v5 |"), "got:\n{md}");
+        assert!(md.contains("1 added, 2 modified, 0 deleted"));
+        assert!(md.contains("- `b`\n"));
+        assert!(md.contains("- `npm i` ✅"));
+    }
+
+    #[test]
+    fn commits_the_update_on_a_new_branch() {
+        let (dir, repo) = repo_with_commit();
+        let base = head_branch(&repo);
+        let pr = dir.path().join("pr.md");
+
+        update_on_branch(dir.path(), "cyan/upgrade", Some(&pr), true, || {
+            fs::write(dir.path().join("README.md"), "hello\n").unwrap();
+            fs::create_dir_all(dir.path().join(".cyan_merge")).unwrap();
+            fs::write(dir.path().join(".cyan_merge/README.md"), "hi\n").unwrap();
+            Ok(CyanRunResult {
+                report: Some(report(vec![change("alice/web", Some(1), 2)])),
+                ..CyanRunResult::completed(Vec::new())
+            })
+        })
+        .unwrap();
+
+        assert_eq!(head_branch(&repo), "cyan/upgrade");
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.summary(), Some("Update alice/web (v1 → v2)"));
+        let tree = head.tree().unwrap();
+        assert!(tree.get_path(Path::new(".cyan_merge")).is_err());
+        assert!(tree.get_path(Path::new("pr.md")).is_err());
+        assert!(fs::read_to_string(&pr).unwrap().contains("alice/web"));
+        assert!(repo.find_branch(&base, git2::BranchType::Local).is_ok());
+    }
+
+    #[test]
+    fn abandons_the_branch_when_nothing_changed_or_it_failed() {
+        let (dir, repo) = repo_with_commit();
+        let base = head_branch(&repo);
+
+        update_on_branch(dir.path(), "cyan/noop", None, true, || {
+            Ok(CyanRunResult::completed(Vec::new()))
+        })
+        .unwrap();
+        assert_eq!(head_branch(&repo), base);
+        assert!(
+            repo.find_branch("cyan/noop", git2::BranchType::Local)
+                .is_err()
+        );
+
+        let err = update_on_branch(dir.path(), "cyan/fail", None, true, || {
+            Err(Box::new(std::io::Error::other("boom")) as Box<dyn Error + Send>)
+        })
+        .err()
+        .expect("the update on a branch fails");
+        assert_eq!(err.to_string(), "boom");
+        assert_eq!(head_branch(&repo), base);
+        assert!(
+            repo.find_branch("cyan/fail", git2::BranchType::Local)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn refuses_a_dirty_tree() {
+        let (dir, _repo) = repo_with_commit();
+        fs::write(dir.path().join("scratch.txt"), "x").unwrap();
+        let err = update_on_branch(dir.path(), "cyan/upgrade", None, true, || {
+            unreachable!("the update must not run")
+        })
+        .err()
+        .expect("the update on a branch fails");
+        assert!(err.to_string().contains("clean working tree"), "got: {err}");
+    }
+}
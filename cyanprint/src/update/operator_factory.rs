@@ -1,7 +1,9 @@
 use std::rc::Rc;
 
 use cyancoordinator::client::CyanCoordinatorClient;
-use cyancoordinator::fs::{DiskFileLoader, DiskFileWriter, GitLikeMerger, TarGzUnpacker};
+use cyancoordinator::fs::{
+    BinaryMergePolicy, DiskFileLoader, DiskFileWriter, GitLikeMerger, TarGzUnpacker,
+};
 use cyancoordinator::operations::TemplateOperator;
 use cyancoordinator::operations::composition::{CompositionOperator, DefaultDependencyResolver};
 use cyancoordinator::template::{DefaultTemplateExecutor, DefaultTemplateHistory};
@@ -29,6 +31,7 @@ impl OperatorFactory {
         registry_client: Rc<CyanRegistryClient>,
         debug: bool,
         cache_config: cyancoordinator::cache::CacheConfig,
+        binary_policy: BinaryMergePolicy,
         headless: bool,
     ) -> CompositionOperator {
         let unpacker = Box::new(TarGzUnpacker);
@@ -36,10 +39,10 @@ impl OperatorFactory {
         // Disable merger debug under headless so its `println!` debug output never
         // pollutes the single-JSON-on-stdout contract; interactive `--debug` is
         // unchanged. See [`merger_debug_enabled`].
-        let merger = Box::new(GitLikeMerger::new(
-            merger_debug_enabled(debug, headless),
-            50,
-        ));
+        let merger = Box::new(
+            GitLikeMerger::new(merger_debug_enabled(debug, headless), 50)
+                .with_binary_policy(binary_policy),
+        );
         let writer = Box::new(DiskFileWriter);
 
         let template_history = Box::new(DefaultTemplateHistory::new());
@@ -5,16 +5,22 @@ use std::rc::Rc;
 
 use cyancoordinator::client::CyanCoordinatorClient;
 use cyancoordinator::session::SessionIdGenerator;
-use cyancoordinator::state::{DefaultStateManager, StateReader, StateWriter};
+use cyancoordinator::state::{DefaultStateManager, StateReader, StateWriter, state_file};
 use cyanregistry::http::client::CyanRegistryClient;
 use inquire::Select;
 
 use super::operator_factory::OperatorFactory;
-use super::spec::{TemplateSpec, TemplateSpecManager, sort_specs};
+use super::spec::{
+    Reanswer, TemplateSpec, TemplateSpecManager, UpdateSelection, fill_missing_secrets, sort_specs,
+};
 use crate::command_executor::CommandExecutor;
 use crate::git::{GitError, get_modified_files, is_git_dirty};
 use crate::headless::CyanRunResult;
-use crate::run::{SessionCleanupGuard, batch_process, release_session};
+use crate::report::RunReport;
+use crate::run::{
+    SessionCleanupGuard, batch_plan, batch_process, commit_write, recover_interrupted_write,
+    release_session,
+};
 
 /// Error type for user-initiated abort
 #[derive(Debug)]
@@ -35,6 +41,9 @@ impl UpdateOrchestrator {
     /// Update all templates in a project to their latest versions with automatic composition detection
     /// Uses unified batch VFS processing: MAP -> LAYER -> MERGE+WRITE
     /// Returns all session IDs that were created and need to be cleaned up
+    ///
+    /// With `dry_run`, the pipeline stops after MERGE: the merged result is printed as a
+    /// unified diff against the working tree and nothing (files or state) is written.
     #[allow(unused_variables)]
     #[allow(clippy::too_many_arguments)]
     pub fn update_templates(
@@ -46,11 +55,16 @@ impl UpdateOrchestrator {
         interactive: bool,
         force: bool,
         cache_config: cyancoordinator::cache::CacheConfig,
+        binary_policy: cyancoordinator::fs::BinaryMergePolicy,
         headless: bool,
         headless_answers: std::collections::HashMap<
             String,
             cyanprompt::domain::models::answer::Answer,
         >,
+        dry_run: bool,
+        targets: &[String],
+        versions: &[(String, i64)],
+        reanswer: &Reanswer,
     ) -> Result<CyanRunResult, Box<dyn Error + Send>> {
         let target_dir = Path::new(&path);
 
@@ -61,8 +75,13 @@ impl UpdateOrchestrator {
         // interaction, non-JSON stdout). Headless always takes the auto-latest path.
         let interactive = interactive && !headless;
 
+        // Roll back an interrupted write first: its leftovers would otherwise be reported
+        // as uncommitted changes
+        recover_interrupted_write(target_dir, headless)?;
+
         // === GIT DIRTY CHECK STARTS HERE ===
-        if !force {
+        // A dry run never touches the working tree, so uncommitted changes are irrelevant.
+        if !force && !dry_run {
             match is_git_dirty(target_dir) {
                 Ok(true) => {
                     if headless {
@@ -121,11 +140,6 @@ impl UpdateOrchestrator {
                     eprintln!("ℹ️  Note: Not a git repository, skipping dirty check");
                     eprintln!();
                 }
-                Err(GitError::GitNotInstalled) => {
-                    // Git not installed - warn and continue
-                    eprintln!("⚠️  Warning: Git not found, skipping dirty check");
-                    eprintln!();
-                }
                 Err(e) => {
                     // Other git error - warn and continue
                     eprintln!(
@@ -135,7 +149,7 @@ impl UpdateOrchestrator {
                     eprintln!();
                 }
             }
-        } else {
+        } else if force {
             // Force mode - skip check but inform user
             eprintln!("ℹ️  Force mode enabled - skipping git dirty check");
             eprintln!();
@@ -149,6 +163,7 @@ impl UpdateOrchestrator {
             registry_client.clone(),
             debug,
             cache_config,
+            binary_policy,
             headless,
         );
 
@@ -156,17 +171,19 @@ impl UpdateOrchestrator {
         crate::hprogress!(
             headless,
             "🔍 PHASE 1: Reading template state from: {:?}",
-            target_dir.join(".cyan_state.yaml")
+            state_file(target_dir)
         );
-        let state_file_path = target_dir.join(".cyan_state.yaml");
+        let state_file_path = state_file(target_dir);
         let state_manager = DefaultStateManager::new();
-        let mut cyan_state = state_manager
+        let cyan_state = state_manager
             .load_state_file(&state_file_path)
             .map_err(|e| {
                 Box::new(std::io::Error::other(format!("Failed to load state: {e}")))
                     as Box<dyn Error + Send>
             })?;
 
+        crate::resolve::ensure_no_pending_merge(&cyan_state)?;
+
         if cyan_state.templates.is_empty() {
             crate::hprogress!(headless, "⚠️ No templates found in state file");
             return Ok(CyanRunResult::completed(Vec::new()));
@@ -185,22 +202,39 @@ impl UpdateOrchestrator {
 
         crate::hprogress!(headless, "📋 Found {} active templates", prev_specs.len());
 
-        // Build curr_specs for update (with version upgrades)
-        let mut curr_specs = manager.update(prev_specs.clone(), interactive)?;
+        // Build curr_specs for update (with version upgrades), limited to the named
+        // templates and held back by pins/constraints recorded in state
+        let selection = UpdateSelection::new(targets, versions, &cyan_state)?;
+        for key in selection.held_by_pin(&prev_specs) {
+            crate::hprogress!(headless, "📌 Skipping pinned template {key}");
+        }
+        let mut curr_specs = manager.update(prev_specs.clone(), interactive, &selection)?;
+        // `--reanswer`/`--reask`: the cleared questions are asked again, so those
+        // templates re-run even if their version stays put
+        let reanswered = reanswer.apply(&mut curr_specs, &selection)?;
+        for key in &reanswered {
+            crate::hprogress!(headless, "✏️  Re-asking questions of {key}");
+        }
 
         // Sort both lists by installation time for consistent LWW ordering
         sort_specs(&mut prev_specs);
         sort_specs(&mut curr_specs);
+        // Secrets state did not keep are supplied wherever their template runs, its prev
+        // run included
+        fill_missing_secrets(&mut prev_specs, &cyan_state, &headless_answers);
+        fill_missing_secrets(&mut curr_specs, &cyan_state, &headless_answers);
 
         // A spec is "upgraded" when its version changed vs the previous state (or it is
-        // new). Compute this BEFORE seeding so headless answers can be scoped to exactly
-        // those templates — the version comparison does not depend on answers.
+        // new), or its answers are being re-asked. Compute this BEFORE seeding so headless
+        // answers can be scoped to exactly those templates — neither test depends on the
+        // answers seeded below.
         let is_upgraded = |c: &TemplateSpec| {
-            prev_specs
-                .iter()
-                .find(|p| p.key() == c.key())
-                .map(|p| p.version != c.version)
-                .unwrap_or(true) // New template
+            reanswered.contains(&c.key())
+                || prev_specs
+                    .iter()
+                    .find(|p| p.key() == c.key())
+                    .map(|p| p.version != c.version)
+                    .unwrap_or(true) // New template
         };
 
         // Headless: seed supplied answers ONLY into the template(s) actually being
@@ -232,28 +266,40 @@ impl UpdateOrchestrator {
             upgraded.len()
         );
 
-        // Convert to references for batch_process
-        let upgraded_refs: Vec<&TemplateSpec> = upgraded.iter().collect();
-
-        // PHASE 2-4: BATCH PROCESS
-        let (session_ids, file_conflicts, commands, managed_by_template, need_input) =
-            batch_process(
+        if dry_run {
+            return Self::preview(
                 &prev_specs,
                 &curr_specs,
-                &upgraded_refs,
                 target_dir,
                 &registry_client,
                 &coord_client,
                 &mut composition_operator,
                 headless,
-            )?;
+            );
+        }
+
+        // Convert to references for batch_process
+        let upgraded_refs: Vec<&TemplateSpec> = upgraded.iter().collect();
+
+        // PHASE 2-4: BATCH PROCESS
+        let outcome = batch_process(
+            &prev_specs,
+            &curr_specs,
+            &upgraded_refs,
+            target_dir,
+            &registry_client,
+            &coord_client,
+            &mut composition_operator,
+            headless,
+        )?;
 
         // Headless: a question is pending — surface it without writing state/files. The
         // sessions go to the caller, which cleans them at the headless boundary.
-        if let Some(question) = need_input {
+        if let Some(question) = outcome.need_input {
             return Ok(CyanRunResult {
-                session_ids,
+                session_ids: outcome.session_ids,
                 need_input: Some(question),
+                report: None,
             });
         }
 
@@ -263,20 +309,37 @@ impl UpdateOrchestrator {
         // and leak the sessions until the coordinator's own timeout. Hand them to a cleanup
         // guard so every error path below releases them; `take()` disarms it on the happy
         // `done` return (where `finish_headless` then cleans normally).
-        let mut session_guard =
-            SessionCleanupGuard::new(|sid: &str| release_session(&coord_client, sid), session_ids);
+        let mut session_guard = SessionCleanupGuard::new(
+            |sid: &str| release_session(&coord_client, sid),
+            outcome.session_ids,
+        );
 
         // One-line cache summary (always printed when caching is enabled). (FR15)
         composition_operator.print_cache_summary();
 
         // Persist file conflicts to state file (always update to clear stale entries)
-        let conflicts_count = file_conflicts.len();
-        cyan_state.file_conflicts = file_conflicts;
+        // `batch_process` saved the upgraded templates' history straight to the state file;
+        // reload so this save builds on it rather than on the pre-update snapshot.
+        let mut cyan_state = state_manager
+            .load_state_file(&state_file_path)
+            .unwrap_or(cyan_state);
+        let conflicts_count = outcome.file_conflicts.len();
+        cyan_state.file_conflicts = outcome.file_conflicts;
         // Recompute the managed-files manifest wholesale from this run's active templates.
-        cyan_state.set_managed_files(&managed_by_template);
+        cyan_state.set_managed_files(&outcome.managed_by_template);
+        cyan_state.pending_merge = outcome.pending_merge;
         let managed_count = cyan_state.managed_files.len();
-        // A save failure here drops the still-armed `session_guard`, releasing the sessions.
+        // A save failure here drops the still-armed `session_guard`, releasing the sessions,
+        // and the uncommitted transaction, rolling the files and state back.
         state_manager.save_state_file(&cyan_state, &state_file_path)?;
+        commit_write(outcome.transaction)?;
+        let mut report = RunReport::for_run(
+            &prev_specs,
+            &upgraded_refs,
+            &outcome.diff,
+            &cyan_state,
+            composition_operator.cache_summary(),
+        );
         if conflicts_count > 0 {
             crate::hprogress!(
                 headless,
@@ -290,7 +353,24 @@ impl UpdateOrchestrator {
             );
         }
 
+        for path in &outcome.kept_local {
+            crate::hprogress!(
+                headless,
+                "✋ Kept {path}: you edited it and no template produces it"
+            );
+        }
+
+        // A conflicted merge holds the post-template commands back until it is resolved.
+        if let Some(pending) = &cyan_state.pending_merge {
+            crate::resolve::report_pending_merge(pending, headless);
+            return Ok(CyanRunResult {
+                report: Some(report),
+                ..CyanRunResult::completed(session_guard.take())
+            });
+        }
+
         // Execute commands if any were collected
+        let commands = outcome.commands;
         if !commands.is_empty() {
             crate::hprogress!(
                 headless,
@@ -323,10 +403,60 @@ impl UpdateOrchestrator {
                     exec_result.succeeded, exec_result.total, exec_result.failed, exec_result.total
                 ))));
             }
+            report.commands = exec_result.runs;
         }
 
         crate::hprogress!(headless, "✅ Batch update complete");
         // `done`: disarm the guard and hand the sessions to `finish_headless`.
+        Ok(CyanRunResult {
+            report: Some(report),
+            ..CyanRunResult::completed(session_guard.take())
+        })
+    }
+}
+
+impl UpdateOrchestrator {
+    /// Dry-run tail of [`Self::update_templates`]: plan the batch (MAP → LAYER → MERGE)
+    /// and print the merged result as a diff against the working tree instead of
+    /// writing it. State and post-template commands are untouched.
+    fn preview(
+        prev_specs: &[TemplateSpec],
+        curr_specs: &[TemplateSpec],
+        target_dir: &Path,
+        registry_client: &CyanRegistryClient,
+        coord_client: &CyanCoordinatorClient,
+        composition_operator: &mut cyancoordinator::operations::CompositionOperator,
+        headless: bool,
+    ) -> Result<CyanRunResult, Box<dyn Error + Send>> {
+        let plan = batch_plan(
+            prev_specs,
+            curr_specs,
+            target_dir,
+            registry_client,
+            coord_client,
+            composition_operator,
+            headless,
+        )?;
+        if let Some(question) = plan.need_input {
+            return Ok(CyanRunResult {
+                session_ids: plan.session_ids,
+                need_input: Some(question),
+                report: None,
+            });
+        }
+
+        // Rendering can still fail (e.g. a closed stdout pipe); keep the sessions guarded
+        // until they are handed to the caller.
+        let mut session_guard = SessionCleanupGuard::new(
+            |sid: &str| release_session(coord_client, sid),
+            plan.session_ids.clone(),
+        );
+
+        println!();
+        let stdout = std::io::stdout();
+        crate::diff::write_plan_diff(&plan, &mut stdout.lock())
+            .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
+
         Ok(CyanRunResult::completed(session_guard.take()))
     }
 }
@@ -335,9 +465,9 @@ impl UpdateOrchestrator {
 fn format_git_error(err: &GitError) -> String {
     match err {
         GitError::NotAGitRepository => "Not a git repository".to_string(),
-        GitError::GitNotInstalled => "Git not installed".to_string(),
         GitError::CommandFailed(msg) => format!("Git command failed: {msg}"),
         GitError::IoError(e) => format!("IO error: {e}"),
+        GitError::Git(e) => format!("Git error: {}", e.message()),
     }
 }
 
@@ -351,10 +481,6 @@ mod tests {
             format_git_error(&GitError::NotAGitRepository),
             "Not a git repository"
         );
-        assert_eq!(
-            format_git_error(&GitError::GitNotInstalled),
-            "Git not installed"
-        );
         assert_eq!(
             format_git_error(&GitError::CommandFailed("test error".to_string())),
             "Git command failed: test error"
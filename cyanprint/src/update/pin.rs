@@ -0,0 +1,84 @@
+use std::error::Error;
+use std::path::Path;
+
+use cyancoordinator::state::models::{CyanState, VersionConstraint};
+use cyancoordinator::state::{DefaultStateManager, StateReader, StateWriter, state_file};
+
+use super::utils::SelectionError;
+
+/// Pin `template` (no constraint), cap it with `constraint`, or clear both.
+pub fn set_pin(
+    state: &mut CyanState,
+    template: &str,
+    constraint: Option<VersionConstraint>,
+    clear: bool,
+) -> Result<(), Box<dyn Error + Send>> {
+    let ts = state
+        .templates
+        .get_mut(template)
+        .filter(|ts| ts.active)
+        .ok_or_else(|| {
+            Box::new(SelectionError(format!(
+                "Template {template} is not installed in this project"
+            ))) as Box<dyn Error + Send>
+        })?;
+    ts.pinned = !clear && constraint.is_none();
+    ts.constraint = if clear { None } else { constraint };
+    Ok(())
+}
+
+/// `cyanprint pin`: record a pin or version constraint in the project's state file.
+pub fn cyan_pin(
+    path: &str,
+    template: &str,
+    constraint: Option<VersionConstraint>,
+    clear: bool,
+) -> Result<(), Box<dyn Error + Send>> {
+    let state_file = state_file(Path::new(path));
+    let manager = DefaultStateManager::new();
+    let mut state = manager.load_state_file(&state_file)?;
+    set_pin(&mut state, template, constraint.clone(), clear)?;
+    manager.save_state_file(&state, &state_file)?;
+
+    match (clear, constraint) {
+        (true, _) => println!("📌 Unpinned {template}"),
+        (false, Some(c)) => println!("📌 {template} will be updated within {c}"),
+        (false, None) => println!("📌 Pinned {template} at its current version"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cyancoordinator::state::models::TemplateState;
+
+    #[test]
+    fn set_pin_pins_caps_and_clears() {
+        let mut state = CyanState::default();
+        state.templates.insert(
+            "alice/web".to_string(),
+            TemplateState {
+                active: true,
+                history: Vec::new(),
+                files: Vec::new(),
+                pinned: false,
+                constraint: None,
+            },
+        );
+
+        set_pin(&mut state, "alice/web", None, false).unwrap();
+        assert!(state.templates["alice/web"].pinned);
+
+        let cap: VersionConstraint = "<=14".parse().unwrap();
+        set_pin(&mut state, "alice/web", Some(cap.clone()), false).unwrap();
+        assert!(!state.templates["alice/web"].pinned);
+        assert_eq!(state.templates["alice/web"].constraint, Some(cap));
+
+        set_pin(&mut state, "alice/web", None, true).unwrap();
+        assert!(!state.templates["alice/web"].pinned);
+        assert!(state.templates["alice/web"].constraint.is_none());
+
+        assert!(set_pin(&mut state, "bob/api", None, false).is_err());
+    }
+}
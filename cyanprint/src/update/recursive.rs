@@ -0,0 +1,690 @@
+//! `cyanprint update --recursive`: update every project below a directory.
+//!
+//! Projects are found by their state files. Each is planned up front (which templates
+//! move, from and to which version); projects with nothing to upgrade are reported
+//! without being run, and the rest are grouped by their exact upgrade set so identical
+//! upgrades run back to back and hit the same cache entries. The working tree is checked
+//! for uncommitted changes once, before anything is written. Updates run headless on a
+//! bounded pool of workers, never two nested projects at once, and the per-project
+//! outcomes are collected into one report.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::error::Error;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::{Condvar, Mutex};
+
+use cyancoordinator::cache::CacheConfig;
+use cyancoordinator::client::{CyanCoordinatorClient, new_client};
+use cyancoordinator::fs::BinaryMergePolicy;
+use cyancoordinator::session::DefaultSessionIdGenerator;
+use cyancoordinator::state::{
+    CyanState, DefaultStateManager, StateLocation, StateReader, state_file,
+};
+use cyanprompt::domain::models::question::QuestionTrait;
+use cyanregistry::http::client::CyanRegistryClient;
+use serde::Serialize;
+
+use super::spec::{TemplateSpecManager, UpdateSelection};
+use crate::git::is_git_dirty;
+use crate::report::RunReport;
+
+/// One template moving between versions.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct Upgrade {
+    pub template: String,
+    pub from: i64,
+    pub to: i64,
+}
+
+/// How a project's update ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectStatus {
+    /// Nothing to upgrade, or none of the named templates installed; the project was
+    /// not run.
+    UpToDate,
+    Updated,
+    /// Updated, with conflict markers left for `cyanprint resolve`.
+    Conflicts,
+    /// A newly asked question has no recorded answer; update the project on its own.
+    NeedInput,
+    Failed,
+}
+
+/// Outcome of one project.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectOutcome {
+    /// Project directory, relative to the root that was walked.
+    pub path: String,
+    pub status: ProjectStatus,
+    pub upgrades: Vec<Upgrade>,
+    /// Paths written with conflict markers.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<String>,
+    /// What the update changed, as `update --output json` reports it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub report: Option<RunReport>,
+    /// Id of the unanswered question, for [`ProjectStatus::NeedInput`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub question: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ProjectOutcome {
+    fn new(path: String, status: ProjectStatus, upgrades: Vec<Upgrade>) -> Self {
+        Self {
+            path,
+            status,
+            upgrades,
+            conflicts: Vec::new(),
+            report: None,
+            question: None,
+            error: None,
+        }
+    }
+
+    fn failed(path: String, upgrades: Vec<Upgrade>, error: &dyn Error) -> Self {
+        Self {
+            error: Some(error.to_string()),
+            ..Self::new(path, ProjectStatus::Failed, upgrades)
+        }
+    }
+}
+
+/// Projects that made the same template upgrade.
+#[derive(Debug, Clone, Serialize)]
+pub struct UpgradeGroup {
+    #[serde(flatten)]
+    pub upgrade: Upgrade,
+    pub projects: Vec<String>,
+}
+
+/// Consolidated result of a recursive update.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecursiveReport {
+    /// Every project found, sorted by path.
+    pub projects: Vec<ProjectOutcome>,
+    /// Upgrades attempted, each with the projects it was applied to.
+    pub upgrades: Vec<UpgradeGroup>,
+}
+
+impl RecursiveReport {
+    fn new(mut projects: Vec<ProjectOutcome>) -> Self {
+        projects.sort_by(|a, b| a.path.cmp(&b.path));
+        let mut groups: BTreeMap<&Upgrade, Vec<String>> = BTreeMap::new();
+        for project in &projects {
+            for upgrade in &project.upgrades {
+                groups
+                    .entry(upgrade)
+                    .or_default()
+                    .push(project.path.clone());
+            }
+        }
+        let upgrades = groups
+            .into_iter()
+            .map(|(upgrade, projects)| UpgradeGroup {
+                upgrade: upgrade.clone(),
+                projects,
+            })
+            .collect();
+        Self { projects, upgrades }
+    }
+
+    pub fn count(&self, status: ProjectStatus) -> usize {
+        self.projects.iter().filter(|p| p.status == status).count()
+    }
+
+    /// Whether any project failed or stopped for input.
+    pub fn has_failures(&self) -> bool {
+        self.count(ProjectStatus::Failed) + self.count(ProjectStatus::NeedInput) > 0
+    }
+}
+
+/// Settings shared by every project's update.
+#[derive(Debug, Clone)]
+pub struct RecursiveOptions {
+    pub registry_endpoint: String,
+    pub coordinator_endpoint: String,
+    pub debug: bool,
+    pub force: bool,
+    pub cache_config: CacheConfig,
+    pub binary_policy: BinaryMergePolicy,
+    /// Maximum number of projects updated at once.
+    pub jobs: usize,
+    pub targets: Vec<String>,
+    pub versions: Vec<(String, i64)>,
+}
+
+/// Project directories below `root` holding a state file at `location`, sorted.
+/// `.gitignore`d directories are skipped.
+pub fn discover_projects(root: &Path, location: &StateLocation) -> Vec<PathBuf> {
+    let depth = location.file().components().count();
+    let mut projects: Vec<PathBuf> = ignore::WalkBuilder::new(root)
+        .hidden(false)
+        .filter_entry(|e| e.file_name() != ".git")
+        .build()
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+        .filter_map(|e| {
+            let rel = e.path().strip_prefix(root).ok()?;
+            location
+                .is_any_state_file(rel)
+                .then(|| e.path().ancestors().nth(depth).map(Path::to_path_buf))
+                .flatten()
+        })
+        .collect();
+    projects.sort();
+    projects.dedup();
+    projects
+}
+
+/// The templates named on the command line (after `--` and with `--to`) that one project
+/// installs.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct ProjectSelection {
+    targets: Vec<String>,
+    versions: Vec<(String, i64)>,
+}
+
+/// Narrow the named templates to those active in `state`. `None` when templates were
+/// named but the project installs none of them: it has nothing to update.
+fn narrow_selection(
+    targets: &[String],
+    versions: &[(String, i64)],
+    state: &CyanState,
+) -> Option<ProjectSelection> {
+    let installed = |key: &String| state.templates.get(key).is_some_and(|ts| ts.active);
+    let selection = ProjectSelection {
+        targets: targets.iter().filter(|k| installed(k)).cloned().collect(),
+        versions: versions
+            .iter()
+            .filter(|(k, _)| installed(k))
+            .cloned()
+            .collect(),
+    };
+    let named = !targets.is_empty() || !versions.is_empty();
+    let kept = !selection.targets.is_empty() || !selection.versions.is_empty();
+    (!named || kept).then_some(selection)
+}
+
+/// A project's planned upgrades and the named templates it installs.
+type ProjectPlan = (Vec<Upgrade>, ProjectSelection);
+
+/// The upgrades `update` would make in the project at `dir`, and the named templates
+/// it installs. `None` when it installs none of them.
+fn plan_project(
+    dir: &Path,
+    manager: &TemplateSpecManager,
+    targets: &[String],
+    versions: &[(String, i64)],
+) -> Result<Option<ProjectPlan>, Box<dyn Error + Send>> {
+    crate::run::recover_interrupted_write(dir, true)?;
+    let state = DefaultStateManager::new().load_state_file(&state_file(dir))?;
+    crate::resolve::ensure_no_pending_merge(&state)?;
+
+    let Some(narrowed) = narrow_selection(targets, versions, &state) else {
+        return Ok(None);
+    };
+    let prev = manager.get(&state);
+    let selection = UpdateSelection::new(&narrowed.targets, &narrowed.versions, &state)?;
+    let curr = manager.update(prev.clone(), false, &selection)?;
+    let mut upgrades: Vec<Upgrade> = prev
+        .iter()
+        .zip(&curr)
+        .filter(|(p, c)| p.version != c.version)
+        .map(|(p, c)| Upgrade {
+            template: p.key(),
+            from: p.version,
+            to: c.version,
+        })
+        .collect();
+    upgrades.sort();
+    Ok(Some((upgrades, narrowed)))
+}
+
+/// Update one planned project headless and read back what it left behind.
+fn run_project(
+    dir: &Path,
+    rel: String,
+    upgrades: Vec<Upgrade>,
+    selection: &ProjectSelection,
+    registry: &Rc<CyanRegistryClient>,
+    opts: &RecursiveOptions,
+) -> ProjectOutcome {
+    let coord_client = CyanCoordinatorClient::new(opts.coordinator_endpoint.clone());
+    let result = super::cyan_update(
+        Box::new(DefaultSessionIdGenerator),
+        dir.to_string_lossy().into_owned(),
+        coord_client.clone(),
+        Rc::clone(registry),
+        opts.debug,
+        false,
+        // The tree was checked once, up front: the projects written before this one
+        // would otherwise make every later check fail in a shared repository
+        true,
+        opts.cache_config.clone(),
+        opts.binary_policy,
+        true,
+        HashMap::new(),
+        false,
+        &selection.targets,
+        &selection.versions,
+        &super::Reanswer::Keep,
+    );
+    let result = match result {
+        Ok(result) => result,
+        Err(e) => return ProjectOutcome::failed(rel, upgrades, e.as_ref()),
+    };
+    for sid in result.session_ids {
+        let _ = coord_client.clean(sid);
+    }
+    if let Some(question) = result.need_input {
+        return ProjectOutcome {
+            question: Some(question.id()),
+            ..ProjectOutcome::new(rel, ProjectStatus::NeedInput, upgrades)
+        };
+    }
+
+    let conflicts = result
+        .report
+        .as_ref()
+        .map(|r| r.unresolved.clone())
+        .unwrap_or_default();
+    let status = if conflicts.is_empty() {
+        ProjectStatus::Updated
+    } else {
+        ProjectStatus::Conflicts
+    };
+    ProjectOutcome {
+        conflicts,
+        report: result.report,
+        ..ProjectOutcome::new(rel, status, upgrades)
+    }
+}
+
+/// Order planned projects so that each distinct upgrade set is started once before any
+/// repeat of it: the first run of a set fills the cache the repeats then read from.
+fn schedule<T>(planned: Vec<(Vec<Upgrade>, T)>) -> VecDeque<(Vec<Upgrade>, T)> {
+    let mut groups: BTreeMap<Vec<Upgrade>, Vec<T>> = BTreeMap::new();
+    for (upgrades, item) in planned {
+        groups.entry(upgrades).or_default().push(item);
+    }
+    let mut leaders = VecDeque::new();
+    let mut repeats = VecDeque::new();
+    for (upgrades, items) in groups {
+        let mut items = items.into_iter();
+        if let Some(first) = items.next() {
+            leaders.push_back((upgrades.clone(), first));
+        }
+        repeats.extend(items.map(|item| (upgrades.clone(), item)));
+    }
+    leaders.extend(repeats);
+    leaders
+}
+
+/// Take the first queued project that neither contains nor is contained in a running
+/// one: a project's loader reads its whole tree, so it must not run while a project
+/// nested in it is being written, and vice versa.
+fn next_runnable<T>(
+    queue: &mut VecDeque<(Vec<Upgrade>, (PathBuf, T))>,
+    running: &[PathBuf],
+) -> Option<(Vec<Upgrade>, (PathBuf, T))> {
+    let overlaps = |dir: &Path| {
+        running
+            .iter()
+            .any(|r| r.starts_with(dir) || dir.starts_with(r))
+    };
+    let index = queue.iter().position(|(_, (dir, _))| !overlaps(dir))?;
+    queue.remove(index)
+}
+
+/// The report name and selection of a project waiting in the [`WorkQueue`].
+type QueuedProject = (String, ProjectSelection);
+
+/// Projects waiting to run and the directories of those running.
+struct WorkQueue {
+    pending: VecDeque<(Vec<Upgrade>, (PathBuf, QueuedProject))>,
+    running: Vec<PathBuf>,
+}
+
+/// Update every project below `root`.
+pub fn cyan_update_recursive(
+    root: &Path,
+    registry: Rc<CyanRegistryClient>,
+    opts: &RecursiveOptions,
+) -> Result<RecursiveReport, Box<dyn Error + Send>> {
+    let projects = discover_projects(root, StateLocation::current());
+    if projects.is_empty() {
+        return Err(Box::new(std::io::Error::other(format!(
+            "No projects with a state file found under {}",
+            root.display()
+        ))));
+    }
+    eprintln!("🔍 Found {} projects", projects.len());
+
+    // Roll back interrupted writes first: their leftovers would otherwise be reported as
+    // uncommitted changes. A project that cannot be recovered fails again when planned.
+    for dir in &projects {
+        let _ = crate::run::recover_interrupted_write(dir, true);
+    }
+    if !opts.force && matches!(is_git_dirty(root), Ok(true)) {
+        return Err(Box::new(std::io::Error::other(
+            "working directory has uncommitted changes; commit/stash them or re-run with --force",
+        )));
+    }
+
+    let relative = |dir: &Path| match dir.strip_prefix(root) {
+        Ok(rel) if rel.as_os_str().is_empty() => ".".to_string(),
+        Ok(rel) => rel.to_string_lossy().into_owned(),
+        Err(_) => dir.to_string_lossy().into_owned(),
+    };
+
+    let manager = TemplateSpecManager::new(registry);
+    let mut outcomes = Vec::new();
+    let mut planned = Vec::new();
+    for dir in projects {
+        let rel = relative(&dir);
+        match plan_project(&dir, &manager, &opts.targets, &opts.versions) {
+            Ok(None) => outcomes.push(ProjectOutcome::new(
+                rel,
+                ProjectStatus::UpToDate,
+                Vec::new(),
+            )),
+            Ok(Some((upgrades, _))) if upgrades.is_empty() => {
+                outcomes.push(ProjectOutcome::new(rel, ProjectStatus::UpToDate, upgrades))
+            }
+            Ok(Some((upgrades, selection))) => planned.push((upgrades, (dir, (rel, selection)))),
+            Err(e) => outcomes.push(ProjectOutcome::failed(rel, Vec::new(), e.as_ref())),
+        }
+    }
+
+    let pending = schedule(planned);
+    let workers = opts.jobs.max(1).min(pending.len());
+    eprintln!(
+        "🚀 Updating {} projects, {workers} at a time",
+        pending.len()
+    );
+    let queue = Mutex::new(WorkQueue {
+        pending,
+        running: Vec::new(),
+    });
+    // Signalled whenever a project finishes, which may unblock one nested with it
+    let finished = Condvar::new();
+    let done = Mutex::new(outcomes);
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                // Clients are `Rc`-based, so every worker builds its own
+                let registry = new_client().map(|http| {
+                    Rc::new(CyanRegistryClient {
+                        endpoint: opts.registry_endpoint.clone(),
+                        version: "1.0".to_string(),
+                        client: Rc::new(http),
+                    })
+                });
+                loop {
+                    let (upgrades, (dir, (rel, selection))) = {
+                        let mut work = queue.lock().unwrap();
+                        loop {
+                            if work.pending.is_empty() {
+                                return;
+                            }
+                            let WorkQueue { pending, running } = &mut *work;
+                            if let Some((upgrades, (dir, planned))) =
+                                next_runnable(pending, running)
+                            {
+                                running.push(dir.clone());
+                                break (upgrades, (dir, planned));
+                            }
+                            work = finished.wait(work).unwrap();
+                        }
+                    };
+                    let outcome = match &registry {
+                        Ok(registry) => {
+                            run_project(&dir, rel, upgrades, &selection, registry, opts)
+                        }
+                        Err(e) => ProjectOutcome::failed(rel, upgrades, e.as_ref()),
+                    };
+                    eprintln!("  {}: {}", outcome.path, status_label(outcome.status));
+                    done.lock().unwrap().push(outcome);
+                    queue.lock().unwrap().running.retain(|r| *r != dir);
+                    finished.notify_all();
+                }
+            });
+        }
+    });
+
+    Ok(RecursiveReport::new(done.into_inner().unwrap()))
+}
+
+fn status_label(status: ProjectStatus) -> &'static str {
+    match status {
+        ProjectStatus::UpToDate => "up to date",
+        ProjectStatus::Updated => "updated",
+        ProjectStatus::Conflicts => "updated with conflicts",
+        ProjectStatus::NeedInput => "needs input",
+        ProjectStatus::Failed => "failed",
+    }
+}
+
+/// Human-readable summary of `report`.
+pub fn write_recursive_report<W: Write>(
+    report: &RecursiveReport,
+    w: &mut W,
+) -> std::io::Result<()> {
+    writeln!(
+        w,
+        "{} projects: {} updated, {} with conflicts, {} up to date, {} need input, {} failed",
+        report.projects.len(),
+        report.count(ProjectStatus::Updated),
+        report.count(ProjectStatus::Conflicts),
+        report.count(ProjectStatus::UpToDate),
+        report.count(ProjectStatus::NeedInput),
+        report.count(ProjectStatus::Failed),
+    )?;
+    if !report.upgrades.is_empty() {
+        writeln!(w, "\nUpgrades:")?;
+        for group in &report.upgrades {
+            let Upgrade { template, from, to } = &group.upgrade;
+            writeln!(
+                w,
+                "  {template} v{from} → v{to}: {}",
+                group.projects.join(", ")
+            )?;
+        }
+    }
+    let attention: Vec<&ProjectOutcome> = report
+        .projects
+        .iter()
+        .filter(|p| !matches!(p.status, ProjectStatus::Updated | ProjectStatus::UpToDate))
+        .collect();
+    if !attention.is_empty() {
+        writeln!(w, "\nNeeds attention:")?;
+        for project in attention {
+            let detail = match project.status {
+                ProjectStatus::Conflicts => project.conflicts.join(", "),
+                ProjectStatus::NeedInput => format!(
+                    "no answer for '{}'",
+                    project.question.as_deref().unwrap_or_default()
+                ),
+                _ => project.error.clone().unwrap_or_default(),
+            };
+            writeln!(
+                w,
+                "  {} ({}): {detail}",
+                project.path,
+                status_label(project.status)
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn upgrade(template: &str, from: i64, to: i64) -> Upgrade {
+        Upgrade {
+            template: template.to_string(),
+            from,
+            to,
+        }
+    }
+
+    #[test]
+    fn discovers_nested_projects() {
+        let root = TempDir::new().unwrap();
+        let touch = |rel: &str| {
+            let path = root.path().join(rel);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        };
+        touch(".cyan_state.yaml");
+        touch("apps/web/.cyan_state.yaml");
+        touch("apps/web/.cyan_state.json");
+        touch("libs/core/.cyan_state.toml");
+        touch("libs/core/src/lib.rs");
+        touch(".git/.cyan_state.yaml");
+
+        let found = discover_projects(root.path(), &StateLocation::default());
+        let found: Vec<&Path> = found
+            .iter()
+            .map(|p| p.strip_prefix(root.path()).unwrap())
+            .collect();
+        assert_eq!(
+            found,
+            vec![Path::new(""), Path::new("apps/web"), Path::new("libs/core")]
+        );
+
+        let custom = StateLocation::new(Some(".config/cyan/state.yaml".into()), None);
+        touch("svc/.config/cyan/state.yaml");
+        let found = discover_projects(root.path(), &custom);
+        assert_eq!(found, vec![root.path().join("svc")]);
+    }
+
+    #[test]
+    fn schedules_one_of_each_upgrade_set_first() {
+        let a = vec![upgrade("alice/web", 1, 2)];
+        let b = vec![upgrade("alice/web", 1, 2), upgrade("bob/api", 3, 4)];
+        let order: Vec<&str> = schedule(vec![
+            (a.clone(), "p1"),
+            (a.clone(), "p2"),
+            (b.clone(), "p3"),
+            (a, "p4"),
+            (b, "p5"),
+        ])
+        .into_iter()
+        .map(|(_, p)| p)
+        .collect();
+        assert_eq!(order, vec!["p1", "p3", "p2", "p4", "p5"]);
+    }
+
+    #[test]
+    fn nested_projects_never_run_together() {
+        let web = vec![upgrade("alice/web", 1, 2)];
+        let mut queue: VecDeque<_> = ["apps", "apps/web/api", "libs", "apps-old"]
+            .into_iter()
+            .map(|p| (web.clone(), (PathBuf::from(p), ())))
+            .collect();
+        let running = vec![PathBuf::from("apps/web")];
+        let taken: Vec<PathBuf> = std::iter::from_fn(|| next_runnable(&mut queue, &running))
+            .map(|(_, (dir, _))| dir)
+            .collect();
+        // `apps-old` only shares a name prefix with `apps`
+        assert_eq!(
+            taken,
+            vec![PathBuf::from("libs"), PathBuf::from("apps-old")]
+        );
+        assert_eq!(
+            queue.len(),
+            2,
+            "the parent and the child of the running project wait"
+        );
+    }
+
+    #[test]
+    fn targets_narrow_to_the_templates_each_project_installs() {
+        let root = TempDir::new().unwrap();
+        let write_state = |rel: &str, key: &str| {
+            let dir = root.path().join(rel);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(
+                dir.join(".cyan_state.yaml"),
+                format!("{key}:\n  active: true\n  history: []\n"),
+            )
+            .unwrap();
+            DefaultStateManager::new()
+                .load_state_file(&state_file(&dir))
+                .unwrap()
+        };
+        let web = write_state("web", "alice/web");
+        let api = write_state("api", "bob/api");
+        let targets = vec!["alice/web".to_string()];
+        let versions = vec![("alice/web".to_string(), 3)];
+
+        assert_eq!(
+            narrow_selection(&targets, &versions, &web),
+            Some(ProjectSelection {
+                targets: targets.clone(),
+                versions: versions.clone(),
+            })
+        );
+        assert_eq!(narrow_selection(&targets, &versions, &api), None);
+        assert_eq!(
+            narrow_selection(&[], &[], &api),
+            Some(ProjectSelection::default()),
+            "naming nothing selects everything"
+        );
+
+        // The project without the target is up to date before the registry is consulted
+        let registry = Rc::new(CyanRegistryClient {
+            endpoint: "http://127.0.0.1:9".to_string(),
+            version: "1.0".to_string(),
+            client: Rc::new(new_client().unwrap()),
+        });
+        let manager = TemplateSpecManager::new(registry);
+        let planned =
+            plan_project(&root.path().join("api"), &manager, &targets, &versions).unwrap();
+        assert!(planned.is_none());
+    }
+
+    #[test]
+    fn report_groups_upgrades_and_summarises() {
+        let web = upgrade("alice/web", 1, 2);
+        let mut conflicted =
+            ProjectOutcome::new("b".to_string(), ProjectStatus::Conflicts, vec![web.clone()]);
+        conflicted.conflicts = vec!["src/main.rs".to_string()];
+        let report = RecursiveReport::new(vec![
+            conflicted,
+            ProjectOutcome::new("a".to_string(), ProjectStatus::Updated, vec![web.clone()]),
+            ProjectOutcome::new("c".to_string(), ProjectStatus::UpToDate, Vec::new()),
+            ProjectOutcome::failed("d".to_string(), Vec::new(), &std::io::Error::other("boom")),
+        ]);
+
+        assert_eq!(report.upgrades.len(), 1);
+        assert_eq!(report.upgrades[0].projects, vec!["a", "b"]);
+        assert!(report.has_failures());
+
+        let mut out = Vec::new();
+        write_recursive_report(&report, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with(
+            "4 projects: 1 updated, 1 with conflicts, 1 up to date, 0 need input, 1 failed\n"
+        ));
+        assert!(text.contains("  alice/web v1 → v2: a, b\n"), "got:\n{text}");
+        assert!(text.contains("  b (updated with conflicts): src/main.rs\n"));
+        assert!(text.contains("  d (failed): boom\n"));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["upgrades"][0]["template"], "alice/web");
+        assert_eq!(json["projects"][1]["status"], "conflicts");
+        assert_eq!(json["projects"][1]["conflicts"][0], "src/main.rs");
+        assert!(json["projects"][0].get("error").is_none());
+    }
+}
@@ -0,0 +1,125 @@
+use std::error::Error;
+use std::path::Path;
+use std::rc::Rc;
+
+use cyancoordinator::client::CyanCoordinatorClient;
+use cyancoordinator::session::SessionIdGenerator;
+use cyancoordinator::state::models::CyanState;
+use cyanregistry::http::client::CyanRegistryClient;
+
+use super::rewrite::{load_state_for_rewrite, rematerialise, report_kept, supply_missing_secrets};
+use super::spec::{TemplateSpec, TemplateSpecManager, sort_specs};
+use super::utils::SelectionError;
+use crate::headless::CyanRunResult;
+
+/// Fail unless `template` is active in `state`.
+fn ensure_active(state: &CyanState, template: &str) -> Result<(), Box<dyn Error + Send>> {
+    if state.templates.get(template).is_some_and(|ts| ts.active) {
+        Ok(())
+    } else {
+        Err(Box::new(SelectionError(format!(
+            "Template {template} is not installed in this project"
+        ))))
+    }
+}
+
+/// Mark `template` inactive, keeping its history so it can be added back later.
+pub fn deactivate(state: &mut CyanState, template: &str) {
+    if let Some(ts) = state.templates.get_mut(template) {
+        ts.active = false;
+    }
+}
+
+/// Every active template except `template`, in layering order.
+fn remaining_specs(prev_specs: &[TemplateSpec], template: &str) -> Vec<TemplateSpec> {
+    let mut specs: Vec<TemplateSpec> = prev_specs
+        .iter()
+        .filter(|s| s.key() != template)
+        .cloned()
+        .collect();
+    sort_specs(&mut specs);
+    specs
+}
+
+/// Remove a template from the project: re-layer the remaining templates without it,
+/// delete the files only it produced (edited ones are kept and reported) and mark it
+/// inactive in state.
+#[allow(clippy::too_many_arguments)]
+pub fn cyan_remove(
+    session_id_generator: Box<dyn SessionIdGenerator>,
+    path: String,
+    template: String,
+    coord_client: CyanCoordinatorClient,
+    registry_client: Rc<CyanRegistryClient>,
+    debug: bool,
+    force: bool,
+    cache_config: cyancoordinator::cache::CacheConfig,
+    binary_policy: cyancoordinator::fs::BinaryMergePolicy,
+) -> Result<CyanRunResult, Box<dyn Error + Send>> {
+    let target_dir = Path::new(&path);
+    let cyan_state = load_state_for_rewrite(target_dir, force)?;
+    ensure_active(&cyan_state, &template)?;
+    println!("🗑️ Removing {template}");
+
+    let manager = TemplateSpecManager::new(Rc::clone(&registry_client));
+    let mut prev_specs = manager.get(&cyan_state);
+    sort_specs(&mut prev_specs);
+    let mut curr_specs = remaining_specs(&prev_specs, &template);
+    supply_missing_secrets(&cyan_state, &mut prev_specs, &mut curr_specs)?;
+
+    let (result, kept) = rematerialise(
+        session_id_generator,
+        target_dir,
+        &coord_client,
+        registry_client,
+        debug,
+        cache_config,
+        binary_policy,
+        &prev_specs,
+        &curr_specs,
+        |state| deactivate(state, &template),
+    )?;
+    report_kept(&kept);
+    println!("✅ Removed {template}");
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cyancoordinator::state::models::{ManagedFile, TemplateState};
+
+    #[test]
+    fn deactivate_keeps_history() {
+        let mut state = CyanState::default();
+        state.templates.insert(
+            "alice/web".to_string(),
+            TemplateState {
+                active: true,
+                history: Vec::new(),
+                files: vec![ManagedFile::new("a.txt".to_string(), b"a")],
+                pinned: false,
+                constraint: None,
+            },
+        );
+        assert!(ensure_active(&state, "alice/web").is_ok());
+        assert!(ensure_active(&state, "bob/api").is_err());
+
+        deactivate(&mut state, "alice/web");
+        assert!(!state.templates["alice/web"].active);
+        assert!(ensure_active(&state, "alice/web").is_err());
+    }
+
+    #[test]
+    fn remaining_specs_drop_only_the_removed_template() {
+        let prev = vec![
+            TemplateSpec::new_template("alice".into(), "web".into(), 2),
+            TemplateSpec::new_template("bob".into(), "api".into(), 4),
+        ];
+        let keys: Vec<String> = remaining_specs(&prev, "alice/web")
+            .iter()
+            .map(TemplateSpec::key)
+            .collect();
+        assert_eq!(keys, vec!["bob/api".to_string()]);
+    }
+}
@@ -0,0 +1,181 @@
+//! Helpers shared by the commands that rewrite a project's templates in place
+//! (`undo`, `remove`): loading the state safely and re-running the batch pipeline
+//! between two template sets without recording new history. Secrets state omitted are
+//! asked for once, not once per run of their template.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+use std::rc::Rc;
+
+use cyancoordinator::client::CyanCoordinatorClient;
+use cyancoordinator::session::SessionIdGenerator;
+use cyancoordinator::state::models::CyanState;
+use cyancoordinator::state::{DefaultStateManager, StateReader, StateWriter, state_file};
+use cyanprompt::domain::models::answer::Answer;
+use cyanregistry::http::client::CyanRegistryClient;
+
+use super::operator_factory::OperatorFactory;
+use super::spec::{TemplateSpec, fill_missing_secrets};
+use crate::command_executor::CommandExecutor;
+use crate::git::is_git_dirty;
+use crate::headless::CyanRunResult;
+use crate::run::{
+    SessionCleanupGuard, batch_process, commit_write, recover_interrupted_write, release_session,
+};
+
+fn other(msg: String) -> Box<dyn Error + Send> {
+    Box::new(std::io::Error::other(msg))
+}
+
+/// Roll back any interrupted write, refuse to rewrite a dirty working tree (unless
+/// forced) and load the state, refusing an unresolved merge.
+pub(super) fn load_state_for_rewrite(
+    target_dir: &Path,
+    force: bool,
+) -> Result<CyanState, Box<dyn Error + Send>> {
+    recover_interrupted_write(target_dir, false)?;
+    if !force && matches!(is_git_dirty(target_dir), Ok(true)) {
+        return Err(other(
+            "working directory has uncommitted changes; commit/stash them or re-run with --force"
+                .to_string(),
+        ));
+    }
+
+    let cyan_state = DefaultStateManager::new()
+        .load_state_file(&state_file(target_dir))
+        .map_err(|e| other(format!("Failed to load state: {e}")))?;
+    crate::resolve::ensure_no_pending_merge(&cyan_state)?;
+    Ok(cyan_state)
+}
+
+/// Supply the secrets state omitted from `CYANPRINT_ANSWER_<ID>`, asking for the rest
+/// once per template: each answer is given to both of that template's runs.
+pub(super) fn supply_missing_secrets(
+    cyan_state: &CyanState,
+    prev_specs: &mut [TemplateSpec],
+    curr_specs: &mut [TemplateSpec],
+) -> Result<(), Box<dyn Error + Send>> {
+    fill_missing_secrets(prev_specs, cyan_state, &HashMap::new());
+    fill_missing_secrets(curr_specs, cyan_state, &HashMap::new());
+    for prev in prev_specs.iter_mut() {
+        let key = prev.key();
+        let Some(entry) = cyan_state
+            .templates
+            .get(&key)
+            .and_then(|ts| ts.history.last())
+        else {
+            continue;
+        };
+        for id in entry.missing_secrets() {
+            if prev.answers.contains_key(&id) {
+                continue;
+            }
+            let answer = inquire::Password::new(&format!("{key}: {id}"))
+                .without_confirmation()
+                .prompt()
+                .map_err(|e| other(format!("no answer for secret '{id}' of {key}: {e}")))?;
+            let answer = Answer::String(answer);
+            for curr in curr_specs.iter_mut().filter(|c| c.key() == key) {
+                curr.answers
+                    .entry(id.clone())
+                    .or_insert_with(|| answer.clone());
+            }
+            prev.answers.insert(id, answer);
+        }
+    }
+    Ok(())
+}
+
+/// Move the project from `prev_specs` to `curr_specs` through the batch pipeline without
+/// recording new history; `update_state` then edits the state before it is saved.
+/// Returns the sessions to clean and the edited files that were kept.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn rematerialise(
+    session_id_generator: Box<dyn SessionIdGenerator>,
+    target_dir: &Path,
+    coord_client: &CyanCoordinatorClient,
+    registry_client: Rc<CyanRegistryClient>,
+    debug: bool,
+    cache_config: cyancoordinator::cache::CacheConfig,
+    binary_policy: cyancoordinator::fs::BinaryMergePolicy,
+    prev_specs: &[TemplateSpec],
+    curr_specs: &[TemplateSpec],
+    update_state: impl FnOnce(&mut CyanState),
+) -> Result<(CyanRunResult, Vec<String>), Box<dyn Error + Send>> {
+    let mut composition_operator = OperatorFactory::create_composition_operator(
+        session_id_generator,
+        coord_client.clone(),
+        registry_client.clone(),
+        debug,
+        cache_config,
+        binary_policy,
+        false,
+    );
+
+    // Nothing is upgraded: every spec is already in history, so the only state changes
+    // are the ones `update_state` makes.
+    let outcome = batch_process(
+        prev_specs,
+        curr_specs,
+        &[],
+        target_dir,
+        &registry_client,
+        coord_client,
+        &mut composition_operator,
+        false,
+    )?;
+    let mut session_guard = SessionCleanupGuard::new(
+        |sid: &str| release_session(coord_client, sid),
+        outcome.session_ids,
+    );
+    composition_operator.print_cache_summary();
+
+    let state_file_path = state_file(target_dir);
+    let state_manager = DefaultStateManager::new();
+    let mut cyan_state = state_manager.load_state_file(&state_file_path)?;
+    update_state(&mut cyan_state);
+    cyan_state.file_conflicts = outcome.file_conflicts;
+    cyan_state.set_managed_files(&outcome.managed_by_template);
+    cyan_state.pending_merge = outcome.pending_merge;
+    state_manager.save_state_file(&cyan_state, &state_file_path)?;
+    commit_write(outcome.transaction)?;
+
+    if let Some(pending) = &cyan_state.pending_merge {
+        crate::resolve::report_pending_merge(pending, false);
+        return Ok((
+            CyanRunResult::completed(session_guard.take()),
+            outcome.kept_local,
+        ));
+    }
+
+    let commands = outcome.commands;
+    if !commands.is_empty() {
+        println!(
+            "\n⚡ Executing {} post-template command(s)...",
+            commands.len()
+        );
+        let exec_result = CommandExecutor::execute_commands_for_mode(&commands, target_dir, false)?;
+        if exec_result.aborted {
+            return Err(other(format!(
+                "Command execution aborted: {}/{} succeeded, {}/{} failed before abort",
+                exec_result.succeeded, exec_result.total, exec_result.failed, exec_result.total
+            )));
+        }
+    }
+
+    Ok((
+        CyanRunResult::completed(session_guard.take()),
+        outcome.kept_local,
+    ))
+}
+
+/// List the edited files a rewrite left in place instead of deleting.
+pub(super) fn report_kept(kept: &[String]) {
+    if !kept.is_empty() {
+        println!("✋ Kept {} file(s) you edited:", kept.len());
+        for path in kept {
+            println!("  {path}");
+        }
+    }
+}
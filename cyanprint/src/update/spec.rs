@@ -1,13 +1,16 @@
 use chrono::{DateTime, Utc};
-use cyancoordinator::state::models::CyanState;
+use cyancoordinator::state::env_answer;
+use cyancoordinator::state::models::{CyanState, VersionConstraint};
 use cyanprompt::domain::models::answer::Answer;
 use cyanregistry::http::client::CyanRegistryClient;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::rc::Rc;
 
-use super::utils::parse_template_key;
-use super::version_manager::{fetch_all_template_versions, select_version_interactive};
+use super::utils::{SelectionError, parse_template_key};
+use super::version_manager::{
+    TemplateVersionInfo, fetch_all_template_versions, select_version_interactive,
+};
 
 /// A simple data structure representing a template to execute.
 /// Used for the unified batch processing flow.
@@ -84,7 +87,7 @@ impl TemplateSpecManager {
                     username,
                     template_name,
                     entry.version,
-                    entry.answers.clone(),
+                    entry.replay_answers(),
                     entry.deterministic_states.clone(),
                     entry.time,
                 ))
@@ -93,15 +96,22 @@ impl TemplateSpecManager {
     }
 
     /// Update specs to latest versions via registry lookup (pure function)
-    /// If interactive=true, prompt user to select versions
+    /// If interactive=true, prompt user to select versions.
+    /// `selection` limits which templates move and how far (targets, `--to`, pins).
     pub fn update(
         &self,
         specs: Vec<TemplateSpec>,
         interactive: bool,
+        selection: &UpdateSelection,
     ) -> Result<Vec<TemplateSpec>, Box<dyn Error + Send>> {
         specs
             .iter()
             .map(|spec| {
+                let plan = selection.plan(&spec.key());
+                if plan == VersionPlan::Keep {
+                    return Ok(spec.clone());
+                }
+
                 // Fetch all versions
                 let all_versions = fetch_all_template_versions(
                     &self.registry,
@@ -109,30 +119,41 @@ impl TemplateSpecManager {
                     &spec.template_name,
                 )?;
 
-                // Get the latest version
-                let latest = all_versions
-                    .iter()
-                    .max_by_key(|v| v.version)
-                    .ok_or_else(|| {
-                        Box::new(std::io::Error::new(
-                            std::io::ErrorKind::NotFound,
-                            format!(
-                                "No versions found for {}/{}",
-                                spec.username, spec.template_name
-                            ),
-                        )) as Box<dyn Error + Send>
-                    })?;
-
                 // Determine target version
-                let target_version = if interactive {
-                    select_version_interactive(
-                        &spec.username,
-                        &spec.template_name,
-                        spec.version,
-                        &all_versions,
-                    )?
-                } else {
-                    latest.version
+                let target_version = match plan {
+                    VersionPlan::Keep => spec.version,
+                    VersionPlan::Exact(version) => {
+                        if !all_versions.iter().any(|v| v.version == version) {
+                            return Err(Box::new(SelectionError(format!(
+                                "Version {version} of {} does not exist",
+                                spec.key()
+                            ))) as Box<dyn Error + Send>);
+                        }
+                        version
+                    }
+                    VersionPlan::Newest(constraint) => {
+                        let allowed: Vec<TemplateVersionInfo> = all_versions
+                            .into_iter()
+                            .filter(|v| constraint.is_none_or(|c| c.allows(v.version)))
+                            .collect();
+                        if allowed.is_empty() {
+                            // Nothing satisfies the constraint: stay where we are
+                            spec.version
+                        } else if interactive {
+                            select_version_interactive(
+                                &spec.username,
+                                &spec.template_name,
+                                spec.version,
+                                &allowed,
+                            )?
+                        } else {
+                            allowed
+                                .iter()
+                                .map(|v| v.version)
+                                .max()
+                                .unwrap_or(spec.version)
+                        }
+                    }
                 };
 
                 Ok(TemplateSpec::new(
@@ -161,7 +182,321 @@ impl TemplateSpecManager {
     }
 }
 
+/// Which templates `cyanprint update` may move, and how far.
+#[derive(Debug, Clone, Default)]
+pub struct UpdateSelection {
+    /// `<user>/<template>` keys named on the command line; empty means every template.
+    targets: HashSet<String>,
+    /// Explicit versions from `--to <user>/<template>:<version>`; these override pins
+    /// and constraints.
+    versions: HashMap<String, i64>,
+    pinned: HashSet<String>,
+    constraints: HashMap<String, VersionConstraint>,
+}
+
+/// How one template's target version is chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionPlan<'a> {
+    /// Stays at its current version.
+    Keep,
+    /// Moves to exactly this version.
+    Exact(i64),
+    /// Moves to the newest version, within the constraint if there is one.
+    Newest(Option<&'a VersionConstraint>),
+}
+
+impl UpdateSelection {
+    /// Build a selection from the command line and the pins recorded in `state`. Every
+    /// named template must be active in `state`.
+    pub fn new(
+        targets: &[String],
+        versions: &[(String, i64)],
+        state: &CyanState,
+    ) -> Result<Self, Box<dyn Error + Send>> {
+        for key in targets.iter().chain(versions.iter().map(|(k, _)| k)) {
+            if !state.templates.get(key).is_some_and(|ts| ts.active) {
+                return Err(Box::new(SelectionError(format!(
+                    "Template {key} is not installed in this project"
+                ))));
+            }
+        }
+        Ok(Self {
+            targets: targets.iter().cloned().collect(),
+            versions: versions.iter().cloned().collect(),
+            pinned: state
+                .templates
+                .iter()
+                .filter(|(_, ts)| ts.pinned)
+                .map(|(key, _)| key.clone())
+                .collect(),
+            constraints: state
+                .templates
+                .iter()
+                .filter_map(|(key, ts)| ts.constraint.clone().map(|c| (key.clone(), c)))
+                .collect(),
+        })
+    }
+
+    /// How the template `key` is updated.
+    pub fn plan(&self, key: &str) -> VersionPlan<'_> {
+        if let Some(&version) = self.versions.get(key) {
+            return VersionPlan::Exact(version);
+        }
+        if !self.selects(key) || self.pinned.contains(key) {
+            return VersionPlan::Keep;
+        }
+        VersionPlan::Newest(self.constraints.get(key))
+    }
+
+    /// Whether the template `key` was named on the command line, or nothing was.
+    pub fn selects(&self, key: &str) -> bool {
+        (self.targets.is_empty() && self.versions.is_empty())
+            || self.targets.contains(key)
+            || self.versions.contains_key(key)
+    }
+
+    /// Keys of the selected `specs` that stay put only because they are pinned.
+    pub fn held_by_pin(&self, specs: &[TemplateSpec]) -> Vec<String> {
+        specs
+            .iter()
+            .map(TemplateSpec::key)
+            .filter(|key| self.pinned.contains(key) && !self.versions.contains_key(key))
+            .filter(|key| self.targets.is_empty() || self.targets.contains(key))
+            .collect()
+    }
+}
+
+/// Recorded answers `cyanprint update` forgets, so the templates ask for them again.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Reanswer {
+    /// Replay every recorded answer.
+    #[default]
+    Keep,
+    /// Forget the answers to these question ids (`--reanswer`).
+    Questions(Vec<String>),
+    /// Forget every answer (`--reask`).
+    All,
+}
+
+impl Reanswer {
+    pub fn new(questions: &[String], all: bool) -> Self {
+        match (all, questions) {
+            (true, _) => Self::All,
+            (false, []) => Self::Keep,
+            (false, ids) => Self::Questions(ids.to_vec()),
+        }
+    }
+
+    /// Clear the chosen answers from the specs `selection` covers and return the keys of
+    /// the specs that lost one; those re-run even when their version stays put. Every
+    /// `--reanswer` id must have been answered by one of the selected templates.
+    pub fn apply(
+        &self,
+        specs: &mut [TemplateSpec],
+        selection: &UpdateSelection,
+    ) -> Result<HashSet<String>, Box<dyn Error + Send>> {
+        let mut cleared = HashSet::new();
+        let selected = specs.iter_mut().filter(|s| selection.selects(&s.key()));
+        match self {
+            Self::Keep => {}
+            Self::All => {
+                for spec in selected {
+                    spec.answers.clear();
+                    spec.deterministic_states.clear();
+                    cleared.insert(spec.key());
+                }
+            }
+            Self::Questions(ids) => {
+                let mut found = HashSet::new();
+                for spec in selected {
+                    for id in ids {
+                        if spec.answers.remove(id).is_some() {
+                            found.insert(id.as_str());
+                            cleared.insert(spec.key());
+                        }
+                    }
+                }
+                if let Some(missing) = ids.iter().find(|id| !found.contains(id.as_str())) {
+                    return Err(Box::new(SelectionError(format!(
+                        "No template being updated has a recorded answer to '{missing}'"
+                    ))));
+                }
+            }
+        }
+        Ok(cleared)
+    }
+}
+
 /// Sort specs by installation time for consistent LWW ordering
 pub fn sort_specs(specs: &mut [TemplateSpec]) {
-    specs.sort_by(|a, b| a.installed_at.cmp(&b.installed_at));
+    specs.sort_by_key(|a| a.installed_at);
+}
+
+/// Give each spec the supplied `answers` to the secrets its state entry could not replay
+/// (omitted, or sealed with a key or variable not set here), so headless runs can pass
+/// them with `--answers`; an id not supplied is read from its `CYANPRINT_ANSWER_<ID>`
+/// variable. Only ids state recorded as that template's own secrets are filled, so an
+/// answer cannot satisfy another template's same-named question.
+pub fn fill_missing_secrets(
+    specs: &mut [TemplateSpec],
+    state: &CyanState,
+    answers: &HashMap<String, Answer>,
+) {
+    for spec in specs {
+        let Some(entry) = state
+            .templates
+            .get(&spec.key())
+            .and_then(|ts| ts.history.last())
+        else {
+            continue;
+        };
+        for id in entry.missing_secrets() {
+            if let Some(answer) = answers.get(&id).cloned().or_else(|| env_answer(&id)) {
+                spec.answers.entry(id).or_insert(answer);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cyancoordinator::state::models::TemplateState;
+
+    fn state(templates: &[(&str, bool, Option<&str>)]) -> CyanState {
+        let mut state = CyanState::default();
+        for (key, pinned, constraint) in templates {
+            state.templates.insert(
+                key.to_string(),
+                TemplateState {
+                    active: true,
+                    history: Vec::new(),
+                    files: Vec::new(),
+                    pinned: *pinned,
+                    constraint: constraint.map(|c| c.parse().unwrap()),
+                },
+            );
+        }
+        state
+    }
+
+    fn specs() -> Vec<TemplateSpec> {
+        ["alice/web", "bob/api", "carol/db"]
+            .iter()
+            .map(|key| {
+                let (user, name) = parse_template_key(key).unwrap();
+                TemplateSpec::new_template(user, name, 1)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn selection_updates_everything_unpinned_by_default() {
+        let state = state(&[
+            ("alice/web", false, None),
+            ("bob/api", true, None),
+            ("carol/db", false, Some("<5")),
+        ]);
+        let sel = UpdateSelection::new(&[], &[], &state).unwrap();
+        assert_eq!(sel.plan("alice/web"), VersionPlan::Newest(None));
+        assert_eq!(sel.plan("bob/api"), VersionPlan::Keep);
+        let cap: VersionConstraint = "<5".parse().unwrap();
+        assert_eq!(sel.plan("carol/db"), VersionPlan::Newest(Some(&cap)));
+        assert_eq!(sel.held_by_pin(&specs()), vec!["bob/api".to_string()]);
+    }
+
+    #[test]
+    fn selection_limits_to_targets_and_explicit_versions() {
+        let state = state(&[
+            ("alice/web", false, None),
+            ("bob/api", true, None),
+            ("carol/db", false, None),
+        ]);
+        let sel = UpdateSelection::new(
+            &["alice/web".to_string()],
+            &[("bob/api".to_string(), 7)],
+            &state,
+        )
+        .unwrap();
+        assert_eq!(sel.plan("alice/web"), VersionPlan::Newest(None));
+        // `--to` overrides the pin
+        assert_eq!(sel.plan("bob/api"), VersionPlan::Exact(7));
+        assert_eq!(sel.plan("carol/db"), VersionPlan::Keep);
+        assert!(sel.held_by_pin(&specs()).is_empty());
+
+        assert!(UpdateSelection::new(&["nobody/none".to_string()], &[], &state).is_err());
+    }
+
+    #[test]
+    fn reanswer_clears_answers_of_selected_templates() {
+        let state = state(&[
+            ("alice/web", false, None),
+            ("bob/api", false, None),
+            ("carol/db", false, None),
+        ]);
+        let answered = || {
+            let mut specs = specs();
+            for spec in &mut specs[..2] {
+                spec.answers
+                    .insert("port".to_string(), Answer::String("80".to_string()));
+                spec.answers.insert("tls".to_string(), Answer::Bool(true));
+            }
+            specs
+        };
+        let all = UpdateSelection::new(&[], &[], &state).unwrap();
+
+        let mut specs = answered();
+        let cleared = Reanswer::new(&["port".to_string()], false)
+            .apply(&mut specs, &all)
+            .unwrap();
+        assert_eq!(
+            cleared,
+            HashSet::from(["alice/web".to_string(), "bob/api".to_string()])
+        );
+        assert!(!specs[0].answers.contains_key("port"));
+        assert!(specs[0].answers.contains_key("tls"));
+
+        let only_api = UpdateSelection::new(&["bob/api".to_string()], &[], &state).unwrap();
+        let mut specs = answered();
+        let cleared = Reanswer::new(&[], true)
+            .apply(&mut specs, &only_api)
+            .unwrap();
+        assert_eq!(cleared, HashSet::from(["bob/api".to_string()]));
+        assert!(specs[1].answers.is_empty());
+        assert_eq!(specs[0].answers.len(), 2);
+
+        let mut specs = answered();
+        assert!(Reanswer::Keep.apply(&mut specs, &all).unwrap().is_empty());
+        let err = Reanswer::new(&["nope".to_string()], false)
+            .apply(&mut specs, &all)
+            .expect_err("an unknown question id is rejected");
+        assert!(err.to_string().contains("'nope'"), "got: {err}");
+    }
+
+    #[test]
+    fn missing_secrets_are_filled_only_into_their_own_template() {
+        let mut state = state(&[("alice/web", false, None), ("bob/api", false, None)]);
+        state.templates.get_mut("alice/web").unwrap().history.push(
+            cyancoordinator::state::models::TemplateHistoryEntry {
+                version: 1,
+                time: Utc::now(),
+                answers: HashMap::from([("name".to_string(), Answer::String("web".to_string()))]),
+                deterministic_states: HashMap::new(),
+                secrets: Some(["token".to_string()].into()),
+                sealed: Default::default(),
+            },
+        );
+        let mut specs = specs();
+        let supplied = HashMap::from([
+            ("token".to_string(), Answer::String("s3cr3t".to_string())),
+            ("name".to_string(), Answer::String("other".to_string())),
+        ]);
+
+        fill_missing_secrets(&mut specs, &state, &supplied);
+        assert_eq!(
+            specs[0].answers,
+            HashMap::from([("token".to_string(), Answer::String("s3cr3t".to_string()))])
+        );
+        assert!(specs[1].answers.is_empty());
+    }
 }
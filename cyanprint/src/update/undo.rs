@@ -0,0 +1,234 @@
+use std::error::Error;
+use std::path::Path;
+use std::rc::Rc;
+
+use cyancoordinator::client::CyanCoordinatorClient;
+use cyancoordinator::session::SessionIdGenerator;
+use cyancoordinator::state::models::{CyanState, TemplateHistoryEntry};
+use cyanregistry::http::client::CyanRegistryClient;
+
+use super::rewrite::{load_state_for_rewrite, rematerialise, report_kept, supply_missing_secrets};
+use super::spec::{TemplateSpec, TemplateSpecManager, sort_specs};
+use super::utils::parse_template_key;
+use crate::headless::CyanRunResult;
+
+fn other(msg: String) -> Box<dyn Error + Send> {
+    Box::new(std::io::Error::other(msg))
+}
+
+/// What `cyanprint undo` reverts: one template's latest history entry.
+#[derive(Debug, Clone)]
+pub struct UndoPlan {
+    /// `<user>/<template>` being reverted.
+    pub key: String,
+    /// The entry it goes back to; `None` when the latest entry is its creation, in
+    /// which case undoing removes the template altogether.
+    pub to: Option<TemplateHistoryEntry>,
+}
+
+/// Pick the template to undo: `template` when given, otherwise the active template
+/// changed most recently.
+pub fn plan_undo(
+    state: &CyanState,
+    template: Option<&str>,
+) -> Result<UndoPlan, Box<dyn Error + Send>> {
+    let key = match template {
+        Some(key) => state
+            .templates
+            .get(key)
+            .filter(|ts| ts.active && !ts.history.is_empty())
+            .map(|_| key.to_string())
+            .ok_or_else(|| other(format!("No active template '{key}' to undo")))?,
+        None => state
+            .templates
+            .iter()
+            .filter(|(_, ts)| ts.active)
+            .filter_map(|(key, ts)| ts.history.last().map(|e| (key, e.time)))
+            .max_by_key(|(_, time)| *time)
+            .map(|(key, _)| key.clone())
+            .ok_or_else(|| other("Nothing to undo: no active templates".to_string()))?,
+    };
+
+    let history = &state.templates[&key].history;
+    let to = history.len().checked_sub(2).map(|i| history[i].clone());
+    Ok(UndoPlan { key, to })
+}
+
+/// Pop the undone entry off the template's history, dropping the template when the
+/// undone entry was its creation.
+pub fn apply_undo(state: &mut CyanState, plan: &UndoPlan) {
+    if plan.to.is_none() {
+        state.templates.remove(&plan.key);
+    } else if let Some(ts) = state.templates.get_mut(&plan.key) {
+        ts.history.pop();
+    }
+}
+
+/// The specs to re-materialise: every active template as it is now, with the undone
+/// one replaced by its previous entry (or left out when it is being removed).
+fn undo_specs(prev_specs: &[TemplateSpec], plan: &UndoPlan) -> Vec<TemplateSpec> {
+    let mut curr: Vec<TemplateSpec> = prev_specs
+        .iter()
+        .filter(|s| s.key() != plan.key)
+        .cloned()
+        .collect();
+    if let (Some(entry), Some((username, template_name))) =
+        (&plan.to, parse_template_key(&plan.key))
+    {
+        curr.push(TemplateSpec::new(
+            username,
+            template_name,
+            entry.version,
+            entry.replay_answers(),
+            entry.deterministic_states.clone(),
+            entry.time,
+        ));
+    }
+    sort_specs(&mut curr);
+    curr
+}
+
+/// Revert the last create/update of one template: re-run the previous version with its
+/// recorded answers, three-way merge it into the working tree and pop the history entry.
+#[allow(clippy::too_many_arguments)]
+pub fn cyan_undo(
+    session_id_generator: Box<dyn SessionIdGenerator>,
+    path: String,
+    template: Option<String>,
+    coord_client: CyanCoordinatorClient,
+    registry_client: Rc<CyanRegistryClient>,
+    debug: bool,
+    force: bool,
+    cache_config: cyancoordinator::cache::CacheConfig,
+    binary_policy: cyancoordinator::fs::BinaryMergePolicy,
+) -> Result<CyanRunResult, Box<dyn Error + Send>> {
+    let target_dir = Path::new(&path);
+    let cyan_state = load_state_for_rewrite(target_dir, force)?;
+
+    let plan = plan_undo(&cyan_state, template.as_deref())?;
+    match &plan.to {
+        Some(entry) => println!("⏪ Reverting {} to version {}", plan.key, entry.version),
+        None => println!("⏪ Removing {} (undoing its creation)", plan.key),
+    }
+
+    let manager = TemplateSpecManager::new(Rc::clone(&registry_client));
+    let mut prev_specs = manager.get(&cyan_state);
+    sort_specs(&mut prev_specs);
+    let mut curr_specs = undo_specs(&prev_specs, &plan);
+    supply_missing_secrets(&cyan_state, &mut prev_specs, &mut curr_specs)?;
+
+    let (result, kept) = rematerialise(
+        session_id_generator,
+        target_dir,
+        &coord_client,
+        registry_client,
+        debug,
+        cache_config,
+        binary_policy,
+        &prev_specs,
+        &curr_specs,
+        |state| apply_undo(state, &plan),
+    )?;
+    report_kept(&kept);
+    println!("✅ Undo complete");
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use cyancoordinator::state::models::TemplateState;
+    use std::collections::HashMap;
+
+    fn entry(version: i64, secs: i64) -> TemplateHistoryEntry {
+        TemplateHistoryEntry {
+            version,
+            time: Utc.timestamp_opt(secs, 0).unwrap(),
+            answers: HashMap::new(),
+            deterministic_states: HashMap::new(),
+            secrets: Default::default(),
+            sealed: Default::default(),
+        }
+    }
+
+    fn state(templates: &[(&str, bool, Vec<TemplateHistoryEntry>)]) -> CyanState {
+        let mut state = CyanState::default();
+        for (key, active, history) in templates {
+            state.templates.insert(
+                key.to_string(),
+                TemplateState {
+                    active: *active,
+                    history: history.clone(),
+                    files: Vec::new(),
+                    pinned: false,
+                    constraint: None,
+                },
+            );
+        }
+        state
+    }
+
+    // Without a template, the most recently changed active template is undone.
+    #[test]
+    fn plan_picks_latest_change() {
+        let state = state(&[
+            ("alice/web", true, vec![entry(1, 10), entry(2, 30)]),
+            ("bob/api", true, vec![entry(4, 20)]),
+            ("carol/old", false, vec![entry(9, 99)]),
+        ]);
+        let plan = plan_undo(&state, None).unwrap();
+        assert_eq!(plan.key, "alice/web");
+        assert_eq!(plan.to.unwrap().version, 1);
+
+        let plan = plan_undo(&state, Some("bob/api")).unwrap();
+        assert!(plan.to.is_none());
+        assert!(plan_undo(&state, Some("carol/old")).is_err());
+        assert!(plan_undo(&state, Some("nobody/none")).is_err());
+    }
+
+    // Undoing pops the entry; undoing a creation removes the template.
+    #[test]
+    fn apply_pops_history_or_removes_template() {
+        let mut state = state(&[
+            ("alice/web", true, vec![entry(1, 10), entry(2, 30)]),
+            ("bob/api", true, vec![entry(4, 20)]),
+        ]);
+        let plan = plan_undo(&state, Some("alice/web")).unwrap();
+        apply_undo(&mut state, &plan);
+        let history = &state.templates["alice/web"].history;
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].version, 1);
+
+        let plan = plan_undo(&state, Some("bob/api")).unwrap();
+        apply_undo(&mut state, &plan);
+        assert!(!state.templates.contains_key("bob/api"));
+    }
+
+    // The undone template runs at its previous version; the others are unchanged.
+    #[test]
+    fn specs_swap_in_previous_entry() {
+        let prev = vec![
+            TemplateSpec::new_template("alice".into(), "web".into(), 2),
+            TemplateSpec::new_template("bob".into(), "api".into(), 4),
+        ];
+        let plan = UndoPlan {
+            key: "alice/web".to_string(),
+            to: Some(entry(1, 10)),
+        };
+        let curr = undo_specs(&prev, &plan);
+        let versions: Vec<(String, i64)> = curr.iter().map(|s| (s.key(), s.version)).collect();
+        assert_eq!(
+            versions,
+            vec![("alice/web".to_string(), 1), ("bob/api".to_string(), 4)]
+        );
+
+        let removed = UndoPlan {
+            key: "alice/web".to_string(),
+            to: None,
+        };
+        let curr = undo_specs(&prev, &removed);
+        assert_eq!(curr.len(), 1);
+        assert_eq!(curr[0].key(), "bob/api");
+    }
+}
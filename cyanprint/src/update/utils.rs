@@ -13,8 +13,53 @@ impl fmt::Display for SelectionError {
 
 impl Error for SelectionError {}
 
+/// Parse a `--to <user>/<template>:<version>` argument.
+pub fn parse_version_target(s: &str) -> Result<(String, i64), String> {
+    let (key, version) = s
+        .rsplit_once(':')
+        .ok_or_else(|| format!("expected <user>/<template>:<version>, got '{s}'"))?;
+    parse_template_key(key).ok_or_else(|| format!("invalid template '{key}'"))?;
+    let version = version
+        .parse::<i64>()
+        .map_err(|_| format!("invalid version '{version}' in '{s}'"))?;
+    Ok((key.to_string(), version))
+}
+
+/// Parse a `<user>/<template>` target named after `update --`.
+pub fn parse_template_target(s: &str) -> Result<String, String> {
+    parse_template_key(s)
+        .map(|_| s.to_string())
+        .ok_or_else(|| format!("expected <user>/<template>, got '{s}'"))
+}
+
 /// Parse template key into username and template name
 pub fn parse_template_key(template_key: &str) -> Option<(String, String)> {
     let parts: Vec<&str> = template_key.split('/').collect();
     (parts.len() == 2).then(|| (parts[0].to_string(), parts[1].to_string()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_target_parses() {
+        assert_eq!(
+            parse_version_target("alice/web:12"),
+            Ok(("alice/web".to_string(), 12))
+        );
+        assert!(parse_version_target("alice/web").is_err());
+        assert!(parse_version_target("alice:12").is_err());
+        assert!(parse_version_target("alice/web:x").is_err());
+    }
+
+    #[test]
+    fn template_target_parses() {
+        assert_eq!(
+            parse_template_target("alice/web"),
+            Ok("alice/web".to_string())
+        );
+        assert!(parse_template_target("services/api/x").is_err());
+        assert!(parse_template_target("app").is_err());
+    }
+}
@@ -74,7 +74,7 @@ pub fn fetch_all_template_versions(
     }
 
     // Sort by version descending (newest first)
-    all_versions.sort_by(|a, b| b.version.cmp(&a.version));
+    all_versions.sort_by_key(|v| std::cmp::Reverse(v.version));
     Ok(all_versions)
 }
 
@@ -7,6 +7,8 @@ pub enum Answer {
     String(String),
     StringArray(Vec<String>),
     Bool(bool),
+    Number(f64),
+    Object(serde_json::Map<String, serde_json::Value>),
 }
 
 impl fmt::Debug for Answer {
@@ -15,6 +17,8 @@ impl fmt::Debug for Answer {
             Answer::String(s) => write!(f, "String({s:?})"),
             Answer::StringArray(arr) => write!(f, "StringArray({arr:?})"),
             Answer::Bool(b) => write!(f, "Bool({b:?})"),
+            Answer::Number(n) => write!(f, "Number({n:?})"),
+            Answer::Object(o) => write!(f, "Object({o:?})"),
         }
     }
 }
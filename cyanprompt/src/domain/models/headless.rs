@@ -13,10 +13,11 @@
 
 use serde::Serialize;
 
-use crate::domain::models::question::Question;
+use crate::domain::models::question::{Question, QuestionOption, TextConstraints};
 
 /// Wire representation of a [`Question`], serialized with a `type` tag matching
-/// the HTTP `QuestionRes` kinds (confirm/date/checkbox/password/text/select) and
+/// the HTTP `QuestionRes` kinds
+/// (confirm/date/checkbox/password/text/select/number/editor/path/list) and
 /// stable snake_case field names. Mapped from the domain [`Question`] rather than
 /// derived on it, to keep the domain type decoupled from the wire shape.
 #[derive(Debug, Clone, Serialize, PartialEq)]
@@ -40,7 +41,8 @@ pub enum QuestionWire {
         id: String,
         message: String,
         desc: Option<String>,
-        options: Vec<String>,
+        options: Vec<OptionWire>,
+        default: Option<Vec<String>>,
     },
     /// Secret-typed question. Intentionally carries NO default/value field so a
     /// secret default is never emitted in plaintext.
@@ -49,6 +51,8 @@ pub enum QuestionWire {
         message: String,
         desc: Option<String>,
         confirmation: Option<bool>,
+        #[serde(flatten)]
+        constraints: ConstraintsWire,
     },
     Text {
         id: String,
@@ -56,12 +60,45 @@ pub enum QuestionWire {
         desc: Option<String>,
         default: Option<String>,
         initial: Option<String>,
+        #[serde(flatten)]
+        constraints: ConstraintsWire,
     },
     Select {
         id: String,
         message: String,
         desc: Option<String>,
-        options: Vec<String>,
+        options: Vec<OptionWire>,
+        default: Option<String>,
+    },
+    Number {
+        id: String,
+        message: String,
+        desc: Option<String>,
+        default: Option<f64>,
+        min: Option<f64>,
+        max: Option<f64>,
+        step: Option<f64>,
+    },
+    Editor {
+        id: String,
+        message: String,
+        desc: Option<String>,
+        default: Option<String>,
+    },
+    Path {
+        id: String,
+        message: String,
+        desc: Option<String>,
+        default: Option<String>,
+        must_exist: Option<bool>,
+        dir: Option<bool>,
+    },
+    List {
+        id: String,
+        message: String,
+        desc: Option<String>,
+        default: Option<Vec<String>>,
+        key_value: Option<bool>,
     },
 }
 
@@ -86,7 +123,8 @@ impl From<&Question> for QuestionWire {
                 id: cb.id.clone(),
                 message: cb.message.clone(),
                 desc: cb.desc.clone(),
-                options: cb.options.clone(),
+                options: cb.options.iter().map(OptionWire::from).collect(),
+                default: cb.default.clone(),
             },
             // Map only non-secret metadata; never the default/value.
             Question::Password(pw) => QuestionWire::Password {
@@ -94,6 +132,7 @@ impl From<&Question> for QuestionWire {
                 message: pw.message.clone(),
                 desc: pw.desc.clone(),
                 confirmation: pw.confirmation,
+                constraints: ConstraintsWire::from(&pw.constraints),
             },
             Question::Text(t) => QuestionWire::Text {
                 id: t.id.clone(),
@@ -101,17 +140,92 @@ impl From<&Question> for QuestionWire {
                 desc: t.desc.clone(),
                 default: t.default.clone(),
                 initial: t.initial.clone(),
+                constraints: ConstraintsWire::from(&t.constraints),
             },
             Question::Select(s) => QuestionWire::Select {
                 id: s.id.clone(),
                 message: s.message.clone(),
                 desc: s.desc.clone(),
-                options: s.options.clone(),
+                options: s.options.iter().map(OptionWire::from).collect(),
+                default: s.default.clone(),
+            },
+            Question::Number(n) => QuestionWire::Number {
+                id: n.id.clone(),
+                message: n.message.clone(),
+                desc: n.desc.clone(),
+                default: n.default,
+                min: n.min,
+                max: n.max,
+                step: n.step,
+            },
+            Question::Editor(e) => QuestionWire::Editor {
+                id: e.id.clone(),
+                message: e.message.clone(),
+                desc: e.desc.clone(),
+                default: e.default.clone(),
+            },
+            Question::Path(p) => QuestionWire::Path {
+                id: p.id.clone(),
+                message: p.message.clone(),
+                desc: p.desc.clone(),
+                default: p.default.clone(),
+                must_exist: p.must_exist,
+                dir: p.dir,
+            },
+            Question::List(l) => QuestionWire::List {
+                id: l.id.clone(),
+                message: l.message.clone(),
+                desc: l.desc.clone(),
+                default: l.default.clone(),
+                key_value: l.key_value,
             },
         }
     }
 }
 
+/// Wire representation of a Select/Checkbox option. Answers carry `value`.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct OptionWire {
+    pub value: String,
+    pub label: String,
+    pub description: Option<String>,
+    pub disabled: bool,
+}
+
+impl From<&QuestionOption> for OptionWire {
+    fn from(o: &QuestionOption) -> Self {
+        OptionWire {
+            value: o.value.clone(),
+            label: o.label.clone(),
+            description: o.description.clone(),
+            disabled: o.disabled,
+        }
+    }
+}
+
+/// Wire representation of a Text/Password question's declared constraints,
+/// flattened into the question object.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ConstraintsWire {
+    pub pattern: Option<String>,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    pub charset: Option<String>,
+    pub required: Option<bool>,
+}
+
+impl From<&TextConstraints> for ConstraintsWire {
+    fn from(c: &TextConstraints) -> Self {
+        ConstraintsWire {
+            pattern: c.pattern.clone(),
+            min_length: c.min_length,
+            max_length: c.max_length,
+            charset: c.charset.clone(),
+            required: c.required,
+        }
+    }
+}
+
 /// The headless output envelope. Serialized as a single JSON object tagged by
 /// `status`, the sole stdout output of a headless invocation.
 #[derive(Debug, Clone, Serialize, PartialEq)]
@@ -147,8 +261,8 @@ impl HeadlessEnvelope {
     /// Serialize to a single-line JSON object.
     ///
     /// This is a closed enum whose fields are all owned `String`s and a [`QuestionWire`]
-    /// (itself only `String`/`Option`/`Vec<String>`/`bool`) — serde_json serialization of
-    /// such a type cannot fail. A failure here would be a serde_json bug, not a reachable
+    /// (itself only `String`/`Option`/`Vec`/`bool`/`f64`, and serde_json writes a
+    /// non-finite `f64` as `null`) — serde_json serialization of such a type cannot fail. A failure here would be a serde_json bug, not a reachable
     /// state, so the invariant is asserted with `expect` rather than masked behind a
     /// synthetic fallback envelope (which would hide a real serialization regression).
     pub fn to_json(&self) -> String {
@@ -160,7 +274,7 @@ impl HeadlessEnvelope {
 mod tests {
     use super::*;
     use crate::domain::models::question::{
-        ConfirmQuestion, PasswordQuestion, SelectQuestion, TextQuestion,
+        ConfirmQuestion, NumberQuestion, PasswordQuestion, SelectQuestion, TextQuestion,
     };
 
     #[test]
@@ -170,6 +284,7 @@ mod tests {
             default: Some("demo".to_string()),
             desc: Some("the name".to_string()),
             initial: None,
+            constraints: Default::default(),
             id: "name".to_string(),
         });
         let env = HeadlessEnvelope::NeedInput {
@@ -189,14 +304,17 @@ mod tests {
         let q = Question::Select(SelectQuestion {
             message: "Pick one".to_string(),
             desc: None,
-            options: vec!["a".to_string(), "b".to_string()],
+            options: vec!["a".into(), "b".into()],
+            default: None,
             id: "choice".to_string(),
         });
         let wire = QuestionWire::from(&q);
         let v = serde_json::to_value(&wire).unwrap();
         assert_eq!(v["type"], "select");
-        assert_eq!(v["options"][0], "a");
-        assert_eq!(v["options"][1], "b");
+        assert_eq!(v["options"][0]["value"], "a");
+        assert_eq!(v["options"][0]["label"], "a");
+        assert_eq!(v["options"][0]["disabled"], false);
+        assert_eq!(v["options"][1]["value"], "b");
     }
 
     #[test]
@@ -213,6 +331,24 @@ mod tests {
         assert_eq!(v["default"], true);
     }
 
+    #[test]
+    fn number_envelope_carries_bounds() {
+        let q = Question::Number(NumberQuestion {
+            message: "Port?".to_string(),
+            desc: None,
+            default: Some(8080.0),
+            min: Some(1.0),
+            max: Some(65535.0),
+            step: None,
+            id: "port".to_string(),
+        });
+        let v = serde_json::to_value(QuestionWire::from(&q)).unwrap();
+        assert_eq!(v["type"], "number");
+        assert_eq!(v["default"], 8080.0);
+        assert_eq!(v["max"], 65535.0);
+        assert!(v["step"].is_null());
+    }
+
     // A Password question NEVER serializes a default/value field.
     #[test]
     fn password_envelope_never_emits_a_default_or_value() {
@@ -220,6 +356,7 @@ mod tests {
             message: "API token?".to_string(),
             desc: Some("secret".to_string()),
             confirmation: Some(false),
+            constraints: Default::default(),
             id: "token".to_string(),
         });
         let v = serde_json::to_value(QuestionWire::from(&q)).unwrap();
@@ -1,10 +1,42 @@
-use inquire::{Confirm, DateSelect, MultiSelect, Password, Select, Text};
+use std::fmt;
+
+use inquire::{Confirm, CustomType, DateSelect, Editor, MultiSelect, Password, Select, Text};
 
 pub enum Prompts<'a> {
     Text(Text<'a>),
     Confirm(Confirm<'a>),
-    Checkbox(MultiSelect<'a, String>),
-    Select(Select<'a, String>),
+    /// The indices checked initially travel beside the prompt, which can only borrow them.
+    Checkbox(MultiSelect<'a, Choice>, Vec<usize>),
+    Select(Select<'a, Choice>),
     Password(Password<'a>),
     Date(DateSelect<'a>),
+    Number(CustomType<'a, f64>),
+    Editor(Editor<'a>),
+    List(ListPrompt<'a>),
+}
+
+/// A repeating text prompt: one item per line until an empty entry.
+pub struct ListPrompt<'a> {
+    pub message: &'a str,
+    pub help_message: Option<&'a str>,
+    pub default: Option<Vec<String>>,
+    /// Items are `key=value` pairs, answered as an object.
+    pub key_value: bool,
+}
+
+/// A Select/Checkbox entry: shows its label (and description), answers its value.
+#[derive(Clone, Debug)]
+pub struct Choice {
+    pub value: String,
+    pub label: String,
+    pub description: Option<String>,
+}
+
+impl fmt::Display for Choice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.description {
+            Some(desc) => write!(f, "{} — {desc}", self.label),
+            None => write!(f, "{}", self.label),
+        }
+    }
 }
@@ -11,6 +11,10 @@ impl QuestionTrait for Question {
             Question::Password(q) => q.id.clone(),
             Question::Text(q) => q.id.clone(),
             Question::Select(q) => q.id.clone(),
+            Question::Number(q) => q.id.clone(),
+            Question::Editor(q) => q.id.clone(),
+            Question::Path(q) => q.id.clone(),
+            Question::List(q) => q.id.clone(),
         }
     }
 }
@@ -23,6 +27,10 @@ pub enum Question {
     Password(PasswordQuestion),
     Text(TextQuestion),
     Select(SelectQuestion),
+    Number(NumberQuestion),
+    Editor(EditorQuestion),
+    Path(PathQuestion),
+    List(ListQuestion),
 }
 
 #[derive(Clone, Debug)]
@@ -47,8 +55,10 @@ pub struct DateQuestion {
 #[derive(Clone, Debug)]
 pub struct CheckboxQuestion {
     pub message: String,
-    pub options: Vec<String>,
+    pub options: Vec<QuestionOption>,
     pub desc: Option<String>,
+    /// Values checked initially.
+    pub default: Option<Vec<String>>,
     pub id: String,
 }
 #[derive(Clone, Debug)]
@@ -56,6 +66,7 @@ pub struct PasswordQuestion {
     pub message: String,
     pub desc: Option<String>,
     pub confirmation: Option<bool>,
+    pub constraints: TextConstraints,
     pub id: String,
 }
 
@@ -65,13 +76,194 @@ pub struct TextQuestion {
     pub default: Option<String>,
     pub desc: Option<String>,
     pub initial: Option<String>,
+    pub constraints: TextConstraints,
     pub id: String,
 }
 
+/// Constraints a Text/Password answer must meet, checked locally before the
+/// coordinator validator is consulted.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TextConstraints {
+    /// A regular expression the whole answer must match.
+    pub pattern: Option<String>,
+    /// Minimum length, in characters.
+    pub min_length: Option<usize>,
+    /// Maximum length, in characters.
+    pub max_length: Option<usize>,
+    /// The only characters the answer may contain.
+    pub charset: Option<String>,
+    /// The answer must not be empty or whitespace.
+    pub required: Option<bool>,
+}
+
+impl TextConstraints {
+    /// The first constraint `value` violates, described without echoing the value.
+    pub fn violation(&self, value: &str) -> Option<String> {
+        if self.required == Some(true) && value.trim().is_empty() {
+            return Some("A value is required".to_string());
+        }
+        let len = value.chars().count();
+        if let Some(min) = self.min_length.filter(|min| len < *min) {
+            return Some(format!("Must be at least {min} characters"));
+        }
+        if let Some(max) = self.max_length.filter(|max| len > *max) {
+            return Some(format!("Must be at most {max} characters"));
+        }
+        if let Some(charset) = &self.charset {
+            if !value.chars().all(|c| charset.contains(c)) {
+                return Some(format!("Only these characters are allowed: {charset}"));
+            }
+        }
+        if let Some(pattern) = &self.pattern {
+            match regex::Regex::new(&format!("^(?:{pattern})$")) {
+                Ok(re) if re.is_match(value) => {}
+                Ok(_) => return Some(format!("Must match the pattern {pattern}")),
+                Err(_) => return Some("The question's pattern is not a valid regex".to_string()),
+            }
+        }
+        None
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct SelectQuestion {
     pub message: String,
     pub desc: Option<String>,
-    pub options: Vec<String>,
+    pub options: Vec<QuestionOption>,
+    /// Value selected initially.
+    pub default: Option<String>,
+    pub id: String,
+}
+
+/// A Select/Checkbox choice. `value` is what is stored as the answer; `label` is only
+/// displayed, so it can be reworded without breaking saved answers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuestionOption {
+    pub value: String,
+    pub label: String,
+    pub description: Option<String>,
+    /// Shown by no prompt and accepted by no validation.
+    pub disabled: bool,
+}
+
+impl QuestionOption {
+    /// Whether `value` names an option that can be picked.
+    pub fn is_selectable(options: &[QuestionOption], value: &str) -> bool {
+        options.iter().any(|o| !o.disabled && o.value == value)
+    }
+}
+
+/// A plain option, whose label is its value.
+impl From<&str> for QuestionOption {
+    fn from(value: &str) -> Self {
+        QuestionOption {
+            value: value.to_string(),
+            label: value.to_string(),
+            description: None,
+            disabled: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct NumberQuestion {
+    pub message: String,
+    pub desc: Option<String>,
+    pub default: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// Answers must be `min + k * step` (or `k * step` without a `min`).
+    pub step: Option<f64>,
     pub id: String,
 }
+
+impl NumberQuestion {
+    /// Whether `value` satisfies `min`, `max` and `step`.
+    pub fn accepts(&self, value: f64) -> bool {
+        if !value.is_finite() {
+            return false;
+        }
+        if self.min.is_some_and(|min| value < min) || self.max.is_some_and(|max| value > max) {
+            return false;
+        }
+        match self.step {
+            Some(step) if step > 0.0 => {
+                let steps = (value - self.min.unwrap_or(0.0)) / step;
+                (steps - steps.round()).abs() < 1e-9
+            }
+            _ => true,
+        }
+    }
+}
+
+/// Multi-line text, entered in the user's `$EDITOR`.
+#[derive(Clone, Debug)]
+pub struct EditorQuestion {
+    pub message: String,
+    pub desc: Option<String>,
+    pub default: Option<String>,
+    pub id: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct PathQuestion {
+    pub message: String,
+    pub desc: Option<String>,
+    pub default: Option<String>,
+    /// The path must already exist.
+    pub must_exist: Option<bool>,
+    /// An existing path must be a directory (`true`) or a file (`false`).
+    pub dir: Option<bool>,
+    pub id: String,
+}
+
+impl PathQuestion {
+    /// Check `path` (relative to the working directory) against `must_exist` and `dir`,
+    /// returning the violated constraint.
+    pub fn violation(&self, path: &str) -> Option<&'static str> {
+        let meta = std::fs::metadata(path).ok();
+        match (meta, self.dir) {
+            (None, _) if self.must_exist == Some(true) => Some("path does not exist"),
+            (Some(m), Some(true)) if !m.is_dir() => Some("path is not a directory"),
+            (Some(m), Some(false)) if m.is_dir() => Some("path is a directory"),
+            _ => None,
+        }
+    }
+}
+
+/// A repeating question: one text item per prompt until an empty entry. With
+/// `key_value`, each item is a `key=value` pair and the answer is an object of them.
+#[derive(Clone, Debug)]
+pub struct ListQuestion {
+    pub message: String,
+    pub desc: Option<String>,
+    /// Items to answer with when the first entry is left empty; `key=value` pairs for
+    /// a key/value list.
+    pub default: Option<Vec<String>>,
+    pub key_value: Option<bool>,
+    pub id: String,
+}
+
+impl ListQuestion {
+    pub fn is_key_value(&self) -> bool {
+        self.key_value == Some(true)
+    }
+}
+
+/// Split a key/value list item at its first `=`. `None` when it has no `=` or an empty
+/// key; the key is trimmed, the value kept as typed.
+pub fn key_value_item(item: &str) -> Option<(String, String)> {
+    let (key, value) = item.split_once('=')?;
+    let key = key.trim();
+    (!key.is_empty()).then(|| (key.to_string(), value.to_string()))
+}
+
+/// The object a key/value list answers with: one string per key, a repeated key keeping
+/// its last value. Items that are not `key=value` pairs are skipped.
+pub fn key_value_object(items: &[String]) -> serde_json::Map<String, serde_json::Value> {
+    items
+        .iter()
+        .filter_map(|item| key_value_item(item))
+        .map(|(k, v)| (k, serde_json::Value::String(v)))
+        .collect()
+}
@@ -3,6 +3,9 @@ use std::collections::HashMap;
 use crate::domain::models::cyan::Cyan;
 use crate::domain::models::question::Question;
 
+// A questionnaire carries a whole question and dwarfs the final variant; the enum
+// is a single-shot response value, so boxing it would only add an indirection.
+#[allow(clippy::large_enum_variant)]
 pub enum TemplateOutput {
     QnA(TemplateQnAOutput),
     Final(TemplateFinalOutput),
@@ -1,5 +1,8 @@
+use inquire::validator::{ErrorMessage, Validation};
+
 use crate::domain::models::answer::Answer;
-use crate::domain::models::prompt::Prompts;
+use crate::domain::models::prompt::{ListPrompt, Prompts};
+use crate::domain::models::question::{key_value_item, key_value_object};
 
 pub fn prompt(p: Prompts) -> Result<Option<Answer>, Box<dyn std::error::Error + Send>> {
     match p {
@@ -11,14 +14,20 @@ pub fn prompt(p: Prompts) -> Result<Option<Answer>, Box<dyn std::error::Error +
             .prompt_skippable()
             .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send>)
             .map(|a| a.map(Answer::Bool)),
-        Prompts::Checkbox(cb) => cb
-            .prompt_skippable()
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send>)
-            .map(|a| a.map(Answer::StringArray)),
+        Prompts::Checkbox(cb, checked) => {
+            let cb = if checked.is_empty() {
+                cb
+            } else {
+                cb.with_default(&checked)
+            };
+            cb.prompt_skippable()
+                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send>)
+                .map(|a| a.map(|c| Answer::StringArray(c.into_iter().map(|c| c.value).collect())))
+        }
         Prompts::Select(s) => s
             .prompt_skippable()
             .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send>)
-            .map(|a| a.map(Answer::String)),
+            .map(|a| a.map(|c| Answer::String(c.value))),
         Prompts::Password(pw) => pw
             .prompt_skippable()
             .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send>)
@@ -27,5 +36,66 @@ pub fn prompt(p: Prompts) -> Result<Option<Answer>, Box<dyn std::error::Error +
             .prompt_skippable()
             .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send>)
             .map(|a| a.map(|b| Answer::String(b.format("%Y-%m-%d").to_string()))),
+        Prompts::Number(n) => n
+            .prompt_skippable()
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send>)
+            .map(|a| a.map(Answer::Number)),
+        Prompts::Editor(e) => e
+            .prompt_skippable()
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send>)
+            .map(|a| a.map(Answer::String)),
+        Prompts::List(l) => {
+            let key_value = l.key_value;
+            prompt_list(l).map(|a| a.map(|items| list_answer(items, key_value)))
+        }
+    }
+}
+
+/// Ask for list items until an empty entry. Skipping the first item skips the
+/// question; an empty first item takes the default, if any. Key/value items must be
+/// `key=value` pairs.
+fn prompt_list(l: ListPrompt) -> Result<Option<Vec<String>>, Box<dyn std::error::Error + Send>> {
+    let mut items: Vec<String> = Vec::new();
+    loop {
+        let message = format!("{} (item {}, empty to finish)", l.message, items.len() + 1);
+        let mut text = inquire::Text::new(&message);
+        if let Some(help) = l.help_message {
+            text = text.with_help_message(help);
+        }
+        if l.key_value {
+            text = text.with_validator(|v: &str| {
+                Ok(if v.trim().is_empty() || key_value_item(v).is_some() {
+                    Validation::Valid
+                } else {
+                    Validation::Invalid(ErrorMessage::Custom("expected key=value".to_string()))
+                })
+            });
+        }
+        let item = text
+            .prompt_skippable()
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send>)?;
+        match item {
+            None if items.is_empty() => return Ok(None),
+            None => return Ok(Some(items)),
+            Some(item) if item.trim().is_empty() => {
+                if items.is_empty() {
+                    if let Some(default) = l.default {
+                        return Ok(Some(default));
+                    }
+                }
+                return Ok(Some(items));
+            }
+            Some(item) => items.push(item),
+        }
+    }
+}
+
+/// The answer for the items of a list: the items themselves, or the object they make
+/// up for a key/value list.
+fn list_answer(items: Vec<String>, key_value: bool) -> Answer {
+    if key_value {
+        Answer::Object(key_value_object(&items))
+    } else {
+        Answer::StringArray(items)
     }
 }
@@ -1,7 +1,9 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::process::exit;
 use std::rc::Rc;
 
+use inquire::InquireError;
+
 use crate::domain::models::answer::Answer;
 use crate::domain::models::question::Question;
 use crate::domain::models::question::QuestionTrait;
@@ -9,9 +11,10 @@ use crate::domain::models::template::input::TemplateAnswerInput;
 use crate::domain::models::template::output::TemplateOutput;
 use crate::domain::services::prompter::prompt;
 use crate::domain::services::repo::CyanRepo;
+use crate::domain::services::template::history::AnswerHistory;
 use crate::domain::services::template::redact;
 use crate::domain::services::template::states::TemplateState;
-use crate::domain::services::template::validate::add_template_validator;
+use crate::domain::services::template::validate::{add_template_validator, validate_answer};
 use crate::http::mapper::prompt_mapper;
 
 pub struct TemplateEngine {
@@ -23,18 +26,24 @@ impl TemplateEngine {
         TemplateEngine { client }
     }
 
+    /// Walk the template Q&A interactively.
+    ///
+    /// Esc on a question steps back to the previous one. Once the coordinator finalizes,
+    /// a review screen lists every answer given in this walk (passwords masked) and lets
+    /// the user reopen any of them; the walk then resumes from that question, replaying
+    /// later answers that the coordinator still asks for.
     pub fn start_with(
         &self,
         initial_answers: Option<HashMap<String, Answer>>,
         initial_states: Option<HashMap<String, String>>,
     ) -> TemplateState {
-        println!("TemplateEngine started");
+        println!("TemplateEngine started (press Esc to go back to the previous question)");
         let mut state = TemplateState::QnA();
 
         // Track answer
         let mut answers: HashMap<String, Answer> = initial_answers.unwrap_or_default();
         let mut state_data: HashMap<String, String> = initial_states.clone().unwrap_or_default();
-        let mut last_question_id: Option<String> = None;
+        let mut history = AnswerHistory::new();
 
         while state.cont() {
             let input = TemplateAnswerInput {
@@ -48,7 +57,25 @@ impl TemplateEngine {
                     TemplateOutput::QnA(q) => {
                         // Get ID from the Question struct using pattern matching
                         let question_id = &q.question.id();
-                        last_question_id = Some(question_id.clone());
+
+                        // An answer held back by an edit is replayed unprompted while it
+                        // is still valid for the question now asked.
+                        if let Some(held) = history.take_held(question_id) {
+                            if validate_answer(
+                                &q.question,
+                                &held,
+                                Rc::clone(&self.client),
+                                answers.clone(),
+                                state_data.clone(),
+                            )
+                            .is_ok()
+                            {
+                                history.record(&q.question, state_data.clone());
+                                answers.insert(question_id.clone(), held);
+                                state_data = q.deterministic_state;
+                                return Ok(TemplateState::QnA());
+                            }
+                        }
 
                         let ans = prompt_mapper(&q.question)
                             .map(|p| {
@@ -62,16 +89,17 @@ impl TemplateEngine {
                             .and_then(|p| prompt(p))
                             // handle responses
                             .map(|x| match x {
-                                // if skipped
-                                None => {
-                                    if answers.is_empty() {
+                                // if skipped, step back to the previous question
+                                None => match history.back(&mut answers) {
+                                    Some(previous) => state_data = previous,
+                                    None if answers.is_empty() => {
                                         println!("User aborted! Exiting...");
                                         exit(0)
-                                    } else if let Some(last_id) = &last_question_id {
-                                        answers.remove(last_id);
                                     }
-                                }
+                                    None => {}
+                                },
                                 Some(val) => {
+                                    history.record(&q.question, state_data.clone());
                                     answers.insert(question_id.clone(), val);
                                     state_data = q.deterministic_state;
                                 }
@@ -82,7 +110,25 @@ impl TemplateEngine {
                         }
                     }
                     TemplateOutput::Final(c) => {
-                        Ok(TemplateState::Complete(c.cyan, answers.clone()))
+                        if history.is_empty() {
+                            return Ok(TemplateState::Complete(
+                                c.cyan,
+                                answers.clone(),
+                                history.secrets(),
+                            ));
+                        }
+                        review(&history, &answers).map(|edit| match edit {
+                            Some(index) => {
+                                if let Some(previous) = history.edit(index, &mut answers) {
+                                    state_data = previous;
+                                }
+                                TemplateState::QnA()
+                            }
+                            None => {
+                                history.finish();
+                                TemplateState::Complete(c.cyan, answers.clone(), history.secrets())
+                            }
+                        })
                     }
                 });
 
@@ -137,7 +183,7 @@ impl TemplateEngine {
         // is the model-independent source of truth; see `validate_supplied_answers` for
         // why it (not the outcome walk's `walked` snapshot) is correct under both
         // coordinator models.
-        let revealed = match self.validate_supplied_answers(&answers) {
+        let (revealed, secrets) = match self.validate_supplied_answers(&answers) {
             Ok(revealed) => revealed,
             Err(msg) => return TemplateState::Err(msg),
         };
@@ -181,7 +227,7 @@ impl TemplateEngine {
                     // (and their secrets), so a stale value for a question that was never
                     // prompted cannot leak into persisted state and be silently reused on
                     // a later run where the branch changes.
-                    return TemplateState::Complete(c.cyan, revealed);
+                    return TemplateState::Complete(c.cyan, revealed, secrets);
                 }
                 Ok(TemplateOutput::QnA(q)) => {
                     let question_id = q.question.id();
@@ -292,15 +338,18 @@ impl TemplateEngine {
     /// excluded), mirroring the interactive `inquire` validator's pre-insertion
     /// snapshot. On failure returns `Err(message)` where the message references the
     /// question id only, never the offending value.
+    ///
+    /// Alongside the map it returns the ids in it that answered `Password` questions.
     fn validate_supplied_answers(
         &self,
         answers: &HashMap<String, Answer>,
-    ) -> Result<HashMap<String, Answer>, String> {
+    ) -> Result<(HashMap<String, Answer>, HashSet<String>), String> {
         if answers.is_empty() {
-            return Ok(HashMap::new());
+            return Ok((HashMap::new(), HashSet::new()));
         }
 
         let mut discovered: HashMap<String, Answer> = HashMap::new();
+        let mut secrets: HashSet<String> = HashSet::new();
         let mut state_data: HashMap<String, String> = HashMap::new();
         // Same in-flight secrecy tracking as the outcome walk: a `prompt_template`
         // transport error during discovery can echo any supplied value, so secret and
@@ -321,7 +370,7 @@ impl TemplateEngine {
                     // validated — the interactive path would never have prompted for
                     // them either — and are NOT in `discovered`, so they are dropped
                     // from the returned (persistable) set.
-                    return Ok(discovered);
+                    return Ok((discovered, secrets));
                 }
                 Ok(TemplateOutput::QnA(q)) => {
                     let question_id = q.question.id();
@@ -353,6 +402,9 @@ impl TemplateEngine {
                             )?;
                             // Valid — record it and thread deterministic state so the
                             // next call advances the walk to the following question.
+                            if matches!(q.question, Question::Password(_)) {
+                                secrets.insert(question_id.clone());
+                            }
                             discovered.insert(question_id, answer.clone());
                             state_data = q.deterministic_state;
                         }
@@ -364,7 +416,7 @@ impl TemplateEngine {
                             // unanswered question). They were never prompted for, so
                             // they are not validated — matching interactive behavior —
                             // and `discovered` holds only the revealed-so-far set.
-                            return Ok(discovered);
+                            return Ok((discovered, secrets));
                         }
                     }
                 }
@@ -384,6 +436,26 @@ impl TemplateEngine {
     }
 }
 
+/// Show the answers given in this walk and ask whether to accept them. Returns the
+/// index of the answer to reopen, or `None` to accept (Esc also accepts).
+fn review(
+    history: &AnswerHistory,
+    answers: &HashMap<String, Answer>,
+) -> Result<Option<usize>, Box<dyn std::error::Error + Send>> {
+    let mut options = vec!["Looks good, continue".to_string()];
+    options.extend(
+        history
+            .review_lines(answers)
+            .into_iter()
+            .map(|line| format!("Edit {line}")),
+    );
+    match inquire::Select::new("Review your answers", options).raw_prompt() {
+        Ok(picked) => Ok(picked.index.checked_sub(1)),
+        Err(InquireError::OperationCanceled) => Ok(None),
+        Err(e) => Err(Box::new(e) as Box<dyn std::error::Error + Send>),
+    }
+}
+
 /// Tracks the supplied answer values a headless walk has put in flight, split by
 /// secrecy, so a `prompt_template` transport/coordinator error can be redacted before it
 /// becomes the headless error envelope. A coordinator/transport error string can echo any
@@ -498,7 +570,7 @@ mod headless_tests {
     use super::*;
     use crate::domain::models::cyan::Cyan;
     use crate::domain::models::question::{
-        ConfirmQuestion, Question, SelectQuestion, TextQuestion,
+        ConfirmQuestion, Question, QuestionOption, SelectQuestion, TextQuestion,
     };
     use crate::domain::models::template::output::{TemplateFinalOutput, TemplateQnAOutput};
     use std::error::Error;
@@ -576,6 +648,7 @@ mod headless_tests {
             message: format!("{id}?"),
             desc: None,
             confirmation: None,
+            constraints: Default::default(),
             id: id.to_string(),
         })
     }
@@ -586,6 +659,7 @@ mod headless_tests {
             default: None,
             desc: None,
             initial: None,
+            constraints: Default::default(),
             id: id.to_string(),
         })
     }
@@ -595,7 +669,8 @@ mod headless_tests {
         Question::Select(SelectQuestion {
             message: format!("{id}?"),
             desc: None,
-            options: options.iter().map(|s| s.to_string()).collect(),
+            options: options.iter().map(|s| QuestionOption::from(*s)).collect(),
+            default: None,
             id: id.to_string(),
         })
     }
@@ -605,8 +680,9 @@ mod headless_tests {
         use crate::domain::models::question::CheckboxQuestion;
         Question::Checkbox(CheckboxQuestion {
             message: format!("{id}?"),
-            options: options.iter().map(|s| s.to_string()).collect(),
+            options: options.iter().map(|s| QuestionOption::from(*s)).collect(),
             desc: None,
+            default: None,
             id: id.to_string(),
         })
     }
@@ -682,7 +758,7 @@ mod headless_tests {
         answers.insert("db_name".to_string(), Answer::String("mydb".to_string()));
         let state = engine.start_headless(Some(answers));
         assert!(
-            matches!(state, TemplateState::Complete(_, _)),
+            matches!(state, TemplateState::Complete(..)),
             "all answers present must complete"
         );
     }
@@ -694,7 +770,35 @@ mod headless_tests {
         let mut answers = HashMap::new();
         answers.insert("use_db".to_string(), Answer::Bool(false));
         let state = engine.start_headless(Some(answers));
-        assert!(matches!(state, TemplateState::Complete(_, _)));
+        assert!(matches!(state, TemplateState::Complete(..)));
+    }
+
+    // A completed headless walk reports which answers were given to Password questions.
+    #[test]
+    fn complete_reports_password_answers_as_secrets() {
+        let engine = TemplateEngine {
+            client: Rc::new(FakeRepo {
+                responder: Box::new(|input: &TemplateAnswerInput| {
+                    if !input.answers.contains_key("name") {
+                        return Ok(qna(text("name"), "name"));
+                    }
+                    if !input.answers.contains_key("token") {
+                        return Ok(qna(password("token"), "token"));
+                    }
+                    Ok(final_output())
+                }),
+            }),
+        };
+        let mut answers = HashMap::new();
+        answers.insert("name".to_string(), Answer::String("demo".to_string()));
+        answers.insert("token".to_string(), Answer::String("s3cr3t".to_string()));
+        match engine.start_headless(Some(answers)) {
+            TemplateState::Complete(_, persisted, secrets) => {
+                assert_eq!(persisted.len(), 2);
+                assert_eq!(secrets, HashSet::from(["token".to_string()]));
+            }
+            _ => panic!("all answers present must complete"),
+        }
     }
 
     // Transport / coordinator errors surface as Err (FR9).
@@ -731,6 +835,7 @@ mod headless_tests {
                         message: "API token?".to_string(),
                         desc: None,
                         confirmation: None,
+                        constraints: Default::default(),
                         id: "token".to_string(),
                     }),
                     "token",
@@ -773,6 +878,7 @@ mod headless_tests {
                         message: "API token?".to_string(),
                         desc: None,
                         confirmation: None,
+                        constraints: Default::default(),
                         id: "token".to_string(),
                     }),
                     "token",
@@ -821,6 +927,7 @@ mod headless_tests {
                         message: "API token?".to_string(),
                         desc: None,
                         confirmation: None,
+                        constraints: Default::default(),
                         id: "token".to_string(),
                     }),
                     "token",
@@ -865,6 +972,7 @@ mod headless_tests {
                         message: "API token?".to_string(),
                         desc: None,
                         confirmation: None,
+                        constraints: Default::default(),
                         id: "token".to_string(),
                     }),
                     "token",
@@ -911,6 +1019,7 @@ mod headless_tests {
                         message: "API token?".to_string(),
                         desc: None,
                         confirmation: None,
+                        constraints: Default::default(),
                         id: "token".to_string(),
                     }),
                     "token",
@@ -962,6 +1071,7 @@ mod headless_tests {
                         message: "API token?".to_string(),
                         desc: None,
                         confirmation: None,
+                        constraints: Default::default(),
                         id: "token".to_string(),
                     }),
                     "token",
@@ -1172,7 +1282,7 @@ mod headless_tests {
         let mut answers = HashMap::new();
         answers.insert("q1".to_string(), Answer::Bool(true));
         let state = engine.start_headless(Some(answers));
-        assert!(matches!(state, TemplateState::Complete(_, _)));
+        assert!(matches!(state, TemplateState::Complete(..)));
     }
 
     // AC3 (FR9): an answer that FAILS the coordinator's validation is surfaced as
@@ -1204,7 +1314,7 @@ mod headless_tests {
         good.insert("name".to_string(), Answer::String("good".to_string()));
         assert!(matches!(
             engine.start_headless(Some(good)),
-            TemplateState::Complete(_, _)
+            TemplateState::Complete(..)
         ));
 
         // An invalid answer is rejected as Err, referencing the id, never the value.
@@ -1276,7 +1386,7 @@ mod headless_tests {
         assert!(
             matches!(
                 engine_ok.start_headless(Some(good)),
-                TemplateState::Complete(_, _)
+                TemplateState::Complete(..)
             ),
             "valid answers must complete even though the coordinator never re-emits them"
         );
@@ -1400,7 +1510,7 @@ mod headless_tests {
         good.insert("q1".to_string(), Answer::String("good".to_string()));
         assert!(matches!(
             engine_ok.start_headless(Some(good)),
-            TemplateState::Complete(_, _)
+            TemplateState::Complete(..)
         ));
     }
 
@@ -1423,7 +1533,7 @@ mod headless_tests {
         assert!(
             matches!(
                 engine.start_headless(Some(answers)),
-                TemplateState::Complete(_, _)
+                TemplateState::Complete(..)
             ),
             "answers for a template that asks nothing are unused, not an error"
         );
@@ -1443,7 +1553,7 @@ mod headless_tests {
         assert!(
             matches!(
                 engine.start_headless(Some(answers)),
-                TemplateState::Complete(_, _)
+                TemplateState::Complete(..)
             ),
             "a pre-supplied answer for an untaken branch must not block completion"
         );
@@ -1473,6 +1583,7 @@ mod headless_tests {
                             message: "Password?".to_string(),
                             desc: None,
                             confirmation: None,
+                            constraints: Default::default(),
                             id: "pw".to_string(),
                         }),
                         "pw",
@@ -1648,7 +1759,8 @@ mod headless_tests {
         let q = Question::Select(SelectQuestion {
             message: "env?".to_string(),
             desc: None,
-            options: vec!["dev".to_string(), "prod".to_string()],
+            options: vec!["dev".into(), "prod".into()],
+            default: None,
             id: "env".to_string(),
         });
 
@@ -1691,7 +1803,8 @@ mod headless_tests {
         use crate::domain::models::question::CheckboxQuestion;
         let q = Question::Checkbox(CheckboxQuestion {
             message: "features?".to_string(),
-            options: vec!["auth".to_string(), "billing".to_string()],
+            options: vec!["auth".into(), "billing".into()],
+            default: None,
             desc: None,
             id: "features".to_string(),
         });
@@ -1845,7 +1958,7 @@ mod headless_tests {
         assert!(
             matches!(
                 engine_ok.start_headless(Some(good)),
-                TemplateState::Complete(_, _)
+                TemplateState::Complete(..)
             ),
             "a Select member value must complete the headless walk"
         );
@@ -1889,7 +2002,7 @@ mod headless_tests {
             Answer::String(stale_secret.to_string()),
         );
         let persisted = match engine.start_headless(Some(answers)) {
-            TemplateState::Complete(_, persisted) => persisted,
+            TemplateState::Complete(_, persisted, _) => persisted,
             other => panic!("expected Complete, got {}", state_variant_name(&other)),
         };
 
@@ -1933,7 +2046,7 @@ mod headless_tests {
         // Over-supply a ghost answer the coordinator never asks about.
         answers_fu.insert("ghost".to_string(), Answer::String("unused".to_string()));
         match engine_fu.start_headless(Some(answers_fu)) {
-            TemplateState::Complete(_, persisted) => {
+            TemplateState::Complete(_, persisted, _) => {
                 assert_eq!(
                     persisted.len(),
                     1,
@@ -1971,7 +2084,7 @@ mod headless_tests {
         answers_seq.insert("name".to_string(), Answer::String("real".to_string()));
         answers_seq.insert("ghost".to_string(), Answer::String("unused".to_string()));
         match engine_seq.start_headless(Some(answers_seq)) {
-            TemplateState::Complete(_, persisted) => {
+            TemplateState::Complete(_, persisted, _) => {
                 assert!(
                     !persisted.contains_key("ghost"),
                     "oversupply must be dropped even in the sequence model: {persisted:?}"
@@ -2006,7 +2119,7 @@ mod headless_tests {
         answers.insert("q1".to_string(), Answer::String("a1".to_string()));
         answers.insert("q2".to_string(), Answer::String("a2".to_string()));
         let persisted = match engine.start_headless(Some(answers)) {
-            TemplateState::Complete(_, persisted) => persisted,
+            TemplateState::Complete(_, persisted, _) => persisted,
             other => panic!("expected Complete, got {}", state_variant_name(&other)),
         };
         assert_eq!(
@@ -2042,7 +2155,7 @@ mod headless_tests {
     fn state_variant_name(state: &TemplateState) -> &'static str {
         match state {
             TemplateState::QnA() => "QnA",
-            TemplateState::Complete(_, _) => "Complete",
+            TemplateState::Complete(..) => "Complete",
             TemplateState::NeedInput(_, _) => "NeedInput",
             TemplateState::Err(_) => "Err",
         }
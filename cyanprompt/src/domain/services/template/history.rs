@@ -0,0 +1,239 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::domain::models::answer::Answer;
+use crate::domain::models::question::{Question, QuestionTrait};
+
+/// Rendering shown in place of a `Password` answer on the review screen.
+const MASK: &str = "********";
+
+/// A question the interactive walk asked and got an answer for.
+struct Asked {
+    id: String,
+    message: String,
+    secret: bool,
+    /// The deterministic state the coordinator was sent when it asked this question, so
+    /// the walk can resume from exactly that point.
+    state_before: HashMap<String, String>,
+}
+
+/// The questions an interactive walk has answered, in order.
+///
+/// The coordinator is stateless: it replays the answers it is sent and asks the first
+/// question it has no answer for. Stepping back or editing is therefore just dropping
+/// answers and restoring the deterministic state from before the reopened question; the
+/// next `prompt_template` call asks that question again.
+pub(crate) struct AnswerHistory {
+    asked: Vec<Asked>,
+    /// Answers to questions after an edited one. They are replayed without prompting
+    /// if the walk asks the same question again, and dropped otherwise (their branch
+    /// was not taken).
+    held: HashMap<String, Answer>,
+}
+
+impl AnswerHistory {
+    pub fn new() -> Self {
+        Self {
+            asked: Vec::new(),
+            held: HashMap::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.asked.is_empty()
+    }
+
+    /// Record that `question` was answered while the coordinator held `state_before`.
+    pub fn record(&mut self, question: &Question, state_before: HashMap<String, String>) {
+        self.asked.push(Asked {
+            id: question.id(),
+            message: question_message(question),
+            secret: matches!(question, Question::Password(_)),
+            state_before,
+        });
+    }
+
+    /// Forget the most recent answer. Returns the deterministic state to resume from, or
+    /// `None` when nothing has been answered in this walk.
+    pub fn back(
+        &mut self,
+        answers: &mut HashMap<String, Answer>,
+    ) -> Option<HashMap<String, String>> {
+        let last = self.asked.pop()?;
+        answers.remove(&last.id);
+        Some(last.state_before)
+    }
+
+    /// Reopen the answer at `index` for editing. Later answers are held back for replay.
+    /// Returns the deterministic state to resume from, or `None` for an unknown index.
+    pub fn edit(
+        &mut self,
+        index: usize,
+        answers: &mut HashMap<String, Answer>,
+    ) -> Option<HashMap<String, String>> {
+        if index >= self.asked.len() {
+            return None;
+        }
+        let mut later = self.asked.split_off(index);
+        let reopened = later.remove(0);
+        answers.remove(&reopened.id);
+        for asked in later {
+            if let Some(answer) = answers.remove(&asked.id) {
+                self.held.insert(asked.id, answer);
+            }
+        }
+        Some(reopened.state_before)
+    }
+
+    /// Take the held answer for question `id`, if an edit set one aside.
+    pub fn take_held(&mut self, id: &str) -> Option<Answer> {
+        self.held.remove(id)
+    }
+
+    /// Drop held answers the walk never asked for again.
+    pub fn finish(&mut self) {
+        self.held.clear();
+    }
+
+    /// The ids of the `Password` questions answered in this walk.
+    pub fn secrets(&self) -> HashSet<String> {
+        self.asked
+            .iter()
+            .filter(|a| a.secret)
+            .map(|a| a.id.clone())
+            .collect()
+    }
+
+    /// One `message: answer` line per answered question, in order, with `Password`
+    /// answers masked.
+    pub fn review_lines(&self, answers: &HashMap<String, Answer>) -> Vec<String> {
+        self.asked
+            .iter()
+            .map(|a| {
+                let shown = match answers.get(&a.id) {
+                    Some(_) if a.secret => MASK.to_string(),
+                    Some(answer) => review_rendering(answer),
+                    None => String::new(),
+                };
+                format!("{}: {shown}", a.message)
+            })
+            .collect()
+    }
+}
+
+fn review_rendering(answer: &Answer) -> String {
+    match answer {
+        Answer::String(s) => s.clone(),
+        Answer::StringArray(arr) => arr.join(", "),
+        Answer::Bool(true) => "yes".to_string(),
+        Answer::Bool(false) => "no".to_string(),
+        Answer::Number(n) => n.to_string(),
+        Answer::Object(o) => serde_json::Value::Object(o.clone()).to_string(),
+    }
+}
+
+fn question_message(question: &Question) -> String {
+    match question {
+        Question::Confirm(q) => q.message.clone(),
+        Question::Date(q) => q.message.clone(),
+        Question::Checkbox(q) => q.message.clone(),
+        Question::Password(q) => q.message.clone(),
+        Question::Text(q) => q.message.clone(),
+        Question::Select(q) => q.message.clone(),
+        Question::Number(q) => q.message.clone(),
+        Question::Editor(q) => q.message.clone(),
+        Question::Path(q) => q.message.clone(),
+        Question::List(q) => q.message.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::question::{PasswordQuestion, TextQuestion};
+
+    fn text(id: &str) -> Question {
+        Question::Text(TextQuestion {
+            message: format!("{id}?"),
+            default: None,
+            desc: None,
+            initial: None,
+            constraints: Default::default(),
+            id: id.to_string(),
+        })
+    }
+
+    fn password(id: &str) -> Question {
+        Question::Password(PasswordQuestion {
+            message: format!("{id}?"),
+            desc: None,
+            confirmation: None,
+            constraints: Default::default(),
+            id: id.to_string(),
+        })
+    }
+
+    fn state(step: &str) -> HashMap<String, String> {
+        HashMap::from([("step".to_string(), step.to_string())])
+    }
+
+    /// Answer `questions` in order, each under the state named after its index.
+    fn walk(questions: &[Question]) -> (AnswerHistory, HashMap<String, Answer>) {
+        let mut history = AnswerHistory::new();
+        let mut answers = HashMap::new();
+        for (i, q) in questions.iter().enumerate() {
+            history.record(q, state(&i.to_string()));
+            answers.insert(q.id(), Answer::String(format!("{}-answer", q.id())));
+        }
+        (history, answers)
+    }
+
+    #[test]
+    fn back_drops_the_last_answer_and_restores_its_state() {
+        let (mut history, mut answers) = walk(&[text("a"), text("b")]);
+        assert_eq!(history.back(&mut answers), Some(state("1")));
+        assert!(!answers.contains_key("b"));
+        assert!(answers.contains_key("a"));
+        assert_eq!(history.back(&mut answers), Some(state("0")));
+        assert!(answers.is_empty());
+        assert_eq!(history.back(&mut answers), None);
+    }
+
+    #[test]
+    fn edit_reopens_one_answer_and_holds_later_ones_for_replay() {
+        let (mut history, mut answers) = walk(&[text("a"), text("b"), text("c")]);
+        assert_eq!(history.edit(1, &mut answers), Some(state("1")));
+        assert_eq!(answers.keys().collect::<Vec<_>>(), vec!["a"]);
+        assert_eq!(history.review_lines(&answers).len(), 1);
+        assert_eq!(
+            history.take_held("b"),
+            None,
+            "the edited answer is not held"
+        );
+        assert_eq!(
+            history.take_held("c"),
+            Some(Answer::String("c-answer".to_string()))
+        );
+        assert_eq!(history.edit(5, &mut answers), None);
+    }
+
+    #[test]
+    fn finish_drops_answers_that_were_never_asked_again() {
+        let (mut history, mut answers) = walk(&[text("a"), text("b")]);
+        history.edit(0, &mut answers);
+        history.finish();
+        assert_eq!(history.take_held("b"), None);
+    }
+
+    #[test]
+    fn review_masks_passwords_and_tracks_secrets() {
+        let (history, answers) = walk(&[text("name"), password("token")]);
+        assert_eq!(
+            history.review_lines(&answers),
+            vec![
+                "name?: name-answer".to_string(),
+                "token?: ********".to_string()
+            ]
+        );
+        assert_eq!(history.secrets(), HashSet::from(["token".to_string()]));
+    }
+}
@@ -5,3 +5,5 @@ pub mod engine;
 pub mod validate;
 
 pub(crate) mod redact;
+
+pub(crate) mod history;
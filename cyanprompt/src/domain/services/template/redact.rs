@@ -29,12 +29,32 @@ use crate::domain::models::answer::Answer;
 /// The secrecy contract forbids echoing a supplied answer VALUE, and a boolean has none.
 /// Empty strings are skipped — the substring check treats `""` as matching everything and
 /// it leaks nothing, so tracking it would only force spurious redaction.
+///
+/// Numbers ARE tracked, in the rendering the prompt forwards (`8080`, `4242.5`): unlike
+/// a boolean, a number can be private (a PIN, an account id). Renderings shorter than
+/// [`MIN_TRACKED_NUMBER_LEN`] are not: a count or a choice such as `1` is no secret, and
+/// it occurs in nearly every error (`HTTP 500`). An `Object` is tracked by its string and
+/// number leaves, under the same rules, since a message echoing the object would embed
+/// them.
 pub(crate) fn answer_renderings(answer: &Answer) -> Vec<String> {
     match answer {
         Answer::String(value) => non_empty(value),
         Answer::StringArray(arr) => arr.iter().flat_map(|s| non_empty(s)).collect(),
         // Booleans are intentionally NOT tracked — see the doc comment above.
         Answer::Bool(_) => Vec::new(),
+        Answer::Number(n) => number_rendering(n.to_string()),
+        Answer::Object(map) => map.values().flat_map(json_leaf_renderings).collect(),
+    }
+}
+
+/// The string and number leaves of a JSON value; booleans and nulls are skipped.
+fn json_leaf_renderings(value: &serde_json::Value) -> Vec<String> {
+    match value {
+        serde_json::Value::String(s) => non_empty(s),
+        serde_json::Value::Number(n) => number_rendering(n.to_string()),
+        serde_json::Value::Array(arr) => arr.iter().flat_map(json_leaf_renderings).collect(),
+        serde_json::Value::Object(map) => map.values().flat_map(json_leaf_renderings).collect(),
+        serde_json::Value::Bool(_) | serde_json::Value::Null => Vec::new(),
     }
 }
 
@@ -46,6 +66,17 @@ pub(crate) fn answer_map_renderings(answers: &HashMap<String, Answer>) -> Vec<St
     answers.values().flat_map(answer_renderings).collect()
 }
 
+/// Shortest number rendering [`answer_renderings`] tracks.
+const MIN_TRACKED_NUMBER_LEN: usize = 4;
+
+fn number_rendering(rendered: String) -> Vec<String> {
+    if rendered.len() < MIN_TRACKED_NUMBER_LEN {
+        Vec::new()
+    } else {
+        vec![rendered]
+    }
+}
+
 fn non_empty(s: &str) -> Vec<String> {
     if s.is_empty() {
         Vec::new()
@@ -236,6 +267,40 @@ mod tests {
         assert!(answer_renderings(&Answer::Bool(false)).is_empty());
     }
 
+    #[test]
+    fn answer_renderings_tracks_numbers_and_object_leaves() {
+        assert_eq!(
+            answer_renderings(&Answer::Number(4242.0)),
+            vec!["4242".to_string()]
+        );
+        let serde_json::Value::Object(map) = serde_json::json!({
+            "host": "db.internal",
+            "port": 5432,
+            "tls": true,
+            "tags": ["a", ""],
+        }) else {
+            unreachable!()
+        };
+        let mut got = answer_renderings(&Answer::Object(map));
+        got.sort();
+        assert_eq!(got, vec!["5432", "a", "db.internal"]);
+    }
+
+    #[test]
+    fn short_numbers_do_not_blank_unrelated_errors() {
+        let tracked = answer_renderings(&Answer::Number(1.0));
+        assert!(tracked.is_empty());
+        assert!(
+            !tracked
+                .iter()
+                .any(|v| value_echoed("coordinator returned HTTP 500", v))
+        );
+        let serde_json::Value::Object(map) = serde_json::json!({ "replicas": 3 }) else {
+            unreachable!()
+        };
+        assert!(answer_renderings(&Answer::Object(map)).is_empty());
+    }
+
     #[test]
     fn answer_map_renderings_flattens_every_value() {
         let mut map = HashMap::new();
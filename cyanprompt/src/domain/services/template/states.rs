@@ -1,12 +1,14 @@
 use crate::domain::models::answer::Answer;
 use crate::domain::models::cyan::Cyan;
 use crate::domain::models::question::Question;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Clone)]
 pub enum TemplateState {
     QnA(),
-    Complete(Cyan, HashMap<String, Answer>),
+    /// The template finished: its output, its answers, and the ids of the answers given
+    /// to `Password` questions.
+    Complete(Cyan, HashMap<String, Answer>, HashSet<String>),
     /// Headless replay reached a question that has no supplied answer yet.
     ///
     /// Carries the unanswered `Question` and the deterministic state accumulated
@@ -21,7 +23,7 @@ impl TemplateState {
     pub fn cont(&self) -> bool {
         match self {
             TemplateState::QnA() => true,
-            TemplateState::Complete(_, _) => false,
+            TemplateState::Complete(..) => false,
             TemplateState::NeedInput(_, _) => false,
             TemplateState::Err(_) => false,
         }
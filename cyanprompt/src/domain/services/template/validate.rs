@@ -1,7 +1,10 @@
 use crate::domain::models::answer::Answer;
 use crate::domain::models::prompt::Prompts;
+use crate::domain::models::question::PasswordQuestion;
 use crate::domain::models::question::Question;
+use crate::domain::models::question::QuestionOption;
 use crate::domain::models::question::QuestionTrait;
+use crate::domain::models::question::TextQuestion;
 use crate::domain::models::template::input::TemplateValidateInput;
 use crate::domain::services::repo::CyanRepo;
 use crate::domain::services::template::redact;
@@ -42,16 +45,23 @@ fn validate_template(
 ///   unconditionally, mirroring the interactive path.
 /// - `Err(id-only message)` — the answer's [`Answer`] discriminant does NOT match the
 ///   question kind (e.g. a `Text` question answered with `Bool`), a `Date` value does
-///   not parse as `%Y-%m-%d` or falls outside `min_date`/`max_date`, or a
-///   `Select`/`Checkbox` value is not among the question's `options`. Headless must
+///   not parse as `%Y-%m-%d` or falls outside `min_date`/`max_date`, a `Text`/`Password`
+///   value breaks the question's declared constraints, or a `Select`/`Checkbox` value is
+///   not among the question's `options`. Headless must
 ///   reject shapes the interactive prompt could never produce, rather than silently
 ///   accepting them. The message references the question id only — never the offending
 ///   value.
 fn validateable_value(question: &Question, answer: &Answer) -> Result<Option<String>, String> {
     match (question, answer) {
-        // Text/Password: a raw string is forwarded to the coordinator validator.
-        (Question::Text(_), Answer::String(s)) | (Question::Password(_), Answer::String(s)) => {
-            Ok(Some(s.clone()))
+        // Text/Password: a raw string that meets the question's declared constraints
+        // is forwarded to the coordinator validator; one that does not is rejected
+        // locally, as the interactive prompt would, without a coordinator round-trip.
+        (Question::Text(TextQuestion { constraints, .. }), Answer::String(s))
+        | (Question::Password(PasswordQuestion { constraints, .. }), Answer::String(s)) => {
+            match constraints.violation(s) {
+                None => Ok(Some(s.clone())),
+                Some(_) => Err(format!("invalid answer for question '{}'", question.id())),
+            }
         }
         (Question::Date(date), Answer::String(s)) => {
             // The interactive Date validator formats the picked NaiveDate as %Y-%m-%d
@@ -88,32 +98,57 @@ fn validateable_value(question: &Question, answer: &Answer) -> Result<Option<Str
             }
             Ok(Some(d.format("%Y-%m-%d").to_string()))
         }
-        // The interactive Select picker only ever yields one of the question's
-        // `options` — a value outside that set can never be selected. Reject it here
+        // The interactive Select picker only ever yields the `value` of one of the
+        // question's enabled `options` — anything else (a label, a disabled option's
+        // value) can never be selected. Reject it here
         // (id-only message) before accepting.
         (Question::Select(select), Answer::String(s)) => {
-            if select.options.iter().any(|opt| opt == s) {
+            if QuestionOption::is_selectable(&select.options, s) {
                 Ok(None)
             } else {
                 Err(format!("invalid answer for question '{}'", question.id()))
             }
         }
-        // The interactive Checkbox (MultiSelect) picker only ever yields a subset of
-        // the question's `options` — a value outside that set can never be selected.
+        // The interactive Checkbox (MultiSelect) picker only ever yields values of a
+        // subset of the question's enabled `options` — anything else can never be
+        // selected.
         // Reject any element that is not a member (id-only message).
         (Question::Checkbox(checkbox), Answer::StringArray(arr)) => {
             if arr
                 .iter()
-                .all(|s| checkbox.options.iter().any(|opt| opt == s))
+                .all(|s| QuestionOption::is_selectable(&checkbox.options, s))
             {
                 Ok(None)
             } else {
                 Err(format!("invalid answer for question '{}'", question.id()))
             }
         }
+        (Question::Editor(_), Answer::String(s)) => Ok(Some(s.clone())),
+        // The interactive Number prompt only yields a finite number within min/max/step,
+        // validated in `f64`'s Display rendering — forward the same rendering.
+        (Question::Number(number), Answer::Number(v)) => {
+            if number.accepts(*v) {
+                Ok(Some(v.to_string()))
+            } else {
+                Err(format!("invalid answer for question '{}'", question.id()))
+            }
+        }
+        // The interactive Path prompt rejects a path violating must_exist/dir.
+        (Question::Path(path), Answer::String(s)) => match path.violation(s) {
+            None => Ok(Some(s.clone())),
+            Some(_) => Err(format!("invalid answer for question '{}'", question.id())),
+        },
         // Confirm has no structural constraint beyond the Bool discriminant — accepted
         // unconditionally (mirrors the interactive path's `default => default` arm).
         (Question::Confirm(_), Answer::Bool(_)) => Ok(None),
+        // A List is free-form items with no interactive validator; a key/value List
+        // answers with an object of strings instead.
+        (Question::List(list), Answer::StringArray(_)) if !list.is_key_value() => Ok(None),
+        (Question::List(list), Answer::Object(o))
+            if list.is_key_value() && o.values().all(|v| v.is_string()) =>
+        {
+            Ok(None)
+        }
         // Any other (question, answer) pairing is a type mismatch the interactive prompt
         // could never produce — reject with an id-only message.
         _ => Err(format!("invalid answer for question '{}'", question.id())),
@@ -262,6 +297,22 @@ pub fn add_template_validator(
                 deterministic_state.clone(),
             )
         })),
+        Prompts::Number(n) => Prompts::Number(n.with_validator(move |v: &f64| {
+            validate_template(
+                v.to_string().as_str(),
+                Rc::clone(&repo),
+                answers.clone(),
+                deterministic_state.clone(),
+            )
+        })),
+        Prompts::Editor(e) => Prompts::Editor(e.with_validator(move |v: &str| {
+            validate_template(
+                v,
+                Rc::clone(&repo),
+                answers.clone(),
+                deterministic_state.clone(),
+            )
+        })),
         default => default,
     }
 }
@@ -270,7 +321,8 @@ pub fn add_template_validator(
 mod tests {
     use super::*;
     use crate::domain::models::question::{
-        CheckboxQuestion, ConfirmQuestion, DateQuestion, PasswordQuestion, SelectQuestion,
+        CheckboxQuestion, ConfirmQuestion, DateQuestion, ListQuestion, NumberQuestion,
+        PasswordQuestion, PathQuestion, QuestionOption, SelectQuestion, TextConstraints,
         TextQuestion,
     };
     use crate::domain::models::template::output::TemplateOutput;
@@ -324,7 +376,8 @@ mod tests {
         Question::Select(SelectQuestion {
             message: "m".into(),
             desc: None,
-            options: opts.iter().map(|s| s.to_string()).collect(),
+            options: opts.iter().map(|s| QuestionOption::from(*s)).collect(),
+            default: None,
             id: id.into(),
         })
     }
@@ -332,8 +385,9 @@ mod tests {
     fn checkbox_q(id: &str, opts: &[&str]) -> Question {
         Question::Checkbox(CheckboxQuestion {
             message: "m".into(),
-            options: opts.iter().map(|s| s.to_string()).collect(),
+            options: opts.iter().map(|s| QuestionOption::from(*s)).collect(),
             desc: None,
+            default: None,
             id: id.into(),
         })
     }
@@ -355,6 +409,7 @@ mod tests {
             default: None,
             desc: None,
             initial: None,
+            constraints: Default::default(),
             id: id.into(),
         })
     }
@@ -374,6 +429,7 @@ mod tests {
             message: "m".into(),
             desc: None,
             confirmation: None,
+            constraints: Default::default(),
             id: id.into(),
         })
     }
@@ -414,6 +470,48 @@ mod tests {
         );
     }
 
+    /// Select/Checkbox answers are matched against option values — never labels — and
+    /// a disabled option's value is rejected like any value the picker could not yield.
+    #[test]
+    fn options_match_on_value_and_skip_disabled() {
+        let options = vec![
+            QuestionOption {
+                value: "pg".into(),
+                label: "PostgreSQL".into(),
+                description: Some("recommended".into()),
+                disabled: false,
+            },
+            QuestionOption {
+                value: "my".into(),
+                label: "MySQL".into(),
+                description: None,
+                disabled: true,
+            },
+        ];
+        let select = Question::Select(SelectQuestion {
+            message: "m".into(),
+            desc: None,
+            options: options.clone(),
+            default: None,
+            id: "db".into(),
+        });
+        let s = |v: &str| Answer::String(v.into());
+        assert!(matches!(validateable_value(&select, &s("pg")), Ok(None)));
+        assert!(validateable_value(&select, &s("PostgreSQL")).is_err());
+        assert!(validateable_value(&select, &s("my")).is_err());
+
+        let checkbox = Question::Checkbox(CheckboxQuestion {
+            message: "m".into(),
+            options,
+            desc: None,
+            default: None,
+            id: "dbs".into(),
+        });
+        let arr = |v: &[&str]| Answer::StringArray(v.iter().map(|s| s.to_string()).collect());
+        assert!(validateable_value(&checkbox, &arr(&["pg"])).is_ok());
+        assert!(validateable_value(&checkbox, &arr(&["pg", "my"])).is_err());
+    }
+
     /// A Checkbox answered with an empty array is a valid (zero-selection) subset of
     /// any options — type-aligned, accepted.
     #[test]
@@ -520,6 +618,163 @@ mod tests {
         );
     }
 
+    fn number_q(id: &str, min: Option<f64>, max: Option<f64>, step: Option<f64>) -> Question {
+        Question::Number(NumberQuestion {
+            message: "m".into(),
+            desc: None,
+            default: None,
+            min,
+            max,
+            step,
+            id: id.into(),
+        })
+    }
+
+    /// A Number answer is range- and step-checked locally, then forwarded in the
+    /// rendering the interactive prompt would send (`8080.0` → `8080`).
+    #[test]
+    fn number_is_checked_against_min_max_and_step() {
+        let q = number_q("port", Some(1000.0), Some(9000.0), Some(10.0));
+        let res = validateable_value(&q, &Answer::Number(8080.0));
+        assert!(matches!(res, Ok(Some(ref v)) if v == "8080"), "{res:?}");
+        for bad in [999.0, 9010.0, 8085.0, f64::NAN] {
+            assert!(
+                validateable_value(&q, &Answer::Number(bad)).is_err(),
+                "{bad} must be rejected"
+            );
+        }
+        // A number given as a string is a type mismatch.
+        assert!(validateable_value(&q, &Answer::String("8080".into())).is_err());
+    }
+
+    /// A Path answer must satisfy must_exist/dir.
+    #[test]
+    fn path_is_checked_against_must_exist_and_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("f.txt");
+        std::fs::write(&file, "x").unwrap();
+        let q = Question::Path(PathQuestion {
+            message: "m".into(),
+            desc: None,
+            default: None,
+            must_exist: Some(true),
+            dir: Some(true),
+            id: "p".into(),
+        });
+        let as_answer = |p: &std::path::Path| Answer::String(p.to_string_lossy().into_owned());
+        assert!(validateable_value(&q, &as_answer(dir.path())).is_ok());
+        assert!(validateable_value(&q, &as_answer(&file)).is_err());
+        assert!(validateable_value(&q, &as_answer(&dir.path().join("missing"))).is_err());
+    }
+
+    /// A List takes a string array and nothing else.
+    #[test]
+    fn list_requires_string_array() {
+        let q = Question::List(ListQuestion {
+            message: "m".into(),
+            desc: None,
+            default: None,
+            key_value: None,
+            id: "l".into(),
+        });
+        assert!(matches!(
+            validateable_value(&q, &Answer::StringArray(vec!["a".into()])),
+            Ok(None)
+        ));
+        assert!(validateable_value(&q, &Answer::String("a".into())).is_err());
+        let object = |v: serde_json::Value| Answer::Object(v.as_object().unwrap().clone());
+        assert!(validateable_value(&q, &object(serde_json::json!({"k": "v"}))).is_err());
+    }
+
+    /// A key/value List takes an object of strings and nothing else.
+    #[test]
+    fn key_value_list_requires_object_of_strings() {
+        let q = Question::List(ListQuestion {
+            message: "m".into(),
+            desc: None,
+            default: None,
+            key_value: Some(true),
+            id: "l".into(),
+        });
+        let object = |v: serde_json::Value| Answer::Object(v.as_object().unwrap().clone());
+        assert!(matches!(
+            validateable_value(&q, &object(serde_json::json!({"PORT": "80"}))),
+            Ok(None)
+        ));
+        assert!(validateable_value(&q, &object(serde_json::json!({"PORT": 80}))).is_err());
+        assert!(validateable_value(&q, &Answer::StringArray(vec!["PORT=80".into()])).is_err());
+    }
+
+    /// Declared Text constraints are enforced locally: each violation is rejected
+    /// with an id-only message; a conforming value is forwarded.
+    #[test]
+    fn text_constraints_are_checked_locally() {
+        let q = Question::Text(TextQuestion {
+            message: "m".into(),
+            default: None,
+            desc: None,
+            initial: None,
+            constraints: TextConstraints {
+                pattern: Some("[a-z][a-z0-9-]*".into()),
+                min_length: Some(3),
+                max_length: Some(8),
+                charset: Some("abcdefghijklmnopqrstuvwxyz0123456789-".into()),
+                required: Some(true),
+            },
+            id: "name".into(),
+        });
+        let ok = validateable_value(&q, &Answer::String("my-app".into()));
+        assert!(matches!(ok, Ok(Some(ref v)) if v == "my-app"), "{ok:?}");
+        for bad in ["", "  ", "ab", "much-too-long", "My-App", "1app", "a_pp"] {
+            let res = validateable_value(&q, &Answer::String(bad.into()));
+            assert_eq!(
+                res,
+                Err("invalid answer for question 'name'".to_string()),
+                "{bad:?} must be rejected"
+            );
+        }
+    }
+
+    /// A Password failing its constraints is rejected before the coordinator is
+    /// consulted, and the message never echoes the secret.
+    #[test]
+    fn password_constraint_violation_skips_coordinator_and_never_echoes_secret() {
+        let q = Question::Password(PasswordQuestion {
+            message: "m".into(),
+            desc: None,
+            confirmation: None,
+            constraints: TextConstraints {
+                min_length: Some(12),
+                ..Default::default()
+            },
+            id: "token".into(),
+        });
+        let repo: Rc<dyn CyanRepo> = Rc::new(ValidatorRepo {
+            validate: Box::new(|_| unreachable!("constraints are checked before the coordinator")),
+        });
+        let res = validate_answer(
+            &q,
+            &Answer::String("hunter2".into()),
+            repo,
+            HashMap::new(),
+            HashMap::new(),
+        );
+        let msg = res.unwrap_err();
+        assert!(msg.contains("token"));
+        assert!(!msg.contains("hunter2"), "secret leaked: {msg}");
+    }
+
+    /// An unparseable pattern rejects every value rather than accepting any.
+    #[test]
+    fn invalid_pattern_rejects_every_value() {
+        let constraints = TextConstraints {
+            pattern: Some("(".into()),
+            ..Default::default()
+        };
+        assert!(constraints.violation("(").is_some());
+        assert!(constraints.violation("").is_some());
+    }
+
     /// FR11: when the coordinator validator returns a TRANSPORT error for a
     /// password question, the surfaced message must reference the question id ONLY —
     /// never the offending secret value, even if the transport error echoes it. The
@@ -18,6 +18,18 @@ pub struct BoolAnswerReq {
     pub answer: bool,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NumberAnswerReq {
+    pub answer: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectAnswerReq {
+    pub answer: serde_json::Map<String, serde_json::Value>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum AnswerReq {
@@ -28,4 +40,10 @@ pub enum AnswerReq {
 
     #[serde(rename = "boolean")]
     Bool(BoolAnswerReq),
+
+    #[serde(rename = "number")]
+    Number(NumberAnswerReq),
+
+    #[serde(rename = "object")]
+    Object(ObjectAnswerReq),
 }
@@ -18,6 +18,18 @@ pub struct BoolAnswerRes {
     pub answer: bool,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NumberAnswerRes {
+    pub answer: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectAnswerRes {
+    pub answer: serde_json::Map<String, serde_json::Value>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum AnswerRes {
@@ -28,4 +40,10 @@ pub enum AnswerRes {
 
     #[serde(rename = "boolean")]
     Bool(BoolAnswerRes),
+
+    #[serde(rename = "number")]
+    Number(NumberAnswerRes),
+
+    #[serde(rename = "object")]
+    Object(ObjectAnswerRes),
 }
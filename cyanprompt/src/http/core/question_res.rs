@@ -15,6 +15,14 @@ pub enum QuestionRes {
     Text(TextQuestionRes),
     #[serde(rename = "select")]
     Select(SelectQuestionRes),
+    #[serde(rename = "number")]
+    Number(NumberQuestionRes),
+    #[serde(rename = "editor")]
+    Editor(EditorQuestionRes),
+    #[serde(rename = "path")]
+    Path(PathQuestionRes),
+    #[serde(rename = "list")]
+    List(ListQuestionRes),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,8 +50,9 @@ pub struct DateQuestionRes {
 #[serde(rename_all = "camelCase")]
 pub struct CheckboxQuestionRes {
     pub message: String,
-    pub options: Vec<String>,
+    pub options: Vec<QuestionOptionRes>,
     pub desc: Option<String>,
+    pub default: Option<Vec<String>>,
     pub id: String,
 }
 
@@ -53,6 +62,8 @@ pub struct PasswordQuestionRes {
     pub message: String,
     pub desc: Option<String>,
     pub confirmation: Option<bool>,
+    #[serde(flatten)]
+    pub constraints: TextConstraintsRes,
     pub id: String,
 }
 
@@ -63,14 +74,87 @@ pub struct TextQuestionRes {
     pub default: Option<String>,
     pub desc: Option<String>,
     pub initial: Option<String>,
+    #[serde(flatten)]
+    pub constraints: TextConstraintsRes,
     pub id: String,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextConstraintsRes {
+    pub pattern: Option<String>,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    pub charset: Option<String>,
+    pub required: Option<bool>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SelectQuestionRes {
     pub message: String,
     pub desc: Option<String>,
-    pub options: Vec<String>,
+    pub options: Vec<QuestionOptionRes>,
+    pub default: Option<String>,
+    pub id: String,
+}
+
+/// A Select/Checkbox option: either a plain string (its own value and label), or
+/// `{value, label?, description?, disabled?}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum QuestionOptionRes {
+    Plain(String),
+    Detailed(DetailedQuestionOptionRes),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetailedQuestionOptionRes {
+    pub value: String,
+    pub label: Option<String>,
+    pub description: Option<String>,
+    pub disabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NumberQuestionRes {
+    pub message: String,
+    pub desc: Option<String>,
+    pub default: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub step: Option<f64>,
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditorQuestionRes {
+    pub message: String,
+    pub desc: Option<String>,
+    pub default: Option<String>,
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathQuestionRes {
+    pub message: String,
+    pub desc: Option<String>,
+    pub default: Option<String>,
+    pub must_exist: Option<bool>,
+    pub dir: Option<bool>,
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListQuestionRes {
+    pub message: String,
+    pub desc: Option<String>,
+    pub default: Option<Vec<String>>,
+    pub key_value: Option<bool>,
     pub id: String,
 }
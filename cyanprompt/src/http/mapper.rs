@@ -1,20 +1,23 @@
 use chrono::NaiveDate;
-use inquire::{DateSelect, MultiSelect, PasswordDisplayMode};
+use inquire::validator::{ErrorMessage, Validation};
+use inquire::{CustomType, CustomUserError, DateSelect, MultiSelect, PasswordDisplayMode};
 
 use crate::domain::models::answer::Answer;
 use crate::domain::models::cyan::{Cyan, CyanGlob, CyanPlugin, CyanProcessor, GlobType};
-use crate::domain::models::prompt::Prompts;
+use crate::domain::models::prompt::{Choice, ListPrompt, Prompts};
 use crate::domain::models::question::{
-    CheckboxQuestion, ConfirmQuestion, DateQuestion, PasswordQuestion, Question, SelectQuestion,
+    CheckboxQuestion, ConfirmQuestion, DateQuestion, EditorQuestion, ListQuestion, NumberQuestion,
+    PasswordQuestion, PathQuestion, Question, QuestionOption, SelectQuestion, TextConstraints,
     TextQuestion,
 };
 use crate::http::core::answer_req::{
-    AnswerReq, BoolAnswerReq, StringAnswerReq, StringArrayAnswerReq,
+    AnswerReq, BoolAnswerReq, NumberAnswerReq, ObjectAnswerReq, StringAnswerReq,
+    StringArrayAnswerReq,
 };
 use crate::http::core::answer_res::AnswerRes;
 use crate::http::core::cyan_req::{CyanGlobReq, CyanPluginReq, CyanProcessorReq, CyanReq};
 use crate::http::core::cyan_res::{CyanGlobRes, CyanPluginRes, CyanProcessorRes, CyanRes};
-use crate::http::core::question_res::QuestionRes;
+use crate::http::core::question_res::{QuestionOptionRes, QuestionRes, TextConstraintsRes};
 
 pub fn question_mapper(r: &QuestionRes) -> Question {
     match r {
@@ -35,14 +38,16 @@ pub fn question_mapper(r: &QuestionRes) -> Question {
         }),
         QuestionRes::Checkbox(cb) => Question::Checkbox(CheckboxQuestion {
             message: cb.message.clone(),
-            options: cb.options.clone(),
+            options: cb.options.iter().map(option_res_mapper).collect(),
             desc: cb.desc.clone(),
+            default: cb.default.clone(),
             id: cb.id.clone(),
         }),
         QuestionRes::Password(pw) => Question::Password(PasswordQuestion {
             message: pw.message.clone(),
             desc: pw.desc.clone(),
             confirmation: pw.confirmation,
+            constraints: constraints_res_mapper(&pw.constraints),
             id: pw.id.clone(),
         }),
         QuestionRes::Text(text) => Question::Text(TextQuestion {
@@ -50,14 +55,68 @@ pub fn question_mapper(r: &QuestionRes) -> Question {
             default: text.default.clone(),
             desc: text.desc.clone(),
             initial: text.initial.clone(),
+            constraints: constraints_res_mapper(&text.constraints),
             id: text.id.clone(),
         }),
         QuestionRes::Select(s) => Question::Select(SelectQuestion {
             message: s.message.clone(),
             desc: s.desc.clone(),
-            options: s.options.clone(),
+            options: s.options.iter().map(option_res_mapper).collect(),
+            default: s.default.clone(),
             id: s.id.clone(),
         }),
+        QuestionRes::Number(n) => Question::Number(NumberQuestion {
+            message: n.message.clone(),
+            desc: n.desc.clone(),
+            default: n.default,
+            min: n.min,
+            max: n.max,
+            step: n.step,
+            id: n.id.clone(),
+        }),
+        QuestionRes::Editor(e) => Question::Editor(EditorQuestion {
+            message: e.message.clone(),
+            desc: e.desc.clone(),
+            default: e.default.clone(),
+            id: e.id.clone(),
+        }),
+        QuestionRes::Path(p) => Question::Path(PathQuestion {
+            message: p.message.clone(),
+            desc: p.desc.clone(),
+            default: p.default.clone(),
+            must_exist: p.must_exist,
+            dir: p.dir,
+            id: p.id.clone(),
+        }),
+        QuestionRes::List(l) => Question::List(ListQuestion {
+            message: l.message.clone(),
+            desc: l.desc.clone(),
+            default: l.default.clone(),
+            key_value: l.key_value,
+            id: l.id.clone(),
+        }),
+    }
+}
+
+pub fn constraints_res_mapper(r: &TextConstraintsRes) -> TextConstraints {
+    TextConstraints {
+        pattern: r.pattern.clone(),
+        min_length: r.min_length,
+        max_length: r.max_length,
+        charset: r.charset.clone(),
+        required: r.required,
+    }
+}
+
+pub fn option_res_mapper(r: &QuestionOptionRes) -> QuestionOption {
+    match r {
+        QuestionOptionRes::Plain(value) => QuestionOption::from(value.as_str()),
+        QuestionOptionRes::Detailed(o) => QuestionOption {
+            value: o.value.clone(),
+            label: o.label.clone().unwrap_or_else(|| o.value.clone()),
+            description: o.description.clone(),
+            disabled: o.disabled.unwrap_or(false),
+        },
     }
 }
 
@@ -66,6 +125,8 @@ pub fn ans_res_mapper(r: &AnswerRes) -> Answer {
         AnswerRes::StringArray(sa) => Answer::StringArray(sa.answer.clone()),
         AnswerRes::String(s) => Answer::String(s.answer.clone()),
         AnswerRes::Bool(b) => Answer::Bool(b.answer),
+        AnswerRes::Number(n) => Answer::Number(n.answer),
+        AnswerRes::Object(o) => Answer::Object(o.answer.clone()),
     }
 }
 
@@ -76,10 +137,12 @@ pub fn ans_req_mapper(a: &Answer) -> AnswerReq {
             AnswerReq::StringArray(StringArrayAnswerReq { answer: sa.clone() })
         }
         Answer::Bool(b) => AnswerReq::Bool(BoolAnswerReq { answer: *b }),
+        Answer::Number(n) => AnswerReq::Number(NumberAnswerReq { answer: *n }),
+        Answer::Object(o) => AnswerReq::Object(ObjectAnswerReq { answer: o.clone() }),
     }
 }
 
-pub fn prompt_mapper(q: &Question) -> Result<Prompts, Box<dyn std::error::Error + Send>> {
+pub fn prompt_mapper(q: &Question) -> Result<Prompts<'_>, Box<dyn std::error::Error + Send>> {
     match q {
         Question::Confirm(c) => Ok(inquire::Confirm::new(&c.message))
             .map(|p| c.default.map_or(p.clone(), |def| p.with_default(def)))
@@ -122,13 +185,24 @@ pub fn prompt_mapper(q: &Question) -> Result<Prompts, Box<dyn std::error::Error
                 })
             })
             .map(Prompts::Date),
-        Question::Checkbox(cb) => Ok(MultiSelect::new(&cb.message, cb.options.clone()))
-            .map(|p| {
-                cb.desc
-                    .as_ref()
-                    .map_or(p.clone(), |desc| p.with_help_message(desc))
-            })
-            .map(Prompts::Checkbox),
+        Question::Checkbox(cb) => {
+            let choices = choices(&cb.options);
+            let checked: Vec<usize> = cb.default.as_ref().map_or(Vec::new(), |values| {
+                choices
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, c)| values.contains(&c.value))
+                    .map(|(i, _)| i)
+                    .collect()
+            });
+            Ok(MultiSelect::new(&cb.message, choices))
+                .map(|p| {
+                    cb.desc
+                        .as_ref()
+                        .map_or(p.clone(), |desc| p.with_help_message(desc))
+                })
+                .map(|p| Prompts::Checkbox(p, checked))
+        }
         Question::Password(pw) => {
             Ok(inquire::Password::new(&pw.message).with_display_mode(PasswordDisplayMode::Masked))
                 .map(|p| {
@@ -145,6 +219,7 @@ pub fn prompt_mapper(q: &Question) -> Result<Prompts, Box<dyn std::error::Error
                         }
                     })
                 })
+                .map(|p| p.with_validator(constraints_validator(&pw.constraints)))
                 .map(Prompts::Password)
         }
         Question::Text(text) => Ok(inquire::Text::new(&text.message))
@@ -163,14 +238,124 @@ pub fn prompt_mapper(q: &Question) -> Result<Prompts, Box<dyn std::error::Error
                     .as_ref()
                     .map_or(p.clone(), |init| p.with_initial_value(init))
             })
+            .map(|p| p.with_validator(constraints_validator(&text.constraints)))
             .map(Prompts::Text),
-        Question::Select(s) => Ok(inquire::Select::new(&s.message, s.options.clone()))
+        Question::Select(s) => {
+            let choices = choices(&s.options);
+            let cursor = s
+                .default
+                .as_ref()
+                .and_then(|def| choices.iter().position(|c| &c.value == def));
+            Ok(inquire::Select::new(&s.message, choices))
+                .map(|p| {
+                    s.desc
+                        .as_ref()
+                        .map_or(p.clone(), |desc| p.with_help_message(desc))
+                })
+                .map(|p| cursor.map_or(p.clone(), |i| p.with_starting_cursor(i)))
+                .map(Prompts::Select)
+        }
+        Question::Number(n) => Ok(CustomType::<f64>::new(&n.message))
+            .map(|p| p.with_error_message("Please type a valid number"))
             .map(|p| {
-                s.desc
+                n.desc
                     .as_ref()
                     .map_or(p.clone(), |desc| p.with_help_message(desc))
             })
-            .map(Prompts::Select),
+            .map(|p| n.default.map_or(p.clone(), |def| p.with_default(def)))
+            .map(|p| {
+                let n = n.clone();
+                p.with_validator(move |v: &f64| {
+                    Ok(if n.accepts(*v) {
+                        Validation::Valid
+                    } else {
+                        Validation::Invalid(ErrorMessage::Custom(number_hint(&n)))
+                    })
+                })
+            })
+            .map(Prompts::Number),
+        Question::Editor(e) => Ok(inquire::Editor::new(&e.message))
+            .map(|p| {
+                e.desc
+                    .as_ref()
+                    .map_or(p.clone(), |desc| p.with_help_message(desc))
+            })
+            .map(|p| {
+                e.default
+                    .as_ref()
+                    .map_or(p.clone(), |def| p.with_predefined_text(def))
+            })
+            .map(Prompts::Editor),
+        Question::Path(path) => Ok(inquire::Text::new(&path.message))
+            .map(|p| {
+                path.desc
+                    .as_ref()
+                    .map_or(p.clone(), |desc| p.with_help_message(desc))
+            })
+            .map(|p| {
+                path.default
+                    .as_ref()
+                    .map_or(p.clone(), |def| p.with_default(def))
+            })
+            .map(|p| {
+                let path = path.clone();
+                p.with_validator(move |v: &str| {
+                    Ok(path.violation(v).map_or(Validation::Valid, |msg| {
+                        Validation::Invalid(ErrorMessage::Custom(msg.to_string()))
+                    }))
+                })
+            })
+            .map(Prompts::Text),
+        Question::List(l) => Ok(Prompts::List(ListPrompt {
+            message: &l.message,
+            help_message: l.desc.as_deref(),
+            default: l.default.clone(),
+            key_value: l.is_key_value(),
+        })),
+    }
+}
+
+/// An inquire validator enforcing a Text/Password question's declared constraints.
+fn constraints_validator(
+    constraints: &TextConstraints,
+) -> impl Fn(&str) -> Result<Validation, CustomUserError> + Clone + 'static {
+    let constraints = constraints.clone();
+    move |v: &str| {
+        Ok(constraints.violation(v).map_or(Validation::Valid, |msg| {
+            Validation::Invalid(ErrorMessage::Custom(msg))
+        }))
+    }
+}
+
+/// The pickable (non-disabled) options, in order.
+fn choices(options: &[QuestionOption]) -> Vec<Choice> {
+    options
+        .iter()
+        .filter(|o| !o.disabled)
+        .map(|o| Choice {
+            value: o.value.clone(),
+            label: o.label.clone(),
+            description: o.description.clone(),
+        })
+        .collect()
+}
+
+/// The constraints a rejected [`NumberQuestion`] answer failed, for the prompt error.
+fn number_hint(n: &NumberQuestion) -> String {
+    let mut parts = Vec::new();
+    if let Some(min) = n.min {
+        parts.push(format!(">= {min}"));
+    }
+    if let Some(max) = n.max {
+        parts.push(format!("<= {max}"));
+    }
+    if let Some(step) = n.step {
+        parts.push(format!("in steps of {step} from {}", n.min.unwrap_or(0.0)));
+    }
+    if parts.is_empty() {
+        "Please type a valid number".to_string()
+    } else {
+        format!("Number must be {}", parts.join(", "))
     }
 }
 
@@ -266,3 +451,39 @@ pub fn cyan_req_mapper(r: Cyan) -> CyanReq {
             .collect(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Plain-string options (the pre-existing form) still parse, alongside objects.
+    #[test]
+    fn select_options_parse_plain_and_detailed_forms() {
+        let raw = r#"{
+            "type": "select",
+            "message": "Database?",
+            "id": "db",
+            "default": "pg",
+            "options": [
+                "sqlite",
+                {"value": "pg", "label": "PostgreSQL", "description": "recommended"},
+                {"value": "my", "disabled": true}
+            ]
+        }"#;
+        let res: QuestionRes = serde_json::from_str(raw).unwrap();
+        let Question::Select(q) = question_mapper(&res) else {
+            panic!("expected a Select question");
+        };
+        assert_eq!(q.default.as_deref(), Some("pg"));
+        assert_eq!(q.options[0], QuestionOption::from("sqlite"));
+        assert_eq!(q.options[1].label, "PostgreSQL");
+        assert_eq!(q.options[1].description.as_deref(), Some("recommended"));
+        assert_eq!(q.options[2].label, "my");
+        assert!(q.options[2].disabled);
+        assert_eq!(
+            choices(&q.options).len(),
+            2,
+            "disabled options are not offered"
+        );
+    }
+}
@@ -40,8 +40,8 @@ impl CyanRegistryClient {
         token: Option<String>,
     ) -> Result<Response, reqwest::Error> {
         let mut req = self.client.post(endpoint).json(r);
-        if token.is_some() {
-            req = req.header("X-API-TOKEN", token.unwrap().as_str())
+        if let Some(token) = token {
+            req = req.header("X-API-TOKEN", token.as_str())
         }
         req.send()
     }